@@ -1,15 +1,38 @@
-pub mod client;
 pub mod server;
 pub mod shared;
 
-use bevy::prelude::*;
-use client::AppClientPlugin;
-use server::AppServerPlugin;
-use shared::AppSharedPlugin;
+use bevy::{prelude::*, render::texture::Image};
+use bevy_mod_picking::backend::PointerHits;
+use lightyear::prelude::Mode;
+use server::{AppServerPlugin, ServerSettings};
+use shared::{prelude::RdioServerState, SharedEditionPlugin};
+use std::net::SocketAddr;
 
-pub struct ApplicationPlugin;
-impl Plugin for ApplicationPlugin {
+/// Server only, no window, UI or camera. Go online on startup
+pub struct DedicatedServerPlugin {
+	pub addr: SocketAddr,
+	pub settings: ServerSettings,
+}
+
+impl Plugin for DedicatedServerPlugin {
 	fn build(&self, app: &mut App) {
-		app.add_plugins((AppClientPlugin, AppServerPlugin, AppSharedPlugin));
+		app.init_asset::<Image>()
+			.add_event::<PointerHits>()
+			.insert_resource(self.settings.clone())
+			.init_state::<RdioServerState>()
+			.enable_state_scoped_entities::<RdioServerState>()
+			.add_plugins((
+				AppServerPlugin {
+					mode: Mode::Separate,
+					addr: self.addr,
+				},
+				SharedEditionPlugin,
+			))
+			.add_systems(
+				Startup,
+				|mut next_state: ResMut<NextState<RdioServerState>>| {
+					next_state.set(RdioServerState::Online);
+				},
+			);
 	}
 }
@@ -0,0 +1,161 @@
+use std::{
+	fs,
+	path::{Path, PathBuf},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{source_to_docs, TEMPORARY_DIR};
+use bevy::{prelude::*, tasks::IoTaskPool};
+
+use super::*;
+
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(60);
+/// Oldest snapshot removed beyond this
+const MAX_AUTOSAVE: usize = 5;
+const AUTOSAVE_PREFIX: &str = "Autosave-";
+
+#[derive(Resource)]
+pub(super) struct AutosaveTimer {
+	timer: Timer,
+	/// Board edited since last snapshot
+	is_dirty: bool,
+}
+
+impl Default for AutosaveTimer {
+	fn default() -> Self {
+		Self {
+			timer: Timer::new(AUTOSAVE_INTERVAL, TimerMode::Repeating),
+			is_dirty: false,
+		}
+	}
+}
+
+fn autosave_dir() -> Option<PathBuf> {
+	source_to_docs().map(|docs_dir| docs_dir.join(TEMPORARY_DIR))
+}
+
+/// Oldest first, name carry padded time so sorting by name is enough
+fn autosave_files(dir: &Path) -> Vec<PathBuf> {
+	let Ok(read_dir) = fs::read_dir(dir) else {
+		return Vec::new();
+	};
+	let mut files = read_dir
+		.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+		.filter(|path| {
+			path.extension().is_some_and(|ext| ext == RDIO_EXTENSION)
+				&& path
+					.file_name()
+					.and_then(|name| name.to_str())
+					.is_some_and(|name| name.starts_with(AUTOSAVE_PREFIX))
+		})
+		.collect::<Vec<_>>();
+	files.sort();
+	files
+}
+
+fn write_snapshot(dir: &Path, bytes: &[u8]) {
+	let millis = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.unwrap_or_default()
+		.as_millis();
+	let path = dir.join(format!("{AUTOSAVE_PREFIX}{millis:020}.{RDIO_EXTENSION}"));
+	// Half written file never look like snapshot
+	let temp_path = path.with_extension("tmp");
+	// Dedicated server never made document folder
+	if let Err(e) = fs::create_dir_all(dir)
+		.and_then(|_| fs::write(&temp_path, bytes))
+		.and_then(|_| fs::rename(&temp_path, &path))
+	{
+		error!("Failed to autosave: {:?}", e);
+		return;
+	}
+	let files = autosave_files(dir);
+	let excess = files.len().saturating_sub(MAX_AUTOSAVE);
+	files.iter().take(excess).for_each(|old_path| {
+		fs::remove_file(old_path).unwrap_or_else(|e| warn!("Failed to remove autosave: {:?}", e));
+	});
+}
+
+/// Image compression and encoding are heavy, never on main thread
+fn encode_and_write(dir: &Path, mut saved_rdio: SavedRdio) {
+	saved_rdio.compress_images();
+	match saved_rdio.to_bytes() {
+		Ok(bytes) => write_snapshot(dir, &bytes),
+		Err(e) => error!("Failed to encode autosave: {:?}", e),
+	}
+}
+
+/// Skip idle or empty board, so older useful snapshots are kept
+pub(super) fn autosave_board(
+	mut autosave_timer: ResMut<AutosaveTimer>,
+	histories: Res<Histories>,
+	rdio_param: RdioParam,
+	time: Res<Time>,
+) {
+	// Every edit, undo and open pass through history
+	if histories.is_changed() && !histories.is_added() {
+		autosave_timer.is_dirty = true;
+	}
+	if !autosave_timer.timer.tick(time.delta()).just_finished() || !autosave_timer.is_dirty {
+		return;
+	}
+	autosave_timer.is_dirty = false;
+	let saved_rdio = rdio_param.collect_raw();
+	if saved_rdio.objects.is_empty() {
+		return;
+	}
+	let Some(dir) = autosave_dir() else {
+		return;
+	};
+	IoTaskPool::get()
+		.spawn(async move { encode_and_write(&dir, saved_rdio) })
+		.detach();
+}
+
+/// Last snapshot once server close or app exit is requested, before objects are despawned
+pub(super) fn autosave_on_close(
+	next_state: Res<NextState<RdioServerState>>,
+	mut app_exit: EventReader<AppExit>,
+	rdio_param: RdioParam,
+) {
+	let is_closing = matches!(*next_state, NextState::Pending(RdioServerState::Offline));
+	if !is_closing && app_exit.is_empty() {
+		return;
+	}
+	app_exit.clear();
+	let saved_rdio = rdio_param.collect_raw();
+	if saved_rdio.objects.is_empty() {
+		return;
+	}
+	let Some(dir) = autosave_dir() else {
+		return;
+	};
+	// Wait for it, task pool may stop with the app
+	encode_and_write(&dir, saved_rdio);
+}
+
+/// Newest readable snapshot, broken one fall back to older
+pub(super) fn restore_autosave(mut cmd: Commands) {
+	cmd.insert_resource(AutosaveTimer::default());
+	let Some(dir) = autosave_dir() else {
+		return;
+	};
+	let op_saved_rdio = autosave_files(&dir).iter().rev().find_map(|path| {
+		let bytes = fs::read(path).ok()?;
+		SavedRdio::from_bytes(&bytes)
+			.map_err(|e| warn!("Skip broken autosave {:?}: {:?}", path, e))
+			.ok()
+	});
+	let Some(saved_rdio) = op_saved_rdio else {
+		return;
+	};
+	if saved_rdio.objects.is_empty() {
+		return;
+	}
+	let count = saved_rdio.objects.len();
+	saved_rdio.spawn_objects(&mut cmd, UserId::default().0);
+	info!("Restored {} objects from autosave", count);
+	cmd.trigger(DisplayMsgEvent(format!(
+		"Restored {count} objects from autosave."
+	)));
+}
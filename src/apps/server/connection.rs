@@ -4,18 +4,29 @@ use bevy::prelude::*;
 use bevy::tasks::futures_lite::future;
 use bevy::tasks::{block_on, IoTaskPool};
 use lightyear::prelude::{ClientId::Netcode, *};
+use local_ip_address::local_ip;
 use server::*;
-use std::net::SocketAddr;
+use std::collections::HashSet;
+use std::net::{IpAddr, SocketAddr};
+use std::ops::DerefMut;
 use std::sync::{Arc, RwLock};
-use tokio::io::AsyncWriteExt;
+use std::time::{Duration, Instant};
+use tokio::{
+	io::{AsyncReadExt, AsyncWriteExt},
+	net::TcpStream,
+	time::timeout,
+};
 
-use crate::apps::shared::shared_config;
+use crate::apps::shared::{shared_config, PROTOCOL_ID};
 
 use super::*;
 
-pub(super) fn build_server_plugin() -> ServerPlugins {
+/// Peer silent for longer is dropped, so it can't hold its task forever
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub(super) fn build_server_plugin(mode: Mode, addr: SocketAddr) -> ServerPlugins {
 	let io = IoConfig {
-		transport: ServerTransport::UdpSocket(SERVER_ADDR),
+		transport: ServerTransport::UdpSocket(addr),
 		..default()
 	};
 	let net_config = NetConfig::Netcode {
@@ -23,7 +34,10 @@ pub(super) fn build_server_plugin() -> ServerPlugins {
 		io,
 	};
 	let config = ServerConfig {
-		shared: shared_config(),
+		shared: SharedConfig {
+			mode,
+			..shared_config()
+		},
 		net: vec![net_config],
 		replication: ReplicationConfig {
 			send_updates_mode: SendUpdatesMode::SinceLastSend,
@@ -36,21 +50,32 @@ pub(super) fn build_server_plugin() -> ServerPlugins {
 
 pub(super) fn start_server(
 	mut cmd: Commands,
-	server_config: Res<ServerConfig>,
+	mut server_config: ResMut<ServerConfig>,
+	server_settings: Res<ServerSettings>,
 	backend_task_server: Res<BackendTaskServer>,
 ) {
-	let server::NetConfig::Netcode { io, .. } = &server_config.net[0];
+	let private_key = server_settings.private_key.unwrap_or_else(generate_key);
+	let server::NetConfig::Netcode { config, io } = &mut server_config.net[0];
+	config.protocol_id = PROTOCOL_ID;
+	config.private_key = private_key;
 	let ServerTransport::UdpSocket(socker) = &io.transport else {
 		unreachable!();
 	};
 	let mut back_end_socket = *socker;
-	back_end_socket.set_port(SERVER_ADDR_BACKEND.port());
+	back_end_socket.set_port(server_settings.backend_port);
+	// Token must carry address reachable by client
+	let mut game_socket = *socker;
+	if game_socket.ip().is_unspecified() {
+		if let Ok(ip) = local_ip() {
+			game_socket.set_ip(ip);
+		}
+	}
 	backend_task_server.write().unwrap().inactive = false;
 	start_netcode_authentication_task(
-		*socker,
+		game_socket,
 		back_end_socket,
-		0,
-		[0; PRIVATE_KEY_BYTES],
+		PROTOCOL_ID,
+		private_key,
 		backend_task_server.clone(),
 	);
 
@@ -67,38 +92,228 @@ pub(super) fn stop_connection(mut cmd: Commands, backend_task_server: Res<Backen
 pub(super) fn handle_connect_events(
 	trigger: Trigger<ConnectEvent>,
 	client_ids: Res<BackendTaskServer>,
+	mut server: ResMut<ConnectionManager>,
+	mut histories: ResMut<Histories>,
+	mut parked: ResMut<ParkedClients>,
+	query_object: Query<(&ReplicationTarget, Has<ObjectImage>), With<ObjectWorld>>,
+	mut query_resume: Query<(
+		Option<&mut ObjectOwner>,
+		Option<&mut ObjectAccess>,
+		Option<&mut MoveLock>,
+		Option<&mut PixelLock>,
+		Option<&mut AlphaLock>,
+		Option<&mut InUse>,
+	)>,
+) {
+	let raw_client_id = trigger.event().client_id;
+	let Netcode(client_id) = raw_client_id else {
+		return;
+	};
+	let op_old_client_id = {
+		let mut backend = client_ids.write().unwrap();
+		backend.clients.insert(client_id);
+		backend
+			.resumes
+			.values_mut()
+			.filter(|(resume_id, ..)| resume_id == &client_id)
+			.for_each(|(.., expire)| *expire = None);
+		backend.resumed.remove(&client_id)
+	};
+
+	// Resumed session take back what old id had
+	if let Some(old_client_id) = op_old_client_id {
+		let old_client_id = Netcode(old_client_id);
+		parked.remove(&old_client_id);
+		if let Some(history) = histories.remove(&old_client_id) {
+			histories.insert(raw_client_id, history);
+		}
+		query_resume.iter_mut().for_each(
+			|(op_owner, op_access, op_move_lock, op_pixel_lock, op_alpha_lock, op_in_use)| {
+				if let Some(mut owner) = op_owner.filter(|owner| owner.0 == old_client_id) {
+					owner.0 = raw_client_id;
+				}
+				if let Some(mut access) = op_access {
+					let mut replaced = access.0.clone();
+					replaced.replace_id(&old_client_id, raw_client_id);
+					access.set_if_neq(ObjectAccess(replaced));
+				}
+				if let Some(move_lock) = op_move_lock {
+					swap_client_id(move_lock, &old_client_id, raw_client_id);
+				}
+				if let Some(pixel_lock) = op_pixel_lock {
+					swap_client_id(pixel_lock, &old_client_id, raw_client_id);
+				}
+				if let Some(alpha_lock) = op_alpha_lock {
+					swap_client_id(alpha_lock, &old_client_id, raw_client_id);
+				}
+				if let Some(in_use) = op_in_use {
+					swap_client_id(in_use, &old_client_id, raw_client_id);
+				}
+			},
+		);
+	}
+
+	let (objects, images) = query_object
+		.iter()
+		.filter(|(rep_target, _)| rep_target.target.targets(&raw_client_id))
+		.fold((0, 0), |(objects, images), (_, is_img)| {
+			(objects + 1, images + is_img as u32)
+		});
+	server
+		.send_message_to_target::<MainChannel, JoinManifest>(
+			&mut JoinManifest { objects, images },
+			NetworkTarget::Single(raw_client_id),
+		)
+		.unwrap_or_else(|e| {
+			error!("Fail to send message: {:?}", e);
+		});
+}
+
+/// Only touch when old id inside, avoid replicating untouched objects
+fn swap_client_id<T: DerefMut<Target = HashSet<ClientId>>>(
+	mut set: Mut<T>,
+	old_client_id: &ClientId,
+	new_client_id: ClientId,
 ) {
-	if let Netcode(client_id) = trigger.event().client_id {
-		client_ids.write().unwrap().clients.insert(client_id);
+	if set.contains(old_client_id) {
+		set.remove(old_client_id);
+		set.insert(new_client_id);
 	}
 }
 
-// pub(super) fn handle_disconnect_events(
-// 	trigger: Trigger<DisconnectEvent>,
-// 	client_ids: Res<BackendTaskServer>,
-// 	query_user: Query<&SharingName>,
-// 	mut server: ResMut<ConnectionManager>,
-// 	mut users: ResMut<Users>,
-// 	mut cmd: Commands,
-// ) {
-// 	let raw_client_id = trigger.event().client_id;
-// 	let ent_user = users.get(&raw_client_id).unwrap();
-// 	cmd.entity(*ent_user).despawn_recursive();
-// 	users.remove(&raw_client_id);
-// 	if let Netcode(client_id) = raw_client_id {
-// 		client_ids.write().unwrap().clients.remove(&client_id);
-// 		let name = query_user.get(trigger.entity()).unwrap();
-// 		let msg = format!("{} left the server.", name.0);
-// 		server
-// 			.send_message_to_target::<MessageChannel, MessageCtx>(
-// 				&mut MessageCtx(msg),
-// 				NetworkTarget::AllExceptSingle(raw_client_id),
-// 			)
-// 			.unwrap_or_else(|e| {
-// 				error!("Fail to send message: {:?}", e);
-// 			});
-// 	}
-// }
+pub(super) fn handle_disconnect_events(
+	trigger: Trigger<DisconnectEvent>,
+	mut cmd: Commands,
+	client_ids: Res<BackendTaskServer>,
+	query_user: Query<&SharingName, With<UserId>>,
+	mut server: ResMut<ConnectionManager>,
+	mut users: ResMut<Users>,
+	mut parked: ResMut<ParkedClients>,
+) {
+	let raw_client_id = trigger.event().client_id;
+	let can_resume = match raw_client_id {
+		Netcode(client_id) => {
+			let mut backend = client_ids.write().unwrap();
+			backend.clients.remove(&client_id);
+			backend.addresses.remove(&client_id);
+			backend.admins.remove(&client_id);
+			// Token stay valid a while, what user held wait for it
+			let expire = Instant::now() + RESUME_TIMEOUT;
+			let mut can_resume = false;
+			backend
+				.resumes
+				.values_mut()
+				.filter(|(resume_id, ..)| resume_id == &client_id)
+				.for_each(|(.., op_expire)| {
+					*op_expire = Some(expire);
+					can_resume = true;
+				});
+			can_resume
+		},
+		_ => false,
+	};
+	if can_resume {
+		parked.insert(raw_client_id, Instant::now() + RESUME_TIMEOUT);
+	} else {
+		cmd.trigger(ReleaseClient(raw_client_id));
+	}
+
+	let Some(ent_user) = users.remove(&raw_client_id) else {
+		return;
+	};
+	let name = query_user
+		.get(ent_user)
+		.map(|name| name.0.clone())
+		.unwrap_or_else(|_| "User".to_owned());
+	if let Some(ent_cmd) = cmd.get_entity(ent_user) {
+		ent_cmd.despawn_recursive();
+	}
+
+	let msg = format!("{} left the server.", name);
+	cmd.trigger(DisplayMsgEvent(msg.clone()));
+	server
+		.send_message_to_target::<MessageChannel, MessageCtx>(
+			&mut MessageCtx(msg),
+			NetworkTarget::AllExceptSingle(raw_client_id),
+		)
+		.unwrap_or_else(|e| {
+			error!("Fail to send message: {:?}", e);
+		});
+}
+
+/// Drop expired resume tokens, parked sessions past deadline get released
+pub(super) fn expire_resumes(
+	mut cmd: Commands,
+	client_ids: Res<BackendTaskServer>,
+	mut parked: ResMut<ParkedClients>,
+) {
+	let now = Instant::now();
+	{
+		let mut backend = client_ids.write().unwrap();
+		let mut expired = Vec::new();
+		backend.resumes.retain(|_, (resume_id, _, op_expire)| {
+			let is_valid = op_expire.map_or(true, |expire| expire > now);
+			if !is_valid {
+				expired.push(*resume_id);
+			}
+			is_valid
+		});
+		// Token issued for resume but never connected
+		expired.iter().for_each(|resume_id| {
+			backend.resumed.remove(resume_id);
+		});
+	}
+	parked.retain(|client_id, deadline| {
+		if *deadline > now {
+			return true;
+		}
+		cmd.trigger(ReleaseClient(*client_id));
+		false
+	});
+}
+
+pub(super) fn release_client(
+	trigger: Trigger<ReleaseClient>,
+	server_settings: Res<ServerSettings>,
+	users: Res<Users>,
+	query_host: Query<&UserId, With<UserAdmin>>,
+	mut query_object: Query<(&mut ObjectOwner, &mut ObjectAccess), With<ObjectWorld>>,
+	mut query_in_use: Query<&mut InUse>,
+	mut histories: ResMut<Histories>,
+) {
+	let raw_client_id = trigger.event().0;
+	histories.remove(&raw_client_id);
+	query_in_use.iter_mut().for_each(|mut in_use| {
+		if in_use.contains(&raw_client_id) {
+			in_use.remove(&raw_client_id);
+		}
+	});
+
+	if server_settings.owner_policy == OwnerPolicy::TransferToHost {
+		// Host first, any remaining admin on dedicated server
+		let op_heir = users
+			.keys()
+			.find(|client_id| matches!(client_id, ClientId::Local(_)))
+			.copied()
+			.or_else(|| {
+				query_host
+					.iter()
+					.map(|user_id| user_id.0)
+					.find(|client_id| client_id != &raw_client_id)
+			});
+		if let Some(heir) = op_heir {
+			query_object
+				.iter_mut()
+				.filter(|(owner, _)| owner.0 == raw_client_id)
+				.for_each(|(mut owner, mut access)| {
+					owner.0 = heir;
+					if let NetworkTarget::Only(_) = access.0 {
+						access.add_id(heir);
+					}
+				});
+		}
+	}
+}
 
 pub(super) fn start_netcode_authentication_task(
 	game_server_addr: SocketAddr,
@@ -129,34 +344,119 @@ pub(super) fn start_netcode_authentication_task(
 				let Some(accepted) = block_on(future::poll_once(listener.accept())) else {
 					continue;
 				};
-				let (mut stream, _) = accepted.unwrap();
-
-				info!("Authing User");
-				let client_id = loop {
-					let client_id = rand::random();
-					if !backend_runner.read().unwrap().clients.contains(&client_id) {
-						break client_id;
-					}
+				let Ok((stream, peer)) = accepted else {
+					continue;
 				};
-
-				let token =
-					ConnectToken::build(game_server_addr, protocol_id, client_id, private_key)
-						.generate()
-						.expect("Failed to generate token");
-
-				let serialized_token = token.try_into_bytes().expect("Failed to serialize token");
-				trace!(
-					"Sending token {:?} to client {}, Token len: {}",
-					serialized_token,
-					client_id,
-					serialized_token.len()
-				);
-				stream
-					.write_all(&serialized_token)
-					.await
-					.expect("Failed to send token to client");
+				// Each handshake on its own, slow client won't block others
+				IoTaskPool::get()
+					.spawn(Compat::new(authorize_client(
+						stream,
+						peer.ip(),
+						game_server_addr,
+						protocol_id,
+						private_key,
+						backend_runner.clone(),
+					)))
+					.detach();
 			}
 			info!("Stopped listening: {:?}", listener.local_addr());
 		}))
 		.detach();
 }
+
+/// Protocol id | Password length (u8) | Password | Resume token
+async fn read_handshake(stream: &mut TcpStream) -> Option<(u64, String, u128)> {
+	let client_protocol_id = stream.read_u64_le().await.ok()?;
+	let len = stream.read_u8().await.ok()?;
+	let mut password = vec![0; len as usize];
+	stream.read_exact(&mut password).await.ok()?;
+	let password = String::from_utf8_lossy(&password).into_owned();
+	let resume = stream.read_u128_le().await.ok()?;
+	Some((client_protocol_id, password, resume))
+}
+
+async fn authorize_client(
+	mut stream: TcpStream,
+	ip: IpAddr,
+	game_server_addr: SocketAddr,
+	protocol_id: u64,
+	private_key: Key,
+	backend_runner: Arc<RwLock<BackendItem>>,
+) {
+	info!("Authing User");
+	let Ok(op_handshake) = timeout(HANDSHAKE_TIMEOUT, read_handshake(&mut stream)).await else {
+		info!("Handshake timed out: {}", ip);
+		return;
+	};
+	let Some((client_protocol_id, password, resume)) = op_handshake else {
+		return;
+	};
+	let (reply, is_admin) = if client_protocol_id != protocol_id {
+		(AuthReply::VersionMismatch, false)
+	} else {
+		backend_runner.read().unwrap().authorize(&ip, &password)
+	};
+	if let Err(e) = stream.write_u8(reply.to_byte()).await {
+		warn!("Failed to reply client: {:?}", e);
+		return;
+	}
+	if reply != AuthReply::Accepted {
+		info!("Rejected {}: {:?}", ip, reply);
+		return;
+	}
+
+	let (client_id, new_resume) = {
+		let mut backend = backend_runner.write().unwrap();
+		let client_id = loop {
+			let client_id = rand::random();
+			if !backend.clients.contains(&client_id) && !backend.addresses.contains_key(&client_id)
+			{
+				break client_id;
+			}
+		};
+		let mut is_admin = is_admin;
+		let now = Instant::now();
+		if let Some((old_client_id, was_admin, op_expire)) = backend.resumes.remove(&resume) {
+			if op_expire.map_or(true, |expire| expire > now) {
+				backend.resumed.insert(client_id, old_client_id);
+				is_admin |= was_admin;
+			}
+		}
+		let new_resume = loop {
+			let new_resume: u128 = rand::random();
+			if new_resume != 0 && !backend.resumes.contains_key(&new_resume) {
+				break new_resume;
+			}
+		};
+		// Expire unless client connect with it
+		backend.resumes.insert(
+			new_resume,
+			(client_id, is_admin, Some(now + RESUME_TIMEOUT)),
+		);
+		backend.addresses.insert(client_id, ip);
+		if is_admin {
+			backend.admins.insert(client_id);
+		}
+		(client_id, new_resume)
+	};
+
+	let token = ConnectToken::build(game_server_addr, protocol_id, client_id, private_key)
+		.generate()
+		.expect("Failed to generate token");
+
+	let serialized_token = token.try_into_bytes().expect("Failed to serialize token");
+	trace!(
+		"Sending token {:?} to client {}, Token len: {}",
+		serialized_token,
+		client_id,
+		serialized_token.len()
+	);
+	stream
+		.write_all(&serialized_token)
+		.await
+		.unwrap_or_else(|e| warn!("Failed to send token to client: {:?}", e));
+	stream
+		.write_u128_le(new_resume)
+		.await
+		.unwrap_or_else(|e| warn!("Failed to send resume to client: {:?}", e));
+}
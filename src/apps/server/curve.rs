@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+
+use bevy::ecs::system::SystemState;
+
+use super::*;
+
+pub(super) fn edit_curve(
+	mut cmd: Commands,
+	mut events: EventReader<MessageEvent<CurveEditNet>>,
+	query_user: Query<&SelectedObject>,
+	users: Res<Users>,
+) {
+	events.read().for_each(|event| {
+		let client_id = *event.context();
+		let edit = *event.message();
+		let selected = users
+			.get(&client_id)
+			.and_then(|ent_user| query_user.get(*ent_user).ok())
+			.map(|selected_obj| selected_obj.group.iter().copied().collect::<Vec<_>>())
+			.unwrap_or_default();
+		cmd.add(move |world: &mut World| match edit {
+			CurveEditNet::Handle { ent, index, to } => {
+				drag_handle(world, client_id, ent, index, to)
+			},
+			CurveEditNet::Convert(kind) => convert_points(world, client_id, selected, kind),
+			CurveEditNet::Node(mode) => set_node_mode(world, client_id, selected, mode),
+		});
+	});
+}
+
+/// Cubic handles beside moved points move along
+pub(super) fn follow_handles(
+	world: &mut World,
+	client_id: ClientId,
+	moved: Vec<Entity>,
+	delta: Vec2,
+) {
+	let (_, next) = neighbours(world, &moved);
+	let mut shifted = HashMap::<Entity, (PointType, PointType)>::new();
+	moved
+		.iter()
+		.map(|ent| (*ent, 1))
+		.chain(moved.iter().filter_map(|ent| Some((*next.get(ent)?, 0))))
+		.for_each(|(ent, index)| {
+			let Some(point_type) = world.get::<PointType>(ent) else {
+				return;
+			};
+			let (_, after) = shifted
+				.entry(ent)
+				.or_insert_with(|| (point_type.clone(), point_type.clone()));
+			if let PointType::CubricBezier { ctrl1, ctrl2, to } = after {
+				if index == 0 {
+					*ctrl1 += delta;
+				} else {
+					*ctrl2 += delta;
+					*to += delta;
+				}
+			}
+		});
+	shifted.retain(|_, (before, after)| before != after);
+	set_curves(world, client_id, shifted);
+}
+
+/// Move lock of the path holding point or of any group around it, same as moving
+fn is_move_locked(world: &mut World, ent: Entity, client_id: ClientId) -> bool {
+	let ent_obj = world.get::<Parent>(ent).map_or(ent, |parent| parent.get());
+	let ancestors = SystemState::<GroupParam>::new(world)
+		.get(world)
+		.ancestors(ent_obj);
+	std::iter::once(ent_obj)
+		.chain(ancestors)
+		.filter_map(|ent| world.get::<MoveLock>(ent))
+		.any(|move_lock| move_lock.contains(&client_id))
+}
+
+/// Handle move with node mode keeping the other side
+fn drag_handle(world: &mut World, client_id: ClientId, ent: Entity, index: u8, to: Vec2) {
+	if !to.is_finite() || !can_edit(world, ent, client_id) || is_move_locked(world, ent, client_id)
+	{
+		return;
+	}
+	let maxed = Vec2::splat(MAX_VALID_RANGE);
+	let to = to.clamp(-maxed, maxed);
+	let Some(point_type) = world.get::<PointType>(ent).cloned() else {
+		return;
+	};
+	let mut moved = point_type.clone();
+	if !moved.set_handle(index, to) {
+		return;
+	}
+	let (previous, next) = neighbours(world, &[ent]);
+	// Handle near end belong to node of this point, otherwise to node of previous point
+	let (ent_node, opposite) = if index as usize + 1 == point_type.handles().len() {
+		(Some(ent), next.get(&ent).map(|ent_next| (*ent_next, 0)))
+	} else {
+		let ent_previous = previous.get(&ent).copied();
+		let opposite = ent_previous.and_then(|ent_previous| {
+			let last = world
+				.get::<PointType>(ent_previous)?
+				.handles()
+				.len()
+				.checked_sub(1)?;
+			Some((ent_previous, last))
+		});
+		(ent_previous, opposite)
+	};
+
+	let mut changed = HashMap::from([(ent, (point_type, moved))]);
+	let mirrored = ent_node
+		.zip(opposite)
+		.and_then(|(ent_node, (ent_opposite, at))| {
+			let node_type = world.get::<PointType>(ent_node)?;
+			// Arc point sit at its center, not on path
+			if ent_opposite == ent || node_type.kind() == SegmentKind::Arc {
+				return None;
+			}
+			let mode = world.get::<NodeMode>(ent_node)?;
+			let node = world.get::<ObjectPosition>(ent_node)?.0;
+			let opposite_type = world.get::<PointType>(ent_opposite)?.clone();
+			let handle = mode.mirror(node, to, *opposite_type.handles().get(at)?)?;
+			let mut aligned = opposite_type.clone();
+			aligned.set_handle(at as u8, handle);
+			Some((ent_opposite, (opposite_type, aligned)))
+		});
+	changed.extend(mirrored);
+
+	let now = world.resource::<Time>().elapsed();
+	world
+		.resource_mut::<Histories>()
+		.entry(client_id)
+		.or_default()
+		.touch(now);
+	set_curves(world, client_id, changed);
+}
+
+/// Selected points change segment kind, keeping where segment begin and end
+fn convert_points(
+	world: &mut World,
+	client_id: ClientId,
+	selected: Vec<Entity>,
+	kind: SegmentKind,
+) {
+	let points = selected
+		.into_iter()
+		.filter(|ent| world.get::<ObjectPoint>(*ent).is_some())
+		.collect::<Vec<_>>();
+	let (previous, _) = neighbours(world, &points);
+	let mut changes = Vec::new();
+	points.iter().for_each(|ent| {
+		// First point only begin the path
+		let Some(ent_previous) = previous.get(ent) else {
+			return;
+		};
+		let (Some(point_type), Some(position), Some(from)) = (
+			world.get::<PointType>(*ent),
+			world.get::<ObjectPosition>(*ent),
+			segment_end(world, *ent_previous, &previous),
+		) else {
+			return;
+		};
+		if point_type.kind() == kind {
+			return;
+		}
+		let (pos, point_type) = point_type.convert(kind, from, position.0);
+		changes.push(Change::Position { ent: *ent, pos });
+		changes.push(Change::Curve {
+			ent: *ent,
+			point_type,
+		});
+	});
+	apply_changes(world, client_id, changes);
+}
+
+/// Smooth and symmetric line up handle after the node with the one before it
+fn set_node_mode(world: &mut World, client_id: ClientId, selected: Vec<Entity>, mode: NodeMode) {
+	let points = selected
+		.into_iter()
+		.filter(|ent| world.get::<ObjectPoint>(*ent).is_some())
+		.collect::<Vec<_>>();
+	let (_, next) = neighbours(world, &points);
+	let mut changes = Vec::new();
+	points.iter().for_each(|ent| {
+		if world
+			.get::<NodeMode>(*ent)
+			.map_or(true, |current| current == &mode)
+		{
+			return;
+		}
+		changes.push(Change::Node { ent: *ent, mode });
+
+		let (Some(node), Some(point_type), Some(ent_next)) = (
+			world.get::<ObjectPosition>(*ent),
+			world.get::<PointType>(*ent),
+			next.get(ent),
+		) else {
+			return;
+		};
+		let (Some(handle), Some(next_type)) = (
+			point_type.handles().last().copied(),
+			world.get::<PointType>(*ent_next),
+		) else {
+			return;
+		};
+		let Some(aligned) = next_type
+			.handles()
+			.first()
+			.and_then(|opposite| mode.mirror(node.0, handle, *opposite))
+		else {
+			return;
+		};
+		let mut point_type = next_type.clone();
+		point_type.set_handle(0, aligned);
+		changes.push(Change::Curve {
+			ent: *ent_next,
+			point_type,
+		});
+	});
+	apply_changes(world, client_id, changes);
+}
+
+/// Pending step keep curves before first change
+fn set_curves(
+	world: &mut World,
+	client_id: ClientId,
+	changed: HashMap<Entity, (PointType, PointType)>,
+) {
+	{
+		let mut histories = world.resource_mut::<Histories>();
+		let history = histories.entry(client_id).or_default();
+		changed
+			.iter()
+			.for_each(|(ent, (before, _))| history.record_curve(*ent, before));
+	}
+	changed.into_iter().for_each(|(ent, (_, after))| {
+		if let Some(mut point_type) = world.get_mut::<PointType>(ent) {
+			*point_type = after;
+		}
+	});
+}
+
+/// Where path is after segment of point, arc end away from its center
+fn segment_end(world: &World, ent: Entity, previous: &HashMap<Entity, Entity>) -> Option<Vec2> {
+	let position = world.get::<ObjectPosition>(ent)?.0;
+	let point_type = world.get::<PointType>(ent)?;
+	let Some(ent_previous) = previous.get(&ent) else {
+		return Some(position);
+	};
+	if point_type.kind() != SegmentKind::Arc {
+		return Some(position);
+	}
+	let from = segment_end(world, *ent_previous, previous)?;
+	Some(point_type.end(from, position))
+}
+
+/// Previous and next of every point along [PointToPoint], in paths holding `points`
+fn neighbours(
+	world: &mut World,
+	points: &[Entity],
+) -> (HashMap<Entity, Entity>, HashMap<Entity, Entity>) {
+	let paths = points
+		.iter()
+		.filter_map(|ent| world.get::<Parent>(*ent))
+		.map(|parent| parent.get())
+		.collect::<HashSet<_>>();
+	let mut state = SystemState::<PathParam>::new(world);
+	let path_param = state.get(world);
+	let mut previous = HashMap::new();
+	let mut next = HashMap::new();
+	paths
+		.into_iter()
+		.flat_map(|ent_path| path_param.point_pairs(ent_path))
+		.for_each(|(from, to)| {
+			next.insert(from, to);
+			previous.insert(to, from);
+		});
+	(previous, next)
+}
@@ -0,0 +1,121 @@
+use super::*;
+
+pub(super) fn group_objects(
+	mut cmd: Commands,
+	query_user: Query<(&ActionState<VerifyAction>, &SelectedObject, &UserId)>,
+	query_object: Query<&ObjectAccess, (With<ObjectWorld>, Without<ObjectPoint>)>,
+	query_point: Query<&Parent, With<ObjectPoint>>,
+) {
+	query_user
+		.iter()
+		.for_each(|(action, selected_obj, user_id)| {
+			let is_group = action.just_pressed(&VerifyAction::Group);
+			let is_ungroup = action.just_pressed(&VerifyAction::Ungroup);
+			if !is_group && !is_ungroup {
+				return;
+			}
+			let client_id = user_id.0;
+			let mut selected = Vec::new();
+			selected_obj
+				.group
+				.iter()
+				.map(|ent| query_point.get(*ent).map_or(*ent, |parent| parent.get()))
+				.filter(|ent| {
+					query_object
+						.get(*ent)
+						.is_ok_and(|access| access.targets(&client_id))
+				})
+				.for_each(|ent| {
+					if !selected.contains(&ent) {
+						selected.push(ent);
+					}
+				});
+			if selected.is_empty() {
+				return;
+			}
+			if is_group {
+				cmd.add(move |world: &mut World| group_selected(world, client_id, selected));
+			} else {
+				cmd.add(move |world: &mut World| ungroup_selected(world, client_id, selected));
+			}
+		});
+}
+
+/// New group take place of lowest selected, keep selected order inside
+fn group_selected(world: &mut World, client_id: ClientId, selected: Vec<Entity>) {
+	let parents = group_parents(world);
+	let parent_of = |ent: Entity| parents.get(&ent).copied();
+	let layers = world.resource::<ObjectOrderZ>();
+	// Object inside other selected come along with it
+	let mut roots = selected
+		.iter()
+		.filter(|ent| {
+			!selected
+				.iter()
+				.any(|other| is_inside(**ent, *other, &parent_of))
+		})
+		.filter_map(|ent| Some((layers.iter().position(|ent_obj| ent_obj == ent)?, *ent)))
+		.collect::<Vec<_>>();
+	roots.sort_by_key(|(n, _)| *n);
+	let (Some((at, _)), Some((_, top))) = (roots.first().copied(), roots.last().copied()) else {
+		return;
+	};
+
+	let ent_group = world.spawn(ObjectGroupBundle::new("Group", client_id)).id();
+	world.flush();
+	if let Some(ent_parent) = parent_of(top) {
+		world.entity_mut(ent_group).insert(InGroup(ent_parent));
+	}
+	let mut layers = world.resource_mut::<ObjectOrderZ>();
+	layers.retain(|ent_obj| ent_obj != &ent_group);
+	layers.insert(at, ent_group);
+
+	let mut step = vec![Change::Despawn { ent: ent_group }];
+	let mut cursor = at + 1;
+	roots.into_iter().for_each(|(_, ent_obj)| {
+		let previous_group = parent_of(ent_obj);
+		world.entity_mut(ent_obj).insert(InGroup(ent_group));
+		let mut layers = world.resource_mut::<ObjectOrderZ>();
+		let Some(previous_at) = layers.move_block(ent_obj, cursor, &parent_of) else {
+			return;
+		};
+		cursor += layers
+			.block(ent_obj, &parent_of)
+			.map_or(1, |block| block.len());
+		step.push(Change::Regroup {
+			ent: ent_obj,
+			group: previous_group,
+		});
+		step.push(Change::Layer {
+			ent: ent_obj,
+			at: previous_at,
+		});
+	});
+	world
+		.resource_mut::<Histories>()
+		.entry(client_id)
+		.or_default()
+		.push(step);
+}
+
+/// Members move to the group above, group itself removed
+fn ungroup_selected(world: &mut World, client_id: ClientId, selected: Vec<Entity>) {
+	let parents = group_parents(world);
+	let mut changes = Vec::new();
+	selected
+		.into_iter()
+		.filter(|ent| world.get::<ObjectGroup>(*ent).is_some())
+		.for_each(|ent_group| {
+			changes.push(Change::Despawn { ent: ent_group });
+			parents
+				.iter()
+				.filter(|(_, parent)| *parent == &ent_group)
+				.for_each(|(ent_obj, _)| {
+					changes.push(Change::Regroup {
+						ent: *ent_obj,
+						group: parents.get(&ent_group).copied(),
+					});
+				});
+		});
+	apply_changes(world, client_id, changes);
+}
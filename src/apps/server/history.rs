@@ -0,0 +1,919 @@
+use std::{collections::HashMap, time::Duration};
+
+use bevy::ecs::system::SystemState;
+use image::{imageops::*, RgbaImage};
+use serde::de::DeserializeOwned;
+
+use super::*;
+
+/// Steps kept per user
+const MAX_HISTORY: usize = 64;
+/// Idle time before pending move / paint become one step
+const SETTLE_TIME: Duration = Duration::from_millis(300);
+
+/// Server only, history of each user. Survive until server close
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct Histories(HashMap<ClientId, History>);
+
+/// Every step hold the changes which revert it
+#[derive(Default)]
+pub struct History {
+	undo: Vec<Vec<Change>>,
+	redo: Vec<Vec<Change>>,
+	pixels: HashMap<Entity, PixelRecord>,
+	positions: HashMap<Entity, Vec2>,
+	paints: HashMap<Entity, (StrokeNet, FillNet)>,
+	dashes: HashMap<Entity, StrokeDash>,
+	opacities: HashMap<Entity, i8>,
+	curves: HashMap<Entity, PointType>,
+	last_touch: Duration,
+}
+
+/// Pixels this user changed since last settle, with their value before
+struct PixelRecord {
+	before: RgbaImage,
+	touched: Vec<bool>,
+}
+
+impl History {
+	pub fn push(&mut self, step: Vec<Change>) {
+		if step.is_empty() {
+			return;
+		}
+		self.undo.push(step);
+		if self.undo.len() > MAX_HISTORY {
+			self.undo.remove(0);
+		}
+		self.redo.clear();
+	}
+	pub fn touch(&mut self, now: Duration) {
+		self.last_touch = now;
+	}
+	/// Keep pixels which differ from `before` taken at `min`, first value only
+	pub fn record_pixels(
+		&mut self,
+		ent_obj: Entity,
+		min: UVec2,
+		before: &RgbaImage,
+		after: &RgbaImage,
+	) {
+		let (width, height) = after.dimensions();
+		let record = self.pixels.entry(ent_obj).or_insert_with(|| PixelRecord {
+			before: RgbaImage::new(width, height),
+			touched: vec![false; (width * height) as usize],
+		});
+		// Resized by someone else, older marks no longer line up
+		if record.before.dimensions() != after.dimensions() {
+			*record = PixelRecord {
+				before: RgbaImage::new(width, height),
+				touched: vec![false; (width * height) as usize],
+			};
+		}
+		before.enumerate_pixels().for_each(|(x, y, pix_before)| {
+			let (at_x, at_y) = (min.x + x, min.y + y);
+			if at_x >= width || at_y >= height || after.get_pixel(at_x, at_y) == pix_before {
+				return;
+			}
+			let n = (at_y * width + at_x) as usize;
+			if !record.touched[n] {
+				record.touched[n] = true;
+				record.before.put_pixel(at_x, at_y, *pix_before);
+			}
+		});
+	}
+	pub fn record_position(&mut self, ent_obj: Entity, pos: Vec2) {
+		self.positions.entry(ent_obj).or_insert(pos);
+	}
+	pub fn record_paint(&mut self, ent_obj: Entity, stroke: &StrokeNet, fill: &FillNet) {
+		self.paints
+			.entry(ent_obj)
+			.or_insert_with(|| (stroke.clone(), fill.clone()));
+	}
+	pub fn record_dash(&mut self, ent_obj: Entity, dash: &StrokeDash) {
+		self.dashes.entry(ent_obj).or_insert_with(|| dash.clone());
+	}
+	pub fn record_opacity(&mut self, ent_obj: Entity, opacity: i8) {
+		self.opacities.entry(ent_obj).or_insert(opacity);
+	}
+	pub fn record_curve(&mut self, ent_point: Entity, point_type: &PointType) {
+		self.curves
+			.entry(ent_point)
+			.or_insert_with(|| point_type.clone());
+	}
+	/// Turn pending records into one step, stroke only keep its own pixels
+	fn settle<'a>(&mut self, get_img: impl Fn(Entity) -> Option<&'a RgbaImage>) {
+		if self.pixels.is_empty()
+			&& self.positions.is_empty()
+			&& self.paints.is_empty()
+			&& self.dashes.is_empty()
+			&& self.opacities.is_empty()
+			&& self.curves.is_empty()
+		{
+			return;
+		}
+		let mut step = self
+			.pixels
+			.drain()
+			.filter_map(|(ent, record)| {
+				let after = get_img(ent)?;
+				if after.dimensions() != record.before.dimensions() {
+					return None;
+				}
+				let width = after.width();
+				let (mut min, mut max) = (UVec2::MAX, UVec2::ZERO);
+				record
+					.touched
+					.iter()
+					.enumerate()
+					.filter(|(_, touched)| **touched)
+					.for_each(|(n, _)| {
+						let at = UVec2::new(n as u32 % width, n as u32 / width);
+						min = min.min(at);
+						max = max.max(at);
+					});
+				if min.cmpgt(max).any() {
+					return None;
+				}
+				let size = max - min + 1;
+				let patch = crop_imm(&record.before, min.x, min.y, size.x, size.y).to_image();
+				let mask = (min.y..=max.y)
+					.flat_map(|y| (min.x..=max.x).map(move |x| (x, y)))
+					.map(|(x, y)| record.touched[(y * width + x) as usize] as u8)
+					.collect::<Vec<_>>();
+				Some(Change::Pixels {
+					ent,
+					min,
+					size,
+					data: DataHold::to_compress(patch.as_raw()),
+					mask: DataHold::to_compress(&mask),
+				})
+			})
+			.collect::<Vec<_>>();
+		step.extend(
+			self.positions
+				.drain()
+				.map(|(ent, pos)| Change::Position { ent, pos }),
+		);
+		step.extend(
+			self.paints
+				.drain()
+				.map(|(ent, (stroke, fill))| Change::Paint { ent, stroke, fill }),
+		);
+		step.extend(
+			self.dashes
+				.drain()
+				.map(|(ent, dash)| Change::Dash { ent, dash }),
+		);
+		step.extend(self.opacities.drain().map(|(ent, opacity)| Change::Lock {
+			ent,
+			action: ObjectActionNet::Opacity(opacity),
+		}));
+		step.extend(
+			self.curves
+				.drain()
+				.map(|(ent, point_type)| Change::Curve { ent, point_type }),
+		);
+		self.push(step);
+	}
+	fn remap(&mut self, from: Entity, to: Entity) {
+		self.undo
+			.iter_mut()
+			.chain(self.redo.iter_mut())
+			.flatten()
+			.for_each(|change| change.remap(from, to));
+		if let Some(img) = self.pixels.remove(&from) {
+			self.pixels.insert(to, img);
+		}
+		if let Some(pos) = self.positions.remove(&from) {
+			self.positions.insert(to, pos);
+		}
+		if let Some(paint) = self.paints.remove(&from) {
+			self.paints.insert(to, paint);
+		}
+		if let Some(dash) = self.dashes.remove(&from) {
+			self.dashes.insert(to, dash);
+		}
+		if let Some(opacity) = self.opacities.remove(&from) {
+			self.opacities.insert(to, opacity);
+		}
+		if let Some(point_type) = self.curves.remove(&from) {
+			self.curves.insert(to, point_type);
+		}
+	}
+}
+
+/// Applying a change give back the change that revert it
+pub enum Change {
+	/// Part of image at `min`, only pixels set in `mask` are restored
+	Pixels {
+		ent: Entity,
+		min: UVec2,
+		size: UVec2,
+		data: DataHold,
+		mask: DataHold,
+	},
+	Position {
+		ent: Entity,
+		pos: Vec2,
+	},
+	/// Whole image with position, size may differ from current
+	Image {
+		ent: Entity,
+		pos: Vec2,
+		size: UVec2,
+		data: DataHold,
+	},
+	Paint {
+		ent: Entity,
+		stroke: StrokeNet,
+		fill: FillNet,
+	},
+	Dash {
+		ent: Entity,
+		dash: StrokeDash,
+	},
+	Curve {
+		ent: Entity,
+		point_type: PointType,
+	},
+	Node {
+		ent: Entity,
+		mode: NodeMode,
+	},
+	Close {
+		ent: Entity,
+	},
+	Lock {
+		ent: Entity,
+		action: ObjectActionNet,
+	},
+	Rename {
+		ent: Entity,
+		name: String,
+	},
+	Despawn {
+		ent: Entity,
+	},
+	/// Points are old point entities in saved order, to remap after spawn
+	Restore {
+		ent: Entity,
+		owner: ClientId,
+		layer: usize,
+		group: Option<Entity>,
+		points: Vec<Entity>,
+		saved: SavedObject,
+		/// Session locks put back as they were, [SavedObject] only keep if locked
+		locks: (Option<MoveLock>, Option<PixelLock>, Option<AlphaLock>),
+	},
+	/// Set or clear group holding object
+	Regroup {
+		ent: Entity,
+		group: Option<Entity>,
+	},
+	/// Object with its members moved to index counted without them
+	Layer {
+		ent: Entity,
+		at: usize,
+	},
+	DespawnPoint {
+		ent: Entity,
+	},
+	RestorePoint {
+		ent: Entity,
+		path: Entity,
+		index: usize,
+		pos: Vec2,
+		point_type: PointType,
+	},
+}
+
+impl Change {
+	fn remap(&mut self, from: Entity, to: Entity) {
+		let ent = match self {
+			Change::Pixels { ent, .. }
+			| Change::Position { ent, .. }
+			| Change::Image { ent, .. }
+			| Change::Paint { ent, .. }
+			| Change::Dash { ent, .. }
+			| Change::Curve { ent, .. }
+			| Change::Node { ent, .. }
+			| Change::Close { ent }
+			| Change::Lock { ent, .. }
+			| Change::Rename { ent, .. }
+			| Change::Despawn { ent }
+			| Change::DespawnPoint { ent } => ent,
+			Change::Restore {
+				ent, points, group, ..
+			} => {
+				points
+					.iter_mut()
+					.filter(|ent_point| *ent_point == &from)
+					.for_each(|ent_point| *ent_point = to);
+				if group == &Some(from) {
+					*group = Some(to);
+				}
+				ent
+			},
+			Change::Regroup { ent, group } => {
+				if group == &Some(from) {
+					*group = Some(to);
+				}
+				ent
+			},
+			Change::Layer { ent, .. } => ent,
+			Change::RestorePoint { ent, path, .. } => {
+				if path == &from {
+					*path = to;
+				}
+				ent
+			},
+		};
+		if ent == &from {
+			*ent = to;
+		}
+	}
+	fn target(&self) -> Option<Entity> {
+		match self {
+			Change::Pixels { ent, .. }
+			| Change::Position { ent, .. }
+			| Change::Image { ent, .. }
+			| Change::Paint { ent, .. }
+			| Change::Dash { ent, .. }
+			| Change::Curve { ent, .. }
+			| Change::Node { ent, .. }
+			| Change::Close { ent }
+			| Change::Lock { ent, .. }
+			| Change::Rename { ent, .. }
+			| Change::Despawn { ent }
+			| Change::DespawnPoint { ent }
+			| Change::Regroup { ent, .. }
+			| Change::Layer { ent, .. } => Some(*ent),
+			Change::Restore { .. } => None,
+			Change::RestorePoint { path, .. } => Some(*path),
+		}
+	}
+	fn apply(
+		self,
+		world: &mut World,
+		client_id: ClientId,
+		remapped: &mut Vec<(Entity, Entity)>,
+	) -> Option<Change> {
+		if let Some(ent) = self.target() {
+			if !can_edit(world, ent, client_id) {
+				return None;
+			}
+		}
+		match self {
+			Change::Pixels {
+				ent,
+				min,
+				size,
+				data,
+				mask,
+			} => {
+				let mut patch = RgbaImage::from_vec(size.x, size.y, data.uncompress().ok()?)?;
+				let mask = mask.uncompress().ok()?;
+				if mask.len() != (size.x * size.y) as usize {
+					return None;
+				}
+				let mut process_img = world.get_mut::<ProcessImage>(ent)?;
+				let limit: UVec2 = process_img.dimensions().into();
+				if (min + size).cmpgt(limit).any() {
+					return None;
+				}
+				// Swap masked pixels, patch then hold the value to revert
+				patch
+					.enumerate_pixels_mut()
+					.zip(&mask)
+					.filter(|(_, bit)| **bit != 0)
+					.for_each(|((x, y, pix), _)| {
+						std::mem::swap(process_img.get_pixel_mut(min.x + x, min.y + y), pix);
+					});
+				let area = crop_imm(&process_img.0, min.x, min.y, size.x, size.y).to_image();
+				let mut rec_patch = ReceiveImagePatch::new(ent, area.as_raw(), min, size);
+				world
+					.resource_mut::<ConnectionManager>()
+					.send_message_to_target::<MainChannel, ReceiveImagePatch>(
+						&mut rec_patch,
+						NetworkTarget::All,
+					)
+					.unwrap_or_else(|e| {
+						error!("Fail to send message: {:?}", e);
+					});
+				Some(Change::Pixels {
+					ent,
+					min,
+					size,
+					data: DataHold::to_compress(patch.as_raw()),
+					mask: DataHold::to_compress(&mask),
+				})
+			},
+			Change::Position { ent, pos } => {
+				let mut obj_pos = world.get_mut::<ObjectPosition>(ent)?;
+				let previous = obj_pos.0;
+				obj_pos.0 = pos;
+				Some(Change::Position { ent, pos: previous })
+			},
+			Change::Image {
+				ent,
+				pos,
+				size,
+				data,
+			} => {
+				let img = RgbaImage::from_vec(size.x, size.y, data.uncompress().ok()?)?;
+				let mut ent_mut = world.get_entity_mut(ent)?;
+				let previous_pos =
+					std::mem::replace(&mut ent_mut.get_mut::<ObjectPosition>()?.0, pos);
+				let previous = std::mem::replace(&mut ent_mut.get_mut::<ProcessImage>()?.0, img);
+				let mut rec_img =
+					ReceiveImageData::new(ent, world.get::<ProcessImage>(ent)?.as_raw(), size);
+				world
+					.resource_mut::<ConnectionManager>()
+					.send_message_to_target::<MainChannel, ReceiveImageData>(
+						&mut rec_img,
+						NetworkTarget::All,
+					)
+					.unwrap_or_else(|e| {
+						error!("Fail to send message: {:?}", e);
+					});
+				Some(Change::Image {
+					ent,
+					pos: previous_pos,
+					size: previous.dimensions().into(),
+					data: DataHold::to_compress(previous.as_raw()),
+				})
+			},
+			Change::Paint { ent, stroke, fill } => {
+				let mut ent_mut = world.get_entity_mut(ent)?;
+				let stroke = std::mem::replace(&mut *ent_mut.get_mut::<StrokeNet>()?, stroke);
+				let fill = std::mem::replace(&mut *ent_mut.get_mut::<FillNet>()?, fill);
+				Some(Change::Paint { ent, stroke, fill })
+			},
+			Change::Dash { ent, dash } => {
+				let mut current = world.get_mut::<StrokeDash>(ent)?;
+				let previous = std::mem::replace(&mut *current, dash);
+				Some(Change::Dash {
+					ent,
+					dash: previous,
+				})
+			},
+			Change::Curve { ent, point_type } => {
+				let mut current = world.get_mut::<PointType>(ent)?;
+				let previous = std::mem::replace(&mut *current, point_type);
+				Some(Change::Curve {
+					ent,
+					point_type: previous,
+				})
+			},
+			Change::Node { ent, mode } => {
+				let mut current = world.get_mut::<NodeMode>(ent)?;
+				let previous = std::mem::replace(&mut *current, mode);
+				Some(Change::Node {
+					ent,
+					mode: previous,
+				})
+			},
+			Change::Close { ent } => {
+				let mut close = world.get_mut::<PathClose>(ent)?;
+				close.0 = !close.0;
+				Some(Change::Close { ent })
+			},
+			Change::Lock { ent, action } => {
+				let add_or_remove = |set: &mut HashSet<ClientId>| {
+					if !set.remove(&client_id) {
+						set.insert(client_id);
+					}
+				};
+				let reverted = match action {
+					ObjectActionNet::LockMove => {
+						add_or_remove(&mut world.get_mut::<MoveLock>(ent)?.0);
+						action
+					},
+					ObjectActionNet::LockPixel => {
+						add_or_remove(&mut world.get_mut::<PixelLock>(ent)?.0);
+						action
+					},
+					ObjectActionNet::LockAlpha => {
+						add_or_remove(&mut world.get_mut::<AlphaLock>(ent)?.0);
+						action
+					},
+					ObjectActionNet::LayerUp => {
+						let parents = group_parents(world);
+						world
+							.resource_mut::<ObjectOrderZ>()
+							.raise(ent, &|ent| parents.get(&ent).copied())
+							.then_some(())?;
+						ObjectActionNet::LayerDown
+					},
+					ObjectActionNet::LayerDown => {
+						let parents = group_parents(world);
+						world
+							.resource_mut::<ObjectOrderZ>()
+							.lower(ent, &|ent| parents.get(&ent).copied())
+							.then_some(())?;
+						ObjectActionNet::LayerUp
+					},
+					// Recorded as Change::Layer and Change::Regroup
+					ObjectActionNet::LayerTop
+					| ObjectActionNet::LayerBottom
+					| ObjectActionNet::LayerAbove(_)
+					| ObjectActionNet::LayerBelow(_) => return None,
+					ObjectActionNet::Opacity(value) => {
+						let mut opacity = world.get_mut::<ObjectOpacity>(ent)?;
+						ObjectActionNet::Opacity(std::mem::replace(&mut opacity.0, value))
+					},
+					ObjectActionNet::Blend(draw_type) => {
+						let mut blend = world.get_mut::<ObjectBlend>(ent)?;
+						ObjectActionNet::Blend(std::mem::replace(&mut blend.0, draw_type))
+					},
+				};
+				Some(Change::Lock {
+					ent,
+					action: reverted,
+				})
+			},
+			Change::Rename { ent, name } => {
+				let mut current = world.get_mut::<SharingName>(ent)?;
+				let previous = std::mem::replace(&mut current.0, name);
+				Some(Change::Rename {
+					ent,
+					name: previous,
+				})
+			},
+			Change::Despawn { ent } => {
+				let saved = SystemState::<RdioParam>::new(world)
+					.get(world)
+					.save_object(ent);
+				let owner = world.get::<ObjectOwner>(ent).map(|owner| owner.0);
+				let group = world.get::<InGroup>(ent).map(|in_group| in_group.0);
+				let locks = (
+					world.get::<MoveLock>(ent).cloned(),
+					world.get::<PixelLock>(ent).cloned(),
+					world.get::<AlphaLock>(ent).cloned(),
+				);
+				let layer = world
+					.resource::<ObjectOrderZ>()
+					.iter()
+					.position(|ent_obj| ent_obj == &ent);
+				let points = SystemState::<PathParam>::new(world)
+					.get(world)
+					.point_ents(ent);
+				world.get_entity_mut(ent)?.despawn_recursive();
+				Some(Change::Restore {
+					ent,
+					owner: owner?,
+					layer: layer?,
+					group,
+					points,
+					saved: saved?,
+					locks,
+				})
+			},
+			Change::Restore {
+				ent,
+				owner,
+				layer,
+				group,
+				points,
+				saved,
+				locks: (op_move_lock, op_pixel_lock, op_alpha_lock),
+			} => {
+				let (new_ent, new_points) = saved.spawn(&mut world.commands(), owner)?;
+				world.flush();
+				let mut ent_mut = world.entity_mut(new_ent);
+				if let Some(move_lock) = op_move_lock {
+					ent_mut.insert(move_lock);
+				}
+				if let Some(pixel_lock) = op_pixel_lock {
+					ent_mut.insert(pixel_lock);
+				}
+				if let Some(alpha_lock) = op_alpha_lock {
+					ent_mut.insert(alpha_lock);
+				}
+				if let Some(ent_group) =
+					group.filter(|ent_group| world.get_entity(*ent_group).is_some())
+				{
+					world.entity_mut(new_ent).insert(InGroup(ent_group));
+				}
+				let mut layers = world.resource_mut::<ObjectOrderZ>();
+				if let Some(pos) = layers.iter().position(|ent_obj| ent_obj == &new_ent) {
+					layers.remove(pos);
+					let layer = layer.min(layers.len());
+					layers.insert(layer, new_ent);
+				}
+				remapped.push((ent, new_ent));
+				remapped.extend(points.into_iter().zip(new_points));
+				Some(Change::Despawn { ent: new_ent })
+			},
+			Change::Regroup { ent, group } => {
+				let parents = group_parents(world);
+				if let Some(ent_group) = group {
+					// Never put group inside itself
+					if ent_group == ent
+						|| is_inside(ent_group, ent, &|ent| parents.get(&ent).copied())
+					{
+						return None;
+					}
+				}
+				let previous = world.get::<InGroup>(ent).map(|in_group| in_group.0);
+				let mut ent_mut = world.get_entity_mut(ent)?;
+				match group {
+					Some(ent_group) => ent_mut.insert(InGroup(ent_group)),
+					None => ent_mut.remove::<InGroup>(),
+				};
+				Some(Change::Regroup {
+					ent,
+					group: previous,
+				})
+			},
+			Change::Layer { ent, at } => {
+				let parents = group_parents(world);
+				let previous =
+					world
+						.resource_mut::<ObjectOrderZ>()
+						.move_block(ent, at, &|ent| parents.get(&ent).copied())?;
+				Some(Change::Layer { ent, at: previous })
+			},
+			Change::DespawnPoint { ent } => {
+				let path = world.get::<Parent>(ent)?.get();
+				let point_type = world.get::<PointType>(ent)?.clone();
+				let pos = world.get::<ObjectPosition>(ent)?.0;
+				let children = world.get::<Children>(path)?;
+				let index = children.iter().position(|ent_point| ent_point == &ent)?;
+				let previous = index.checked_sub(1).map(|n| children[n]);
+				let next = children.get(index + 1).copied();
+				world.entity_mut(ent).despawn_recursive();
+				if let (Some(previous), Some(next)) = (previous, next) {
+					world.trigger(ConnectRelations::<PointToPoint>::new(previous, next));
+				}
+				Some(Change::RestorePoint {
+					ent,
+					path,
+					index,
+					pos,
+					point_type,
+				})
+			},
+			Change::RestorePoint {
+				ent,
+				path,
+				index,
+				pos,
+				point_type,
+			} => {
+				let (previous, next) = world
+					.get::<Children>(path)
+					.map(|children| {
+						let index = index.min(children.len());
+						(
+							index.checked_sub(1).map(|n| children[n]),
+							children.get(index).copied(),
+						)
+					})
+					.unwrap_or_default();
+				let new_point = world.spawn((PointBundle::new(pos), point_type)).id();
+				let index = world
+					.get::<Children>(path)
+					.map_or(0, |children| index.min(children.len()));
+				world.entity_mut(path).insert_children(index, &[new_point]);
+				if let Some(previous) = previous {
+					world.trigger(ConnectRelations::<PointToPoint>::new(previous, new_point));
+				}
+				if let Some(next) = next {
+					world.trigger(ConnectRelations::<PointToPoint>::new(new_point, next));
+				}
+				remapped.push((ent, new_point));
+				Some(Change::DespawnPoint { ent: new_point })
+			},
+		}
+	}
+}
+
+/// Revert of a pixel edit at `min`, only pixels that differ are kept
+pub(super) fn pixels_change(
+	ent: Entity,
+	min: UVec2,
+	previous: &RgbaImage,
+	current: &RgbaImage,
+) -> Change {
+	let mask = previous
+		.enumerate_pixels()
+		.map(|(x, y, pix)| (current.get_pixel(min.x + x, min.y + y) != pix) as u8)
+		.collect::<Vec<_>>();
+	Change::Pixels {
+		ent,
+		min,
+		size: previous.dimensions().into(),
+		data: DataHold::to_compress(previous.as_raw()),
+		mask: DataHold::to_compress(&mask),
+	}
+}
+
+/// Object to group pairs, taken before world is mutated
+pub(super) fn group_parents(world: &mut World) -> HashMap<Entity, Entity> {
+	SystemState::<GroupParam>::new(world).get(world).parents()
+}
+
+/// Points follow access of their path
+pub(super) fn can_edit(world: &World, ent: Entity, client_id: ClientId) -> bool {
+	let ent_obj = match world.get::<ObjectPoint>(ent) {
+		Some(_) => world.get::<Parent>(ent).map_or(ent, |parent| parent.get()),
+		None => ent,
+	};
+	world
+		.get::<ObjectAccess>(ent_obj)
+		.map_or(true, |access| access.targets(&client_id))
+}
+
+/// Apply changes for user and keep reverts as new step
+pub(super) fn apply_changes(world: &mut World, client_id: ClientId, changes: Vec<Change>) {
+	let mut history = world
+		.resource_mut::<Histories>()
+		.remove(&client_id)
+		.unwrap_or_default();
+	let step = run_changes(world, &mut history, client_id, changes);
+	history.push(step);
+	world.resource_mut::<Histories>().insert(client_id, history);
+}
+
+/// Changes run from last to first, returned step is in same order as undo stack expect
+fn run_changes(
+	world: &mut World,
+	history: &mut History,
+	client_id: ClientId,
+	mut changes: Vec<Change>,
+) -> Vec<Change> {
+	let mut reverts = Vec::new();
+	while let Some(change) = changes.pop() {
+		let mut remapped = Vec::new();
+		if let Some(revert) = change.apply(world, client_id, &mut remapped) {
+			reverts.push(revert);
+		}
+		remapped.into_iter().for_each(|(from, to)| {
+			changes.iter_mut().for_each(|change| change.remap(from, to));
+			history.remap(from, to);
+			world
+				.resource_mut::<Histories>()
+				.values_mut()
+				.for_each(|other| other.remap(from, to));
+		});
+	}
+	world.flush();
+	reverts
+}
+
+pub(super) fn step_history(world: &mut World, client_id: ClientId, bir: ObjectBirNet) {
+	let Some(mut history) = world.resource_mut::<Histories>().remove(&client_id) else {
+		return;
+	};
+	history.settle(|ent| world.get::<ProcessImage>(ent).map(|img| &img.0));
+	let step = match bir {
+		ObjectBirNet::Undo => history.undo.pop(),
+		ObjectBirNet::Redo => history.redo.pop(),
+	};
+	if let Some(step) = step {
+		let reverts = run_changes(world, &mut history, client_id, step);
+		if !reverts.is_empty() {
+			match bir {
+				ObjectBirNet::Undo => history.redo.push(reverts),
+				ObjectBirNet::Redo => history.undo.push(reverts),
+			}
+		}
+	}
+	world.resource_mut::<Histories>().insert(client_id, history);
+}
+
+pub(super) fn history_bir(
+	trigger: Trigger<ObjectBirNet>,
+	query_user: Query<&UserId>,
+	mut cmd: Commands,
+) {
+	let Ok(user_id) = query_user.get(trigger.entity()) else {
+		return;
+	};
+	let client_id = user_id.0;
+	let bir = *trigger.event();
+	cmd.add(move |world: &mut World| step_history(world, client_id, bir));
+}
+
+pub(super) fn history_action(
+	query_user: Query<(&ActionState<VerifyAction>, &UserId)>,
+	mut cmd: Commands,
+) {
+	query_user.iter().for_each(|(action, user_id)| {
+		let bir = if action.just_pressed(&VerifyAction::Undo) {
+			ObjectBirNet::Undo
+		} else if action.just_pressed(&VerifyAction::Redo) {
+			ObjectBirNet::Redo
+		} else {
+			return;
+		};
+		let client_id = user_id.0;
+		cmd.add(move |world: &mut World| step_history(world, client_id, bir));
+	});
+}
+
+/// Pixel edit whose changed area is known before it run
+pub(super) trait PixelAction: Event + Clone + Copy + Serialize + DeserializeOwned {
+	/// Part of object image it may change, clamped inside `limit`
+	fn touch_rect(&self, world: &World, user: Entity, ent_obj: Entity, limit: UVec2) -> URect;
+}
+
+impl PixelAction for PenDraw {
+	fn touch_rect(&self, world: &World, user: Entity, ent_obj: Entity, limit: UVec2) -> URect {
+		let (Some(draw_pile), Some(obj_pos)) = (
+			world.get::<DrawPiled>(user),
+			world.get::<ObjectPosition>(ent_obj),
+		) else {
+			return URect::from_corners(UVec2::ZERO, limit);
+		};
+		let CursorFromTo { from, to } = self.0;
+		stroke_rect(from, to, obj_pos.0, draw_pile.dimensions().into(), limit)
+	}
+}
+
+impl PixelAction for BucketFill {
+	fn touch_rect(&self, _: &World, _: Entity, _: Entity, limit: UVec2) -> URect {
+		URect::from_corners(UVec2::ZERO, limit)
+	}
+}
+
+/// Pen stroke and bucket fill, keep only pixels the user changed for undo
+pub(super) fn verify_pixel_action<T: PixelAction>(
+	mut events: EventReader<MessageEvent<T>>,
+	mut server: ResMut<ConnectionManager>,
+	mut cmd: Commands,
+	users: Res<Users>,
+) {
+	events.read().for_each(|event| {
+		let client_id = *event.context();
+		let Some(user) = users.get(&client_id).copied() else {
+			return;
+		};
+		let action = *event.message();
+		server
+			.send_message_to_target::<MainChannel, ToClientEntDataEvent<T>>(
+				&mut ToClientEntDataEvent::<T>::new(action, user),
+				NetworkTarget::All,
+			)
+			.unwrap_or_else(|e| {
+				error!("Fail to send message: {:?}", e);
+			});
+		cmd.add(move |world: &mut World| {
+			let Some(selected_obj) = world.get::<SelectedObject>(user) else {
+				return;
+			};
+			let befores = selected_obj
+				.group
+				.iter()
+				.filter_map(|ent_obj| {
+					let process_img = world.get::<ProcessImage>(*ent_obj)?;
+					let limit = process_img.dimensions().into();
+					let rect = action.touch_rect(world, user, *ent_obj, limit);
+					let size = rect.size();
+					let before =
+						crop_imm(&process_img.0, rect.min.x, rect.min.y, size.x, size.y).to_image();
+					Some((*ent_obj, rect.min, before))
+				})
+				.collect::<Vec<_>>();
+			world.trigger_targets(action, user);
+			world.flush();
+			let now = world.resource::<Time>().elapsed();
+			let mut history = world
+				.resource_mut::<Histories>()
+				.remove(&client_id)
+				.unwrap_or_default();
+			befores.into_iter().for_each(|(ent_obj, min, before)| {
+				if let Some(process_img) = world.get::<ProcessImage>(ent_obj) {
+					history.record_pixels(ent_obj, min, &before, &process_img.0);
+				}
+			});
+			history.touch(now);
+			world.resource_mut::<Histories>().insert(client_id, history);
+		});
+	});
+}
+
+pub(super) fn settle_history(
+	mut histories: ResMut<Histories>,
+	users: Res<Users>,
+	query_user: Query<&ActionState<ClientAction>, With<UserId>>,
+	query_object: Query<&ProcessImage, With<ObjectWorld>>,
+	time: Res<Time>,
+) {
+	let now = time.elapsed();
+	// Settling never edit the board, autosave watch history for edits
+	histories
+		.bypass_change_detection()
+		.iter_mut()
+		.for_each(|(client_id, history)| {
+			let is_drawing = users
+				.get(client_id)
+				.and_then(|user| query_user.get(*user).ok())
+				.is_some_and(|action| action.pressed(&ClientAction::Drawing));
+			if is_drawing || now.saturating_sub(history.last_touch) < SETTLE_TIME {
+				return;
+			}
+			history.settle(|ent| query_object.get(ent).ok().map(|img| &img.0));
+		});
+}
@@ -1,21 +1,206 @@
 use std::{
 	collections::{HashMap, HashSet},
+	net::IpAddr,
+	ops::Range,
 	sync::{Arc, RwLock},
+	time::{Duration, Instant},
 };
 
 use bevy::prelude::*;
 use leafwing_input_manager::Actionlike;
-use lightyear::prelude::ClientId;
+use lightyear::prelude::{ClientId, Key};
+
+use crate::apps::shared::prelude::{AuthReply, SERVER_ADDR_BACKEND};
+
+/// How long left user can resume and take back what it held
+pub const RESUME_TIMEOUT: Duration = Duration::from_secs(300);
 
 #[derive(Resource, Default)]
 pub struct ObjectIncrementCount(pub u64);
 
+/// Left user whose history and objects wait for resume until deadline
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct ParkedClients(HashMap<ClientId, Instant>);
+
 #[derive(Resource, Default, Deref, DerefMut)]
 pub struct Users(HashMap<ClientId, Entity>);
 
+/// Bottom to top, group sit right below everything inside it
 #[derive(Resource, Default, Deref, DerefMut)]
 pub struct ObjectOrderZ(Vec<Entity>);
 
+impl ObjectOrderZ {
+	/// Object with everything inside it
+	pub fn block(
+		&self,
+		ent: Entity,
+		parent_of: &impl Fn(Entity) -> Option<Entity>,
+	) -> Option<Range<usize>> {
+		let start = self.iter().position(|ent_obj| ent_obj == &ent)?;
+		let len = self[start + 1..]
+			.iter()
+			.take_while(|ent_obj| is_inside(**ent_obj, ent, parent_of))
+			.count();
+		Some(start..start + 1 + len)
+	}
+	/// Swap with block above in the same group, false when already on top
+	pub fn raise(&mut self, ent: Entity, parent_of: &impl Fn(Entity) -> Option<Entity>) -> bool {
+		let Some(block) = self.block(ent, parent_of) else {
+			return false;
+		};
+		let Some(above) = self.get(block.end).copied() else {
+			return false;
+		};
+		if parent_of(above) != parent_of(ent) {
+			return false;
+		}
+		let Some(above_block) = self.block(above, parent_of) else {
+			return false;
+		};
+		self[block.start..above_block.end].rotate_left(block.len());
+		true
+	}
+	/// Swap with block below in the same group, false when already at bottom
+	pub fn lower(&mut self, ent: Entity, parent_of: &impl Fn(Entity) -> Option<Entity>) -> bool {
+		let Some(block) = self.block(ent, parent_of) else {
+			return false;
+		};
+		let parent = parent_of(ent);
+		let Some(below) = self[..block.start]
+			.iter()
+			.rev()
+			.find(|ent_obj| parent_of(**ent_obj) == parent || Some(**ent_obj) == parent)
+			.copied()
+		else {
+			return false;
+		};
+		if Some(below) == parent {
+			return false;
+		}
+		let Some(below_block) = self.block(below, parent_of) else {
+			return false;
+		};
+		self[below_block.start..block.end].rotate_right(block.len());
+		true
+	}
+	/// Top or bottom among objects of the same group, give back where it was
+	pub fn move_edge(
+		&mut self,
+		ent: Entity,
+		is_top: bool,
+		parent_of: &impl Fn(Entity) -> Option<Entity>,
+	) -> Option<usize> {
+		let block = self.block(ent, parent_of)?;
+		let inside = match parent_of(ent) {
+			Some(ent_group) => {
+				let group_block = self.block(ent_group, parent_of)?;
+				group_block.start + 1..group_block.end
+			},
+			None => 0..self.len(),
+		};
+		let at = if is_top {
+			inside.end - block.len()
+		} else {
+			inside.start
+		};
+		if at == block.start {
+			return None;
+		}
+		self.move_block(ent, at, parent_of)
+	}
+	/// Right above or below block of other object, give back where it was
+	pub fn move_beside(
+		&mut self,
+		ent: Entity,
+		ent_other: Entity,
+		is_above: bool,
+		parent_of: &impl Fn(Entity) -> Option<Entity>,
+	) -> Option<usize> {
+		if ent == ent_other || is_inside(ent_other, ent, parent_of) {
+			return None;
+		}
+		let block = self.block(ent, parent_of)?;
+		let other_block = self.block(ent_other, parent_of)?;
+		let at = if is_above {
+			other_block.end
+		} else {
+			other_block.start
+		};
+		let at = if at > block.start {
+			at - block.len()
+		} else {
+			at
+		};
+		self.move_block(ent, at, parent_of)
+	}
+	/// Move object with its members to index counted without them, give back where it was
+	pub fn move_block(
+		&mut self,
+		ent: Entity,
+		at: usize,
+		parent_of: &impl Fn(Entity) -> Option<Entity>,
+	) -> Option<usize> {
+		let block = self.block(ent, parent_of)?;
+		let moved = self.drain(block.clone()).collect::<Vec<_>>();
+		let at = at.min(self.len());
+		self.splice(at..at, moved);
+		Some(block.start)
+	}
+}
+
+pub fn is_inside(
+	ent: Entity,
+	ent_group: Entity,
+	parent_of: &impl Fn(Entity) -> Option<Entity>,
+) -> bool {
+	let mut current = parent_of(ent);
+	// Bounded in case of broken loop
+	for _ in 0..u16::MAX {
+		let Some(ent_parent) = current else {
+			return false;
+		};
+		if ent_parent == ent_group {
+			return true;
+		}
+		current = parent_of(ent_parent);
+	}
+	false
+}
+
+/// Overridable before server start, dedicated server fill it from CLI
+#[derive(Resource, Clone)]
+pub struct ServerSettings {
+	pub backend_port: u16,
+	pub password: Option<String>,
+	/// Random each start when none
+	pub password_admin: Option<String>,
+	/// Netcode key signing connect token, random each start when none
+	pub private_key: Option<Key>,
+	pub owner_policy: OwnerPolicy,
+}
+
+/// What happen to objects owned by user who left
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OwnerPolicy {
+	/// Owner stay as left user
+	#[default]
+	KeepOrphaned,
+	/// Host, or another admin on dedicated server
+	TransferToHost,
+}
+
+impl Default for ServerSettings {
+	fn default() -> Self {
+		Self {
+			backend_port: SERVER_ADDR_BACKEND.port(),
+			password: None,
+			password_admin: None,
+			private_key: None,
+			owner_policy: OwnerPolicy::default(),
+		}
+	}
+}
+
 #[derive(Resource, Deref, DerefMut)]
 pub struct BackendTaskServer(pub Arc<RwLock<BackendItem>>);
 
@@ -24,6 +209,15 @@ pub struct BackendItem {
 	pub inactive: bool,
 	pub password: Option<String>,
 	pub password_admin: String,
+	/// Token issued with admin password
+	pub admins: HashSet<u64>,
+	/// Address token was issued to, for banning
+	pub addresses: HashMap<u64, IpAddr>,
+	pub banned: HashSet<IpAddr>,
+	/// Resume token to its last client id, admin flag and expiry, none while connected
+	pub resumes: HashMap<u128, (u64, bool, Option<Instant>)>,
+	/// New client id to the one it resumed, consumed on connect
+	pub resumed: HashMap<u64, u64>,
 }
 
 impl Default for BackendItem {
@@ -33,6 +227,25 @@ impl Default for BackendItem {
 			inactive: false,
 			password: None,
 			password_admin: "Admin".to_owned(),
+			admins: HashSet::default(),
+			addresses: HashMap::default(),
+			banned: HashSet::default(),
+			resumes: HashMap::default(),
+			resumed: HashMap::default(),
+		}
+	}
+}
+
+impl BackendItem {
+	pub fn authorize(&self, ip: &IpAddr, password: &str) -> (AuthReply, bool) {
+		if self.banned.contains(ip) {
+			(AuthReply::Banned, false)
+		} else if !self.password_admin.is_empty() && password == self.password_admin {
+			(AuthReply::Accepted, true)
+		} else if self.password.as_ref().is_some_and(|pass| pass != password) {
+			(AuthReply::WrongPassword, false)
+		} else {
+			(AuthReply::Accepted, false)
 		}
 	}
 }
@@ -49,6 +262,10 @@ pub struct UserAdmin;
 #[derive(Event)]
 pub struct DisconnectClient(pub ClientId);
 
+/// Session which can no longer resume, free everything it held
+#[derive(Event)]
+pub struct ReleaseClient(pub ClientId);
+
 // #[derive(Component, Serialize, Deserialize, Debug, Clone)]
 // struct UniqueUserKey([char; 16]);
 
@@ -1,41 +1,74 @@
 use aery::prelude::*;
 use bevy::prelude::*;
 // use bevy::tasks::IoTaskPool;
+use bevy::time::common_conditions::on_timer;
 use leafwing_input_manager::plugin::InputManagerSystem;
 use leafwing_input_manager::prelude::*;
-use lightyear::connection::netcode::PRIVATE_KEY_BYTES;
 use lightyear::prelude::*;
 // use rainful_diffolor::source_to_docs;
 use server::*;
 use std::collections::HashSet;
+use std::net::SocketAddr;
 // use std::fs::File;
 // use std::io::Write;
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
 
 use crate::apps::shared::prelude::*;
 use crate::camera_control::lib::MAX_VALID_RANGE;
 use crate::trait_bevy::ApplyDiff;
+mod autosave;
 mod connection;
+mod curve;
+mod group;
+mod history;
 mod lib;
 mod performing;
+mod transform;
+use autosave::*;
 use connection::*;
+use curve::*;
+use group::*;
+use history::*;
 use lib::*;
 use performing::*;
+use transform::*;
 
 use super::shared::proto::MainChannel;
 
-pub(super) struct AppServerPlugin;
+pub use lib::{OwnerPolicy, ServerSettings};
+
+/// [Mode::HostServer] run along with client, [Mode::Separate] is dedicated server
+pub struct AppServerPlugin {
+	pub mode: Mode,
+	pub addr: SocketAddr,
+}
+
+impl Default for AppServerPlugin {
+	fn default() -> Self {
+		Self {
+			mode: Mode::HostServer,
+			addr: SERVER_ADDR,
+		}
+	}
+}
+
 impl Plugin for AppServerPlugin {
 	fn build(&self, app: &mut App) {
-		app.add_plugins(build_server_plugin())
+		app.add_plugins(build_server_plugin(self.mode, self.addr))
+			.init_resource::<ServerSettings>()
 			.add_systems(
 				OnEnter(RdioServerState::Online),
-				(start_server, insert_server_source),
+				(insert_server_source, start_server, restore_autosave).chain(),
 			)
 			.add_systems(
 				OnExit(RdioServerState::Online),
 				(stop_connection, remove_server_source),
 			)
+			.add_systems(
+				Last,
+				autosave_on_close.run_if(in_state(RdioServerState::Online)),
+			)
 			.add_systems(
 				PreUpdate,
 				(
@@ -51,20 +84,33 @@ impl Plugin for AppServerPlugin {
 				(
 					move_object,
 					delete_object,
-					spawn_point,
-					spawn_text,
+					(spawn_point, spawn_text, spawn_shape),
+					(open_rdio, import_rdio),
 					process_incoming_message,
 					receive_point_request,
-					verify_action::<PenDraw>,
+					(
+						verify_pixel_action::<PenDraw>,
+						verify_pixel_action::<BucketFill>,
+					),
 					verify_action::<ObjectBirNet>,
 					// receive_marker_pen_draw,
-					receive_img_data,
-					spawn_new_image,
-					send_image_data,
-					obj_action,
-					path_apply_color,
-					toggle_close,
+					(
+						receive_img_data,
+						spawn_new_image,
+						send_image_data,
+						transform_image,
+						crop_image,
+						commit_resize,
+						floating_selection,
+					),
+					(obj_action, group_objects, rename_object),
+					(path_apply_color, toggle_close, edit_curve, edit_stroke),
 					obj_permission,
+					moderate_user,
+					send_join_state,
+					autosave_board,
+					expire_resumes.run_if(on_timer(Duration::from_secs(1))),
+					(history_action, settle_history),
 				)
 					.run_if(in_state(NetworkingState::Started)),
 			)
@@ -78,72 +124,162 @@ impl Plugin for AppServerPlugin {
 }
 
 fn path_apply_color(
-	query_user: Query<(&ActionState<VerifyAction>, &SelectedObject, &PaintInk), With<UserId>>,
+	query_user: Query<(
+		&ActionState<VerifyAction>,
+		&SelectedObject,
+		&PaintInk,
+		&UserId,
+	)>,
 	query_point: Query<&Parent, With<ObjectPoint>>,
 	mut query_object: Query<(&mut StrokeNet, &mut FillNet), With<ObjectWorld>>,
+	mut histories: ResMut<Histories>,
+	time: Res<Time>,
 ) {
-	query_user.iter().for_each(|(action, selected_obj, paint)| {
-		if !action.pressed(&VerifyAction::PathApplyColor) {
-			return;
-		}
+	query_user
+		.iter()
+		.for_each(|(action, selected_obj, paint, user_id)| {
+			if !action.pressed(&VerifyAction::PathApplyColor) {
+				return;
+			}
 
-		let Some(Some(point_parent)) = selected_obj
-			.single
-			.map(|ent_main| query_point.get(ent_main).ok())
-		else {
-			return;
-		};
-		let Ok((mut stroke, mut fill)) = query_object.get_mut(point_parent.get()) else {
-			return;
-		};
-		let color_stroke = paint.0.into();
-		let color_fill = paint.1.into();
-		stroke.color.apply_diff(color_stroke);
-		fill.color.apply_diff(color_fill);
-	});
+			let Some(Some(point_parent)) = selected_obj
+				.single
+				.map(|ent_main| query_point.get(ent_main).ok())
+			else {
+				return;
+			};
+			let Ok((mut stroke, mut fill)) = query_object.get_mut(point_parent.get()) else {
+				return;
+			};
+			let color_stroke = paint.0.into();
+			let color_fill = paint.1.into();
+			if stroke.color != color_stroke || fill.color != color_fill {
+				let history = histories.entry(user_id.0).or_default();
+				history.record_paint(point_parent.get(), &stroke, &fill);
+				history.touch(time.elapsed());
+			}
+			stroke.color.apply_diff(color_stroke);
+			fill.color.apply_diff(color_fill);
+		});
 }
 
 fn toggle_close(
-	query_user: Query<(&ActionState<VerifyAction>, &SelectedObject), With<UserId>>,
+	query_user: Query<(&ActionState<VerifyAction>, &SelectedObject, &UserId)>,
 	query_point: Query<&Parent, With<ObjectPoint>>,
-	mut query_path: Query<&mut PathClose, With<ObjectWorld>>,
+	mut query_path: Query<(Entity, &mut PathClose), With<ObjectWorld>>,
+	mut histories: ResMut<Histories>,
+) {
+	query_user
+		.iter()
+		.for_each(|(action, selected_obj, user_id)| {
+			if !action.just_pressed(&VerifyAction::ToggleClose) {
+				return;
+			}
+
+			let all_path = query_point
+				.iter_many(selected_obj.group.iter())
+				.map(|parent| parent.get())
+				.collect::<HashSet<Entity>>();
+			let mut step = Vec::new();
+			let mut path_query = query_path.iter_many_mut(all_path.iter());
+			while let Some((ent_path, mut close)) = path_query.fetch_next() {
+				close.0 = !close.0;
+				step.push(Change::Close { ent: ent_path });
+			}
+			histories.entry(user_id.0).or_default().push(step);
+		});
+}
+
+/// Stroke panel edit, recorded like paint so quick drags become one step
+fn edit_stroke(
+	mut events: EventReader<MessageEvent<StrokeEditNet>>,
+	mut query_path: Query<
+		(&mut StrokeNet, &mut StrokeDash, &FillNet, &ObjectAccess),
+		With<ObjectPath>,
+	>,
+	mut histories: ResMut<Histories>,
+	time: Res<Time>,
 ) {
-	query_user.iter().for_each(|(action, selected_obj)| {
-		if !action.just_pressed(&VerifyAction::ToggleClose) {
+	events.read().for_each(|event| {
+		let client_id = *event.context();
+		let StrokeEditNet { path, edit } = event.message().clone();
+		let Ok((mut stroke, mut dash, fill, access)) = query_path.get_mut(path) else {
+			return;
+		};
+		if !access.targets(&client_id) {
 			return;
 		}
-
-		let all_path = query_point
-			.iter_many(selected_obj.group.iter())
-			.map(|parent| parent.get())
-			.collect::<HashSet<Entity>>();
-		let mut path_query = query_path.iter_many_mut(all_path.iter());
-		while let Some(mut close) = path_query.fetch_next() {
-			close.0 = !close.0;
+		let mut options = stroke.options;
+		let mut edited_dash = dash.clone();
+		match edit {
+			StrokeEdit::Width(width) if width.is_finite() => {
+				options.line_width = width.clamp(0.0, MAX_STROKE_WIDTH);
+			},
+			StrokeEdit::StartCap(cap) => options.start_cap = cap,
+			StrokeEdit::EndCap(cap) => options.end_cap = cap,
+			StrokeEdit::Dash(edited) if edited.is_valid() => edited_dash = edited,
+			// Every client would draw it
+			StrokeEdit::Width(_) | StrokeEdit::Dash(_) => return,
 		}
+		let history = histories.entry(client_id).or_default();
+		if options != stroke.options {
+			history.record_paint(path, &stroke, fill);
+			stroke.options = options;
+		}
+		if edited_dash != *dash {
+			history.record_dash(path, &dash);
+			*dash = edited_dash;
+		}
+		history.touch(time.elapsed());
 	});
 }
 
 pub(super) fn obj_action(
+	mut cmd: Commands,
 	mut query_object: Query<
 		(
 			AnyOf<(&mut MoveLock, &mut PixelLock, &mut AlphaLock)>,
+			&mut ObjectOpacity,
+			&mut ObjectBlend,
 			&ObjectAccess,
 		),
 		With<ObjectWorld>,
 	>,
 	mut events: EventReader<MessageEvent<ObjectActionToServer>>,
 	mut layers: ResMut<ObjectOrderZ>,
+	mut histories: ResMut<Histories>,
+	time: Res<Time>,
+	group_param: GroupParam,
 ) {
 	events.read().for_each(|event| {
 		let ent_obj = event.message().obj_ent;
-		let Ok(((mut move_lock, mut pix_lock, mut alpha_lock), access)) =
+		let action = event.message().action;
+		let client_id = event.context();
+		// Joining other object need access to it and every group around, none move locked
+		if let ObjectActionNet::LayerAbove(ent_other) | ObjectActionNet::LayerBelow(ent_other) =
+			action
+		{
+			let is_arrangeable = |ent: Entity| {
+				query_object
+					.get(ent)
+					.is_ok_and(|((move_lock, ..), _, _, access)| {
+						access.targets(client_id)
+							&& !move_lock.is_some_and(|lock| lock.contains(client_id))
+					})
+			};
+			if !std::iter::once(ent_other)
+				.chain(group_param.ancestors(ent_other))
+				.chain(group_param.ancestors(ent_obj))
+				.all(is_arrangeable)
+			{
+				return;
+			}
+		}
+		let Ok(((mut move_lock, mut pix_lock, mut alpha_lock), mut opacity, mut blend, access)) =
 			query_object.get_mut(ent_obj)
 		else {
 			return;
 		};
-		let action = event.message().action;
-		let client_id = event.context();
 		if !access.targets(client_id) {
 			return;
 		}
@@ -152,42 +288,139 @@ pub(super) fn obj_action(
 				set.insert(*client_id);
 			}
 		};
-		match action {
+		let reverted = match action {
 			ObjectActionNet::LockMove => {
 				if let Some(ref mut hashed_set) = move_lock {
 					add_or_remove(&mut hashed_set.0);
 				}
+				action
 			},
 			ObjectActionNet::LockPixel => {
 				if let Some(ref mut hashed_set) = pix_lock {
 					add_or_remove(&mut hashed_set.0);
 				}
+				action
 			},
 			ObjectActionNet::LockAlpha => {
 				if let Some(ref mut hashed_set) = alpha_lock {
 					add_or_remove(&mut hashed_set.0);
 				}
+				action
 			},
 			ObjectActionNet::LayerUp => {
-				let Some(pull_up) = layers.iter().position(|ent| ent == &ent_obj) else {
-					return;
-				};
-				let pull_down = pull_up + 1;
-				if pull_down >= layers.len() {
+				if !layers.raise(ent_obj, &|ent| group_param.parent(ent)) {
 					return;
 				}
-				layers.swap(pull_down, pull_up);
+				ObjectActionNet::LayerDown
 			},
 			ObjectActionNet::LayerDown => {
-				let Some(pull_down) = layers.iter().position(|ent| ent == &ent_obj) else {
+				if !layers.lower(ent_obj, &|ent| group_param.parent(ent)) {
+					return;
+				}
+				ObjectActionNet::LayerUp
+			},
+			ObjectActionNet::LayerTop | ObjectActionNet::LayerBottom => {
+				let is_top = action == ObjectActionNet::LayerTop;
+				let Some(previous_at) =
+					layers.move_edge(ent_obj, is_top, &|ent| group_param.parent(ent))
+				else {
 					return;
 				};
-				let Some(pull_up) = pull_down.checked_sub(1) else {
+				histories
+					.entry(*client_id)
+					.or_default()
+					.push(vec![Change::Layer {
+						ent: ent_obj,
+						at: previous_at,
+					}]);
+				return;
+			},
+			ObjectActionNet::LayerAbove(ent_other) | ObjectActionNet::LayerBelow(ent_other) => {
+				let is_above = matches!(action, ObjectActionNet::LayerAbove(_));
+				let Some(previous_at) = layers
+					.move_beside(ent_obj, ent_other, is_above, &|ent| group_param.parent(ent))
+				else {
 					return;
 				};
-				layers.swap(pull_down, pull_up);
+				let mut step = vec![Change::Layer {
+					ent: ent_obj,
+					at: previous_at,
+				}];
+				let previous_group = group_param.parent(ent_obj);
+				let group = group_param.parent(ent_other);
+				if group != previous_group {
+					match group {
+						Some(ent_group) => cmd.entity(ent_obj).insert(InGroup(ent_group)),
+						None => cmd.entity(ent_obj).remove::<InGroup>(),
+					};
+					step.push(Change::Regroup {
+						ent: ent_obj,
+						group: previous_group,
+					});
+				}
+				histories.entry(*client_id).or_default().push(step);
+				return;
 			},
+			ObjectActionNet::Opacity(value) => {
+				let value = value.clamp(0, 100);
+				if opacity.0 == value {
+					return;
+				}
+				// Dragging send many, settle them into one step
+				let history = histories.entry(*client_id).or_default();
+				history.touch(time.elapsed());
+				history.record_opacity(ent_obj, opacity.0);
+				opacity.0 = value;
+				return;
+			},
+			ObjectActionNet::Blend(draw_type) => {
+				if blend.0 == draw_type {
+					return;
+				}
+				ObjectActionNet::Blend(std::mem::replace(&mut blend.0, draw_type))
+			},
+		};
+		histories
+			.entry(*client_id)
+			.or_default()
+			.push(vec![Change::Lock {
+				ent: ent_obj,
+				action: reverted,
+			}]);
+	});
+}
+
+pub(super) fn rename_object(
+	mut query_object: Query<(&mut SharingName, &ObjectAccess), With<ObjectWorld>>,
+	mut events: EventReader<MessageEvent<RenameObjectNet>>,
+	mut histories: ResMut<Histories>,
+) {
+	events.read().for_each(|event| {
+		let message = event.message();
+		let client_id = event.context();
+		let Ok((mut name, access)) = query_object.get_mut(message.obj_ent) else {
+			return;
+		};
+		if !access.targets(client_id) {
+			return;
+		}
+		let new_name = message
+			.name
+			.trim()
+			.chars()
+			.filter(|c| !c.is_control())
+			.take(MAX_NAME_LEN)
+			.collect::<String>();
+		if new_name.is_empty() || new_name == name.0 {
+			return;
 		}
+		histories
+			.entry(*client_id)
+			.or_default()
+			.push(vec![Change::Rename {
+				ent: message.obj_ent,
+				name: std::mem::replace(&mut name.0, new_name),
+			}]);
 	});
 }
 
@@ -217,9 +450,127 @@ pub(super) fn obj_permission(
 	});
 }
 
+fn send_join_state(
+	mut events: EventReader<MessageEvent<RequestJoinState>>,
+	mut server: ResMut<ConnectionManager>,
+	query_path: Query<Entity, (With<ObjectPath>, With<ObjectWorld>)>,
+	query_user: Query<(Entity, &SelectedObject), With<UserId>>,
+	path_param: PathParam,
+	layers: Res<ObjectOrderZ>,
+	users: Res<Users>,
+) {
+	events.read().for_each(|event| {
+		let client_id = *event.context();
+		let ent_joiner = users.get(&client_id).copied();
+		let mut join_state = JoinState {
+			relations: query_path
+				.iter()
+				.flat_map(|ent_path| path_param.point_pairs(ent_path))
+				.collect(),
+			z_order: layers.to_vec(),
+			selections: query_user
+				.iter()
+				.filter(|(ent_user, _)| Some(*ent_user) != ent_joiner)
+				.map(|(ent_user, selected)| (ent_user, selected.clone()))
+				.collect(),
+		};
+		server
+			.send_message_to_target::<MainChannel, JoinState>(
+				&mut join_state,
+				NetworkTarget::Single(client_id),
+			)
+			.unwrap_or_else(|e| {
+				error!("Fail to send message: {:?}", e);
+			});
+	});
+}
+
+fn moderate_user(
+	mut cmd: Commands,
+	mut events: EventReader<MessageEvent<ModerateNet>>,
+	mut server: ResMut<ConnectionManager>,
+	query_user: Query<(&SharingName, Has<UserAdmin>)>,
+	users: Res<Users>,
+	backend_task_server: Res<BackendTaskServer>,
+	mut pending_disconnect: Local<Vec<(ClientId, Timer)>>,
+	time: Res<Time>,
+) {
+	// Give target time to receive why before link drop
+	pending_disconnect.retain_mut(|(target, timer)| {
+		if !timer.tick(time.delta()).finished() {
+			return true;
+		}
+		cmd.trigger(DisconnectClient(*target));
+		false
+	});
+	events.read().for_each(|event| {
+		let sender = *event.context();
+		let is_admin = matches!(sender, ClientId::Local(_))
+			|| users
+				.get(&sender)
+				.and_then(|ent_user| query_user.get(*ent_user).ok())
+				.is_some_and(|(_, is_admin)| is_admin);
+		if !is_admin {
+			server
+				.send_message_to_target::<MessageChannel, MessageCtx>(
+					&mut MessageCtx("Only admin can kick or ban.".to_owned()),
+					NetworkTarget::Single(sender),
+				)
+				.unwrap_or_else(|e| {
+					error!("Fail to send message: {:?}", e);
+				});
+			return;
+		}
+		let (target, verb) = match *event.message() {
+			ModerateNet::Kick(target) => (target, "kicked"),
+			ModerateNet::Ban(target) => (target, "banned"),
+		};
+		if target == sender {
+			return;
+		}
+		if let ClientId::Netcode(raw_client_id) = target {
+			let mut backend = backend_task_server.write().unwrap();
+			backend
+				.resumes
+				.retain(|_, (client_id, ..)| client_id != &raw_client_id);
+			if let ModerateNet::Ban(_) = event.message() {
+				if let Some(ip) = backend.addresses.get(&raw_client_id).copied() {
+					backend.banned.insert(ip);
+				}
+			}
+		}
+		let name = users
+			.get(&target)
+			.and_then(|ent_user| query_user.get(*ent_user).ok())
+			.map(|(name, _)| name.0.clone())
+			.unwrap_or_else(|| "User".to_owned());
+		let msg = format!("{name} was {verb}.");
+		cmd.trigger(DisplayMsgEvent(msg.clone()));
+		server
+			.send_message_to_target::<MessageChannel, MessageCtx>(
+				&mut MessageCtx(msg),
+				NetworkTarget::All,
+			)
+			.unwrap_or_else(|e| {
+				error!("Fail to send message: {:?}", e);
+			});
+		let mut moderate = *event.message();
+		server
+			.send_message_to_target::<MessageChannel, ModerateNet>(
+				&mut moderate,
+				NetworkTarget::Single(target),
+			)
+			.unwrap_or_else(|e| {
+				error!("Fail to send message: {:?}", e);
+			});
+		pending_disconnect.push((target, Timer::from_seconds(0.5, TimerMode::Once)));
+	});
+}
+
 fn spawn_new_image(
 	mut cmd: Commands,
 	query_user: Query<(&ActionState<VerifyAction>, &CursorPos, &UserId)>,
+	mut histories: ResMut<Histories>,
 ) {
 	query_user.iter().for_each(|(action, cur_pos, user_id)| {
 		if !action.just_pressed(&VerifyAction::NewImage) {
@@ -228,13 +579,19 @@ fn spawn_new_image(
 		let size = UVec2::splat(500);
 		let invert = size.as_vec2() / Vec2::new(2.0, -2.0);
 		let total = size.element_product() * 4;
-		cmd.spawn(RdioImageBundle::new(
-			"New Image",
-			size,
-			vec![0; total as usize],
-			(cur_pos.xy() - invert).round(),
-			user_id.0,
-		));
+		let ent_obj = cmd
+			.spawn(RdioImageBundle::new(
+				"New Image",
+				size,
+				vec![0; total as usize],
+				(cur_pos.xy() - invert).round(),
+				user_id.0,
+			))
+			.id();
+		histories
+			.entry(user_id.0)
+			.or_default()
+			.push(vec![Change::Despawn { ent: ent_obj }]);
 	});
 }
 
@@ -301,6 +658,7 @@ fn spawn_point(
 	)>,
 	query_parent: Query<&Parent, With<ObjectPoint>>,
 	query_leaf: Query<(Entity, &Parent), Leaf<PointToPoint>>,
+	mut histories: ResMut<Histories>,
 ) {
 	query_user
 		.iter()
@@ -311,29 +669,33 @@ fn spawn_point(
 
 			let new_point = cmd.spawn(PointBundle::new(cur_pos.xy())).id();
 
-			let mut spawn_pre_point = || -> Entity {
+			let mut spawn_pre_point = || -> (Entity, Change) {
 				let spawn_pre_point = cmd
 					.spawn(PointBundle::new(cur_pos.xy() - Vec2::new(12.0, 0.0)))
 					.id();
-				cmd.spawn(RdioPathBundle::new("The Main Path", user_id.0))
-					.push_children(&[spawn_pre_point, new_point]);
-				spawn_pre_point
+				let ent_path = cmd
+					.spawn(RdioPathBundle::new("The Main Path", user_id.0))
+					.push_children(&[spawn_pre_point, new_point])
+					.id();
+				(spawn_pre_point, Change::Despawn { ent: ent_path })
 			};
 
-			let pre_point = if let Some(main_ent) = selected_obj.single {
+			let (pre_point, revert) = if let Some(main_ent) = selected_obj.single {
 				if let Ok(parent) = query_parent.get(main_ent) {
 					let ent_parent = parent.get();
 					cmd.entity(ent_parent).add_child(new_point);
-					query_leaf
+					let leaf = query_leaf
 						.iter()
 						.find_map(|(ent, parent)| (parent.get() == ent_parent).then_some(ent))
-						.unwrap()
+						.unwrap();
+					(leaf, Change::DespawnPoint { ent: new_point })
 				} else {
 					spawn_pre_point()
 				}
 			} else {
 				spawn_pre_point()
 			};
+			histories.entry(user_id.0).or_default().push(vec![revert]);
 
 			let relationser = ConnectRelations::<PointToPoint>::new(pre_point, new_point);
 			cmd.trigger(relationser);
@@ -343,35 +705,181 @@ fn spawn_point(
 fn spawn_text(
 	query_user: Query<(&CursorPos, &ActionState<VerifyAction>, &UserId)>,
 	mut cmd: Commands,
+	mut histories: ResMut<Histories>,
 ) {
 	query_user.iter().for_each(|(cur_pos, action, user_id)| {
 		if !action.just_pressed(&VerifyAction::AddText) {
 			return;
 		}
-		cmd.spawn(WorldTextBundle::new(
-			"New Text",
-			"Hello World".to_owned(),
-			cur_pos.xy(),
-			user_id.0,
-		));
+		let ent_obj = cmd
+			.spawn(WorldTextBundle::new(
+				"New Text",
+				"Hello World".to_owned(),
+				cur_pos.xy(),
+				user_id.0,
+			))
+			.id();
+		histories
+			.entry(user_id.0)
+			.or_default()
+			.push(vec![Change::Despawn { ent: ent_obj }]);
+	});
+}
+
+fn spawn_shape(
+	mut cmd: Commands,
+	mut events: EventReader<MessageEvent<NewShapeNet>>,
+	query_user: Query<&PaintInk>,
+	users: Res<Users>,
+	mut histories: ResMut<Histories>,
+) {
+	events.read().for_each(|event| {
+		let client_id = event.context();
+		let Some(paint) = users
+			.get(client_id)
+			.and_then(|ent_user| query_user.get(*ent_user).ok())
+		else {
+			return;
+		};
+		let NewShapeNet { kind, from, to } = *event.message();
+		if !from.is_finite() || !to.is_finite() {
+			return;
+		}
+		let maxed = Vec2::splat(MAX_VALID_RANGE);
+		let (from, to) = (from.clamp(-maxed, maxed), to.clamp(-maxed, maxed));
+		if (to - from).abs().min_element() < 1.0 {
+			return;
+		}
+		let ent_points = kind
+			.points(from, to)
+			.into_iter()
+			.map(|(position, point_type)| {
+				cmd.spawn(PointBundle::new(position))
+					.insert(point_type)
+					.id()
+			})
+			.collect::<Vec<_>>();
+		let ent_path = cmd
+			.spawn(RdioPathBundle::new(kind.name(), *client_id))
+			.insert((
+				StrokeNet {
+					color: paint.0.into(),
+					..default()
+				},
+				FillNet {
+					color: paint.1.into(),
+					..default()
+				},
+				PathClose(true),
+			))
+			.push_children(&ent_points)
+			.id();
+		ent_points.windows(2).for_each(|pair| {
+			cmd.trigger(ConnectRelations::<PointToPoint>::new(pair[0], pair[1]));
+		});
+		histories
+			.entry(*client_id)
+			.or_default()
+			.push(vec![Change::Despawn { ent: ent_path }]);
+	});
+}
+
+fn open_rdio(
+	mut events: EventReader<MessageEvent<SavedRdio>>,
+	mut cmd: Commands,
+	mut server: ResMut<ConnectionManager>,
+	query_user: Query<Has<UserAdmin>>,
+	query_root: Query<Entity, (With<ObjectWorld>, Without<ObjectPoint>)>,
+	users: Res<Users>,
+) {
+	events.read().for_each(|event| {
+		let owner = *event.context();
+		let is_admin = matches!(owner, ClientId::Local(_))
+			|| users
+				.get(&owner)
+				.and_then(|ent_user| query_user.get(*ent_user).ok())
+				.unwrap_or_default();
+		let refused = if !is_admin {
+			Some("Only admin can open document.")
+		} else if !event.message().within_limit() {
+			Some("Document too large to open.")
+		} else {
+			None
+		};
+		if let Some(reason) = refused {
+			server
+				.send_message_to_target::<MessageChannel, MessageCtx>(
+					&mut MessageCtx(reason.to_owned()),
+					NetworkTarget::Single(owner),
+				)
+				.unwrap_or_else(|e| {
+					error!("Fail to send message: {:?}", e);
+				});
+			return;
+		}
+		// Opened document replace the board, old undo point to gone objects
+		query_root.iter().for_each(|ent_obj| {
+			cmd.entity(ent_obj).despawn_recursive();
+		});
+		cmd.add(|world: &mut World| world.resource_mut::<Histories>().clear());
+		event.message().clone().spawn_objects(&mut cmd, owner);
+	});
+}
+
+/// Imported objects join the board, undo remove them again
+fn import_rdio(
+	mut events: EventReader<MessageEvent<ImportRdio>>,
+	mut cmd: Commands,
+	mut server: ResMut<ConnectionManager>,
+	mut histories: ResMut<Histories>,
+) {
+	events.read().for_each(|event| {
+		let owner = *event.context();
+		let ImportRdio(saved_rdio) = event.message().clone();
+		if !saved_rdio.within_limit() {
+			server
+				.send_message_to_target::<MessageChannel, MessageCtx>(
+					&mut MessageCtx("Import too large to add.".to_owned()),
+					NetworkTarget::Single(owner),
+				)
+				.unwrap_or_else(|e| {
+					error!("Fail to send message: {:?}", e);
+				});
+			return;
+		}
+		// Group before its members, so redo restore group first
+		let step = saved_rdio
+			.spawn_objects(&mut cmd, owner)
+			.into_iter()
+			.map(|ent| Change::Despawn { ent })
+			.collect();
+		histories.entry(owner).or_default().push(step);
 	});
 }
 
 fn move_object(
+	mut cmd: Commands,
 	mut events: EventReader<MessageEvent<MovedPoint>>,
 	mut query_object: Query<
 		(
+			Entity,
 			&mut ObjectPosition,
 			Option<&MoveLock>,
 			Option<&Parent>,
 			Has<ProcessImage>,
+			Has<ObjectPoint>,
 			Option<&ObjectAccess>,
 		),
 		With<ObjectWorld>,
 	>,
 	query_user: Query<&SelectedObject, With<UserId>>,
 	query_path: Query<(&MoveLock, &ObjectAccess), With<ObjectPath>>,
+	query_children: Query<&Children, With<ObjectPath>>,
+	query_group_lock: Query<&MoveLock, With<ObjectGroup>>,
+	group_param: GroupParam,
 	users: Res<Users>,
+	mut histories: ResMut<Histories>,
+	time: Res<Time>,
 ) {
 	events.read().for_each(|event| {
 		let client_id = event.context();
@@ -382,19 +890,52 @@ fn move_object(
 			return;
 		};
 
-		let mut selected_exist = query_object.iter_many_mut(selected_obj.group.iter());
-		while let Some((mut obj_pos, op_move_lock, op_parent, is_image, op_access)) =
-			selected_exist.fetch_next()
+		// Group move everything inside, path inside group move by its points
+		let mut moving = group_param.expand(selected_obj.group.iter().copied());
+		let points = moving
+			.iter()
+			.filter(|ent| !selected_obj.group.contains(ent))
+			.filter_map(|ent| query_children.get(*ent).ok())
+			.flatten()
+			.copied()
+			.collect::<Vec<_>>();
+		points.into_iter().for_each(|ent_point| {
+			if !moving.contains(&ent_point) {
+				moving.push(ent_point);
+			}
+		});
+
+		let history = histories.entry(*client_id).or_default();
+		history.touch(time.elapsed());
+		let mut moved_points = Vec::new();
+		let mut selected_exist = query_object.iter_many_mut(moving.iter());
+		while let Some((
+			ent_obj,
+			mut obj_pos,
+			op_move_lock,
+			op_parent,
+			is_image,
+			is_point,
+			op_access,
+		)) = selected_exist.fetch_next()
 		{
 			let move_lock =
 				op_move_lock.unwrap_or_else(|| query_path.get(op_parent.unwrap().get()).unwrap().0);
-			if move_lock.contains(client_id)
+			let ent_top = op_parent.map_or(ent_obj, |parent| parent.get());
+			let is_group_lock = group_param
+				.ancestors(ent_top)
+				.into_iter()
+				.filter_map(|ent_group| query_group_lock.get(ent_group).ok())
+				.any(|group_lock| group_lock.contains(client_id));
+			if is_group_lock
+				|| move_lock.contains(client_id)
 				|| !op_access
 					.unwrap_or_else(|| query_path.get(op_parent.unwrap().get()).unwrap().1)
 					.targets(client_id)
 			{
 				continue;
 			}
+			history.record_position(ent_obj, obj_pos.0);
 			if is_image {
 				let moved = event.message().pixel.xy().as_vec2();
 				obj_pos.0 = (obj_pos.0 + moved).round();
@@ -404,6 +945,13 @@ fn move_object(
 			}
 			let maxed = Vec2::splat(MAX_VALID_RANGE);
 			obj_pos.0 = obj_pos.0.clamp(-maxed, maxed);
+			if is_point {
+				moved_points.push(ent_obj);
+			}
+		}
+		if !moved_points.is_empty() {
+			let (client_id, moved) = (*client_id, event.message().world);
+			cmd.add(move |world: &mut World| follow_handles(world, client_id, moved_points, moved));
 		}
 	});
 }
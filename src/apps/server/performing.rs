@@ -1,24 +1,49 @@
 use bevy::prelude::*;
 use image::EncodableLayout;
 use leafwing_input_manager::action_diff::ActionDiff;
+use rand::{distributions::Alphanumeric, Rng};
 use serde::de::DeserializeOwned;
 
 use super::*;
 
 pub(super) fn insert_server_source(world: &mut World) {
-	let client_ids = Arc::new(RwLock::new(BackendItem::default()));
+	let settings = world.resource::<ServerSettings>().clone();
+	// Empty admin password would let everyone in as admin
+	let password_admin = match settings.password_admin.filter(|pass| !pass.is_empty()) {
+		Some(password_admin) => password_admin,
+		None => {
+			let password_admin: String = rand::thread_rng()
+				.sample_iter(&Alphanumeric)
+				.take(8)
+				.map(char::from)
+				.collect();
+			// Only shown when generated, operator already know the one they set
+			info!("Admin password: {}", password_admin);
+			world.trigger(DisplayMsgEvent(format!("Admin password: {password_admin}")));
+			password_admin
+		},
+	};
+	let client_ids = Arc::new(RwLock::new(BackendItem {
+		password: settings.password,
+		password_admin,
+		..default()
+	}));
 	world.init_resource::<Users>();
 	world.init_resource::<ObjectOrderZ>();
 	world.init_resource::<ObjectIncrementCount>();
+	world.init_resource::<Histories>();
+	world.init_resource::<ParkedClients>();
 	world.insert_resource(BackendTaskServer(client_ids));
 
 	let all_server_observe = [
 		world.observe(on_remove_objects).id(),
 		world.observe(on_add_objects).id(),
 		world.observe(handle_connect_events).id(),
-		// world.observe(handle_disconnect_events).id(),
+		world.observe(handle_disconnect_events).id(),
+		world.observe(release_client).id(),
 		world.observe(new_user_id).id(),
 		world.observe(remove_user).id(),
+		world.observe(history_bir).id(),
 	];
 	all_server_observe.into_iter().for_each(|ent_obs| {
 		world
@@ -32,17 +57,31 @@ pub(super) fn remove_server_source(world: &mut World) {
 	world.remove_resource::<Users>();
 	world.remove_resource::<ObjectOrderZ>();
 	world.remove_resource::<ObjectIncrementCount>();
+	world.remove_resource::<Histories>();
+	world.remove_resource::<ParkedClients>();
 	world.remove_resource::<BackendTaskServer>();
+	world.remove_resource::<AutosaveTimer>();
 }
 
 pub(super) fn replicate_cursor(
 	mut cmd: Commands,
 	mut users: ResMut<Users>,
+	backend_task_server: Res<BackendTaskServer>,
 	replicated_cursor: Query<(Entity, &Replicated), (With<CursorPos>, Added<Replicated>)>,
 ) {
 	replicated_cursor.iter().for_each(|(ent_user, replicated)| {
 		let client_id = replicated.client_id();
 		users.insert(client_id, ent_user);
+		if let ClientId::Netcode(raw_client_id) = client_id {
+			if backend_task_server
+				.read()
+				.unwrap()
+				.admins
+				.contains(&raw_client_id)
+			{
+				cmd.entity(ent_user).insert(UserAdmin);
+			}
+		}
 		cmd.entity(ent_user).insert((
 			Replicate {
 				target: ReplicationTarget {
@@ -117,12 +156,19 @@ pub(super) fn send_image_data(
 pub(super) fn update_z_layer(
 	mut query_object: Query<&mut ObjectZLayer, (Without<ObjectPoint>, With<ObjectWorld>)>,
 	layers: Res<ObjectOrderZ>,
+	mut is_warned: Local<bool>,
 ) {
+	if layers.len() > MAX_Z_OBJECTS && !*is_warned {
+		*is_warned = true;
+		warn!(
+			"More than {} objects, objects above share one z and lose their order",
+			MAX_Z_OBJECTS
+		);
+	}
 	let mut n = 0;
 	let mut many_obj = query_object.iter_many_mut(layers.iter());
 	while let Some(mut obj_z) = many_obj.fetch_next() {
-		let cal = BEGIN_OBJ_Z_INDEX as i16 + n;
-		obj_z.set_if_neq(ObjectZLayer(cal));
+		obj_z.set_if_neq(ObjectZLayer::at(n));
 		n += 1;
 	}
 }
@@ -132,6 +178,7 @@ pub(super) fn receive_img_data(
 	mut cmd: Commands,
 	query_user: Query<&CursorPos>,
 	users: Res<Users>,
+	mut histories: ResMut<Histories>,
 ) {
 	if events.is_empty() {
 		return;
@@ -151,13 +198,19 @@ pub(super) fn receive_img_data(
 		let cost_pos = img_net.size.as_vec2() / Vec2::new(-2.0, 2.0);
 		let center_img = (real_world_ray.floor() + cost_pos).round();
 
-		cmd.spawn(RdioImageBundle::new(
-			&img_net.name,
-			img_net.size,
-			decode_data,
-			center_img,
-			*event.context(),
-		));
+		let ent_obj = cmd
+			.spawn(RdioImageBundle::new(
+				&img_net.name,
+				img_net.size,
+				decode_data,
+				center_img,
+				*event.context(),
+			))
+			.id();
+		histories
+			.entry(*event.context())
+			.or_default()
+			.push(vec![Change::Despawn { ent: ent_obj }]);
 	});
 }
 
@@ -237,6 +290,8 @@ pub(super) fn process_incoming_message(
 pub(super) fn delete_object(
 	query_user: Query<(&SelectedObject, &ActionState<VerifyAction>, &UserId)>,
 	query_object: Query<(Entity, &ObjectAccess), With<ObjectWorld>>,
+	group_param: GroupParam,
+	mut server: ResMut<ConnectionManager>,
 	mut cmd: Commands,
 ) {
 	query_user
@@ -245,14 +300,30 @@ pub(super) fn delete_object(
 			if !action.just_pressed(&VerifyAction::DeleteObject) {
 				return;
 			}
-			for (ent_obj, perm) in query_object.iter_many(selected_obj.group.iter()) {
-				if !perm.targets(&user_id.0) {
-					return;
-				}
-				if let Some(mut ent_cmd) = cmd.get_entity(ent_obj) {
-					ent_cmd.despawn();
-				}
+			let client_id = user_id.0;
+			// Group before its members, so undo restore group first
+			let selected = group_param.expand(selected_obj.group.iter().copied());
+			let mut changes = Vec::new();
+			let mut is_denied = false;
+			query_object
+				.iter_many(selected.iter())
+				.for_each(|(ent_obj, perm)| {
+					is_denied |= !perm.targets(&client_id);
+					changes.push(Change::Despawn { ent: ent_obj });
+				});
+			// Group is deleted whole or not at all
+			if is_denied {
+				server
+					.send_message_to_target::<MessageChannel, MessageCtx>(
+						&mut MessageCtx("Some selected object can not be deleted.".to_owned()),
+						NetworkTarget::Single(client_id),
+					)
+					.unwrap_or_else(|e| {
+						error!("Fail to send message: {:?}", e);
+					});
+				return;
 			}
+			cmd.add(move |world: &mut World| apply_changes(world, client_id, changes));
 		});
 }
 
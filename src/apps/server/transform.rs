@@ -0,0 +1,521 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use image::{
+	imageops::{crop_imm, flip_horizontal, flip_vertical, overlay, rotate180, rotate270, rotate90},
+	Rgba, RgbaImage,
+};
+use imageproc::geometric_transformations::{rotate_about_center, Interpolation};
+
+use super::*;
+
+/// Rect of image in world, position is top left
+fn image_rect(pos: Vec2, size: UVec2) -> Rect {
+	let size = size.as_vec2();
+	Rect::new(pos.x, pos.y - size.y, pos.x + size.x, pos.y)
+}
+
+/// Canvas size holding image of `size` turned by `degree`
+fn rotated_fit(size: UVec2, degree: f32) -> UVec2 {
+	let size = size.as_vec2();
+	let (sin, cos) = (degree * PI / 180.0).sin_cos();
+	Vec2::new(
+		size.x * cos.abs() + size.y * sin.abs(),
+		size.x * sin.abs() + size.y * cos.abs(),
+	)
+	.ceil()
+	.as_uvec2()
+}
+
+/// Canvas grow to fit the rotated image, outside is transparent
+fn rotate_resample(img: &RgbaImage, degree: f32, filter: RotateFilter) -> RgbaImage {
+	let theta = degree * PI / 180.0;
+	let (width, height) = img.dimensions();
+	let fit = rotated_fit(UVec2::new(width, height), degree);
+	let mut canvas = RgbaImage::new(fit.x, fit.y);
+	let offset = (fit - UVec2::new(width, height)) / 2;
+	overlay(&mut canvas, img, offset.x as i64, offset.y as i64);
+	let interpolation = match filter {
+		RotateFilter::Nearest => Interpolation::Nearest,
+		RotateFilter::Bilinear => Interpolation::Bilinear,
+		RotateFilter::Bicubic => Interpolation::Bicubic,
+	};
+	rotate_about_center(&canvas, theta, interpolation, Rgba([0, 0, 0, 0]))
+}
+
+/// New image and its center in world, [None] when nothing change
+fn transform_one(
+	img: &RgbaImage,
+	center: Vec2,
+	pivot: Vec2,
+	transform: ImageTransformNet,
+) -> Option<(RgbaImage, Vec2)> {
+	let relative = center - pivot;
+	match transform {
+		ImageTransformNet::FlipHorizontal => Some((
+			flip_horizontal(img),
+			pivot + Vec2::new(-relative.x, relative.y),
+		)),
+		ImageTransformNet::FlipVertical => Some((
+			flip_vertical(img),
+			pivot + Vec2::new(relative.x, -relative.y),
+		)),
+		ImageTransformNet::Rotate(degree, filter) => {
+			let degree = degree.rem_euclid(360.0);
+			if degree == 0.0 || !degree.is_finite() {
+				return None;
+			}
+			let rotated = if degree == 90.0 {
+				rotate90(img)
+			} else if degree == 180.0 {
+				rotate180(img)
+			} else if degree == 270.0 {
+				rotate270(img)
+			} else {
+				rotate_resample(img, degree, filter)
+			};
+			// Clockwise on screen, world y go up
+			let (sin, cos) = (degree * PI / 180.0).sin_cos();
+			let turned = Vec2::new(
+				relative.x * cos + relative.y * sin,
+				-relative.x * sin + relative.y * cos,
+			);
+			Some((rotated, pivot + turned))
+		},
+	}
+}
+
+pub(super) fn transform_image(
+	mut events: EventReader<MessageEvent<ImageTransformNet>>,
+	mut query_object: Query<
+		(
+			Entity,
+			&mut ProcessImage,
+			&mut ObjectPosition,
+			&PixelLock,
+			&ObjectAccess,
+		),
+		With<ObjectImage>,
+	>,
+	query_user: Query<&SelectedObject, With<UserId>>,
+	users: Res<Users>,
+	mut server: ResMut<ConnectionManager>,
+	mut histories: ResMut<Histories>,
+) {
+	events.read().for_each(|event| {
+		let client_id = event.context();
+		let Some(selected_obj) = users
+			.get(client_id)
+			.and_then(|ent_user| query_user.get(*ent_user).ok())
+		else {
+			return;
+		};
+		let allowed = query_object
+			.iter_many(selected_obj.group.iter())
+			.filter(|(_, _, _, pixel_lock, access)| {
+				access.targets(client_id) && !pixel_lock.contains(client_id)
+			})
+			.map(|(ent_obj, ..)| ent_obj)
+			.collect::<Vec<_>>();
+		let Some(bound) = query_object
+			.iter_many(allowed.iter())
+			.map(|(_, process_img, obj_pos, ..)| {
+				image_rect(obj_pos.0, process_img.dimensions().into())
+			})
+			.reduce(|rect, other| rect.union(other))
+		else {
+			return;
+		};
+		let pivot = bound.center();
+		// Resampled rotation grow the canvas, keep it within resize limit
+		if let ImageTransformNet::Rotate(degree, _) = *event.message() {
+			let degree = degree.rem_euclid(360.0);
+			let is_resampled = degree.is_finite() && degree % 90.0 != 0.0;
+			let is_too_large = is_resampled
+				&& query_object
+					.iter_many(allowed.iter())
+					.any(|(_, process_img, ..)| {
+						let fit = rotated_fit(process_img.dimensions().into(), degree);
+						fit.max_element() as f32 > MAX_SCALE_SIDE
+					});
+			if is_too_large {
+				server
+					.send_message_to_target::<MessageChannel, MessageCtx>(
+						&mut MessageCtx("Rotated image would be too large.".to_owned()),
+						NetworkTarget::Single(*client_id),
+					)
+					.unwrap_or_else(|e| {
+						error!("Fail to send message: {:?}", e);
+					});
+				return;
+			}
+		}
+
+		let mut step = Vec::new();
+		let mut many_obj = query_object.iter_many_mut(allowed.iter());
+		while let Some((ent_obj, mut process_img, mut obj_pos, ..)) = many_obj.fetch_next() {
+			let size: UVec2 = process_img.dimensions().into();
+			let center = image_rect(obj_pos.0, size).center();
+			let Some((new_img, new_center)) =
+				transform_one(&process_img.0, center, pivot, *event.message())
+			else {
+				continue;
+			};
+			let new_size = UVec2::from(new_img.dimensions()).as_vec2();
+			let new_pos = (new_center + Vec2::new(-new_size.x, new_size.y) / 2.0).round();
+			step.push(Change::Image {
+				ent: ent_obj,
+				pos: obj_pos.0,
+				size,
+				data: DataHold::to_compress(process_img.as_raw()),
+			});
+			process_img.0 = new_img;
+			obj_pos.0 = new_pos;
+			server
+				.send_message_to_target::<MainChannel, ReceiveImageData>(
+					&mut ReceiveImageData::new(
+						ent_obj,
+						process_img.as_raw(),
+						process_img.dimensions().into(),
+					),
+					NetworkTarget::All,
+				)
+				.unwrap_or_else(|e| {
+					error!("Fail to send message: {:?}", e);
+				});
+		}
+		histories.entry(*client_id).or_default().push(step);
+	});
+}
+
+pub(super) fn crop_image(
+	mut events: EventReader<MessageEvent<CropImageNet>>,
+	mut query_object: Query<
+		(
+			&mut ProcessImage,
+			&mut ObjectPosition,
+			&PixelLock,
+			&ObjectAccess,
+		),
+		With<ObjectImage>,
+	>,
+	query_user: Query<&SelectedObject, With<UserId>>,
+	users: Res<Users>,
+	mut server: ResMut<ConnectionManager>,
+	mut histories: ResMut<Histories>,
+) {
+	events.read().for_each(|event| {
+		let client_id = event.context();
+		let Some(selected_obj) = users
+			.get(client_id)
+			.and_then(|ent_user| query_user.get(*ent_user).ok())
+		else {
+			return;
+		};
+		let CropImageNet(begin, end) = *event.message();
+		let crop_rect = Rect::from_corners(begin.round(), end.round());
+
+		let mut step = Vec::new();
+		selected_obj.group.iter().for_each(|ent_obj| {
+			let Ok((mut process_img, mut obj_pos, pixel_lock, access)) =
+				query_object.get_mut(*ent_obj)
+			else {
+				return;
+			};
+			if !access.targets(client_id) || pixel_lock.contains(client_id) {
+				return;
+			}
+			let size: UVec2 = process_img.dimensions().into();
+			let kept = image_rect(obj_pos.0, size).intersect(crop_rect);
+			// Fully outside or untouched
+			if kept.is_empty() || kept == image_rect(obj_pos.0, size) {
+				return;
+			}
+			let min = Vec2::new(kept.min.x - obj_pos.0.x, obj_pos.0.y - kept.max.y).as_uvec2();
+			let kept_size = kept.size().as_uvec2();
+			let cropped =
+				crop_imm(&process_img.0, min.x, min.y, kept_size.x, kept_size.y).to_image();
+			step.push(Change::Image {
+				ent: *ent_obj,
+				pos: obj_pos.0,
+				size,
+				data: DataHold::to_compress(process_img.as_raw()),
+			});
+			process_img.0 = cropped;
+			obj_pos.0 = Vec2::new(kept.min.x, kept.max.y);
+			server
+				.send_message_to_target::<MainChannel, ReceiveImageData>(
+					&mut ReceiveImageData::new(*ent_obj, process_img.as_raw(), kept_size),
+					NetworkTarget::All,
+				)
+				.unwrap_or_else(|e| {
+					error!("Fail to send message: {:?}", e);
+				});
+		});
+		histories.entry(*client_id).or_default().push(step);
+	});
+}
+
+fn scale_point(point: Vec2, pivot: Vec2, ratio: Vec2) -> Vec2 {
+	pivot + (point - pivot) * ratio
+}
+
+/// Commit what Scale / Resize tool previewed, image resample and path scale point positions
+pub(super) fn commit_resize(
+	mut events: EventReader<MessageEvent<ApplyChange>>,
+	query_user: Query<(&SelectedObject, &ScaleAction, &ResizeKind, &ScalePosition), With<UserId>>,
+	mut query_image: Query<
+		(
+			&mut ProcessImage,
+			&mut ObjectPosition,
+			&PixelLock,
+			&ObjectAccess,
+		),
+		(With<ObjectImage>, Without<ObjectPoint>),
+	>,
+	query_path: Query<(&Children, &ObjectAccess, &MoveLock), With<ObjectPath>>,
+	query_parent: Query<&Parent, With<ObjectPoint>>,
+	mut query_point: Query<(&mut ObjectPosition, &mut PointType), With<ObjectPoint>>,
+	users: Res<Users>,
+	mut server: ResMut<ConnectionManager>,
+	mut histories: ResMut<Histories>,
+) {
+	events.read().for_each(|event| {
+		if !event.message().0 {
+			return;
+		}
+		let client_id = event.context();
+		let Some((selected_obj, scale_action, kind, scale_pos)) = users
+			.get(client_id)
+			.and_then(|ent_user| query_user.get(*ent_user).ok())
+		else {
+			return;
+		};
+		let Some(scale) = scale_action.0 else {
+			return;
+		};
+
+		let mut step = Vec::new();
+		selected_obj.group.iter().for_each(|ent_obj| {
+			let Ok((mut process_img, mut obj_pos, pixel_lock, access)) =
+				query_image.get_mut(*ent_obj)
+			else {
+				return;
+			};
+			if !access.targets(client_id) || pixel_lock.contains(client_id) {
+				return;
+			}
+			let size: UVec2 = process_img.dimensions().into();
+			let Some((new_size, new_pos)) = scale_pos.apply(size.as_vec2(), obj_pos.0, scale)
+			else {
+				return;
+			};
+			let offset = Vec2::new(obj_pos.0.x - new_pos.x, new_pos.y - obj_pos.0.y);
+			let resized = resample_image(
+				&process_img.0,
+				new_size.as_uvec2(),
+				*kind,
+				offset.as_i64vec2(),
+			);
+			step.push(Change::Image {
+				ent: *ent_obj,
+				pos: obj_pos.0,
+				size,
+				data: DataHold::to_compress(process_img.as_raw()),
+			});
+			process_img.0 = resized;
+			obj_pos.0 = new_pos;
+			server
+				.send_message_to_target::<MainChannel, ReceiveImageData>(
+					&mut ReceiveImageData::new(*ent_obj, process_img.as_raw(), new_size.as_uvec2()),
+					NetworkTarget::All,
+				)
+				.unwrap_or_else(|e| {
+					error!("Fail to send message: {:?}", e);
+				});
+		});
+
+		// Resize only make sense for pixels
+		if kind == &ResizeKind::Resize {
+			histories.entry(*client_id).or_default().push(step);
+			return;
+		}
+		let paths = selected_obj
+			.group
+			.iter()
+			.map(|ent| query_parent.get(*ent).map_or(*ent, |parent| parent.get()))
+			.filter(|ent| query_path.contains(*ent))
+			.collect::<HashSet<_>>();
+		paths.into_iter().for_each(|ent_path| {
+			let Ok((children, access, move_lock)) = query_path.get(ent_path) else {
+				return;
+			};
+			if !access.targets(client_id) || move_lock.contains(client_id) {
+				return;
+			}
+			let Some(bound) = query_point
+				.iter_many(children.iter())
+				.map(|(point_pos, _)| Rect::from_center_size(point_pos.0, Vec2::ZERO))
+				.reduce(|rect, other| rect.union(other))
+			else {
+				return;
+			};
+			let size = bound.size();
+			let top_left = Vec2::new(bound.min.x, bound.max.y);
+			let Some((new_size, _)) = scale_pos.apply(size, top_left, scale) else {
+				return;
+			};
+			let pivot = scale_pos.pivot(size, top_left);
+			// Flat path keep its flat axis
+			let ratio = Vec2::select(size.cmpgt(Vec2::ZERO), new_size / size, Vec2::ONE);
+
+			children.iter().for_each(|ent_point| {
+				let Ok((mut point_pos, mut point_type)) = query_point.get_mut(*ent_point) else {
+					return;
+				};
+				step.push(Change::Position {
+					ent: *ent_point,
+					pos: point_pos.0,
+				});
+				point_pos.0 = scale_point(point_pos.0, pivot, ratio);
+				let scaled = match point_type.clone() {
+					PointType::LineTo | PointType::MoveTo => return,
+					PointType::QuadraticBezier { ctrl } => PointType::QuadraticBezier {
+						ctrl: scale_point(ctrl, pivot, ratio),
+					},
+					PointType::CubricBezier { ctrl1, ctrl2, to } => PointType::CubricBezier {
+						ctrl1: scale_point(ctrl1, pivot, ratio),
+						ctrl2: scale_point(ctrl2, pivot, ratio),
+						to: scale_point(to, pivot, ratio),
+					},
+					PointType::Arc {
+						radii,
+						sweep_angle,
+						x_rotation,
+					} => PointType::Arc {
+						radii: radii * ratio,
+						sweep_angle,
+						x_rotation,
+					},
+				};
+				step.push(Change::Curve {
+					ent: *ent_point,
+					point_type: point_type.clone(),
+				});
+				*point_type = scaled;
+			});
+		});
+		histories.entry(*client_id).or_default().push(step);
+	});
+}
+
+/// Pixels region which can be reverted, clipped to image
+fn pixel_region(min: IVec2, size: UVec2, limit: UVec2) -> Option<URect> {
+	let region = IRect::from_corners(min, min + size.as_ivec2())
+		.intersect(IRect::from_corners(IVec2::ZERO, limit.as_ivec2()));
+	(!region.is_empty()).then(|| region.as_urect())
+}
+
+pub(super) fn floating_selection(
+	mut events: EventReader<MessageEvent<PixelSelectNet>>,
+	mut query_object: Query<
+		(&mut ProcessImage, &PixelLock, &AlphaLock, &ObjectAccess),
+		With<ObjectImage>,
+	>,
+	mut server: ResMut<ConnectionManager>,
+	mut histories: ResMut<Histories>,
+) {
+	events.read().for_each(|event| {
+		let client_id = event.context();
+		let ent_obj = match event.message() {
+			PixelSelectNet::Lift(mask) => mask.ent,
+			PixelSelectNet::Anchor { ent, .. } => *ent,
+		};
+		let Ok((mut process_img, pixel_lock, alpha_lock, access)) = query_object.get_mut(ent_obj)
+		else {
+			return;
+		};
+		if !access.targets(client_id)
+			|| pixel_lock.contains(client_id)
+			|| alpha_lock.contains(client_id)
+		{
+			return;
+		}
+		let limit: UVec2 = process_img.dimensions().into();
+		let (region, previous) = match event.message() {
+			PixelSelectNet::Lift(mask) => {
+				// Image changed since client selected
+				if mask.size != limit {
+					return;
+				}
+				let Ok(bits) = mask.data.uncompress() else {
+					return;
+				};
+				// One bit per pixel, else bound and cleared pixels disagree
+				if bits.len() != limit.element_product() as usize {
+					return;
+				}
+				let Some(bound) = mask_bound(&bits, limit) else {
+					return;
+				};
+				let previous = crop_imm(
+					&process_img.0,
+					bound.min.x,
+					bound.min.y,
+					bound.width(),
+					bound.height(),
+				)
+				.to_image();
+				process_img
+					.pixels_mut()
+					.zip(bits)
+					.filter(|(_, bit)| *bit != 0)
+					.for_each(|(pix, _)| *pix = Rgba([0; 4]));
+				(bound, previous)
+			},
+			PixelSelectNet::Anchor {
+				min, size, data, ..
+			} => {
+				let Some(floating) = data
+					.uncompress()
+					.ok()
+					.and_then(|data| RgbaImage::from_vec(size.x, size.y, data))
+				else {
+					return;
+				};
+				let Some(region) = pixel_region(*min, *size, limit) else {
+					return;
+				};
+				let previous = crop_imm(
+					&process_img.0,
+					region.min.x,
+					region.min.y,
+					region.width(),
+					region.height(),
+				)
+				.to_image();
+				overlay(&mut process_img.0, &floating, min.x as i64, min.y as i64);
+				(region, previous)
+			},
+		};
+		histories
+			.entry(*client_id)
+			.or_default()
+			.push(vec![pixels_change(
+				ent_obj,
+				region.min,
+				&previous,
+				&process_img.0,
+			)]);
+		let size = region.size();
+		let area = crop_imm(&process_img.0, region.min.x, region.min.y, size.x, size.y).to_image();
+		server
+			.send_message_to_target::<MainChannel, ReceiveImagePatch>(
+				&mut ReceiveImagePatch::new(ent_obj, area.as_raw(), region.min, size),
+				NetworkTarget::All,
+			)
+			.unwrap_or_else(|e| {
+				error!("Fail to send message: {:?}", e);
+			});
+	});
+}
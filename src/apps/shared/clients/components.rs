@@ -39,7 +39,9 @@ pub struct ClientUserBundle {
 	hard_edge: HardEdgeDraw,
 	draw_type: DrawType,
 	selection: Selection,
+	pixel_mask: PixelMask,
 	blur_scale: BlurScale,
+	bucket: BucketSettings,
 }
 
 impl ClientUserBundle {
@@ -84,6 +86,55 @@ pub enum ScalePosition {
 	Middle,
 }
 
+/// Largest side Scale / Resize tool can make
+pub const MAX_SCALE_SIDE: f32 = 2000.0;
+
+impl ScalePosition {
+	/// Growth per dragged distance, and fixed point as fraction from top left
+	fn handle(&self) -> (Vec2, Vec2) {
+		use ScalePosition::*;
+		match self {
+			Top => (Vec2::new(0.0, -1.0), Vec2::new(0.0, 1.0)),
+			Bottom => (Vec2::new(0.0, 1.0), Vec2::ZERO),
+			Left => (Vec2::new(1.0, 0.0), Vec2::new(1.0, 0.0)),
+			Right => (Vec2::new(-1.0, 0.0), Vec2::ZERO),
+			TopLeft => (Vec2::new(1.0, -1.0), Vec2::ONE),
+			TopRight => (Vec2::new(-1.0, -1.0), Vec2::new(0.0, 1.0)),
+			BottomLeft => (Vec2::ONE, Vec2::new(1.0, 0.0)),
+			BottomRight => (Vec2::new(-1.0, 1.0), Vec2::ZERO),
+			Middle => (Vec2::ZERO, Vec2::splat(0.5)),
+		}
+	}
+	/// World point which stay in place, `pos` is top left
+	pub fn pivot(&self, size: Vec2, pos: Vec2) -> Vec2 {
+		let (_, fixed) = self.handle();
+		pos + Vec2::new(size.x * fixed.x, -size.y * fixed.y)
+	}
+	/// New size and top left position in whole pixel, [None] when size stay the same
+	pub fn apply(&self, size: Vec2, pos: Vec2, scale: ScaleKind) -> Option<(Vec2, Vec2)> {
+		let (drag, fixed) = self.handle();
+		let new_size = match scale {
+			ScaleKind::Pixel(dragged) => size + dragged * drag,
+			ScaleKind::Percent(percent) => {
+				let axis = if self == &ScalePosition::Middle {
+					Vec2::ONE
+				} else {
+					drag.abs()
+				};
+				size * (Vec2::ONE + (percent / 100.0 - 1.0) * axis)
+			},
+		}
+		.round()
+		.clamp(Vec2::ONE, Vec2::splat(MAX_SCALE_SIDE));
+		if new_size == size {
+			return None;
+		}
+		let pivot = self.pivot(size, pos);
+		let new_pos = pivot - Vec2::new(new_size.x * fixed.x, -new_size.y * fixed.y);
+		Some((new_size, new_pos.round()))
+	}
+}
+
 #[derive(Component, Default, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub struct ScaleAction(pub Option<ScaleKind>);
 
@@ -176,7 +227,7 @@ pub struct CursorFromTo {
 #[derive(Component, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct Selection(pub Option<Vec2>, pub Option<Vec2>);
 
-/// New Client didn't receive the correct entity from old one
+/// Late joiner get it again from [JoinState] once objects are replicated
 #[derive(Component, Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct SelectedObject {
 	pub single: Option<Entity>,
@@ -256,6 +307,10 @@ pub enum VerifyAction {
 	Delete,
 	#[reflect(@ToolTip("Delete selected objects"))]
 	DeleteObject,
+	#[reflect(@ToolTip("Put selected objects in new group"), @ToolPath("gimp-merge-down.png"))]
+	Group,
+	#[reflect(@ToolTip("Release objects of selected groups"))]
+	Ungroup,
 
 	// TEXT
 	#[reflect(@ToolTip("Add new Text to the world"))]
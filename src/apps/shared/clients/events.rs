@@ -1,13 +1,18 @@
 use bevy::{ecs::entity::MapEntities, prelude::*};
+use lightyear::prelude::ClientId;
 use serde::{Deserialize, Serialize};
 
 use crate::apps::shared::prelude::DataHold;
 
-use super::CursorFromTo;
+use super::{CursorFromTo, CursorPos, SelectedObject};
 
 #[derive(Event, Clone, Copy, Serialize, Deserialize)]
 pub struct PenDraw(pub CursorFromTo, pub DrawingWay);
 
+/// Fill at cursor on selected images with user [BucketSettings]
+#[derive(Event, Clone, Copy, Serialize, Deserialize)]
+pub struct BucketFill(pub CursorPos);
+
 #[derive(Clone, Copy, Serialize, Deserialize)]
 pub enum DrawingWay {
 	Color,
@@ -83,6 +88,35 @@ impl MapEntities for ReceiveImageData {
 	}
 }
 
+/// Part of image at `min`, rest stay as client hold it
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReceiveImagePatch {
+	pub ent: Entity,
+	data: DataHold,
+	pub min: UVec2,
+	pub size: UVec2,
+}
+
+impl ReceiveImagePatch {
+	pub fn new(ent: Entity, data: &[u8], min: UVec2, size: UVec2) -> Self {
+		Self {
+			ent,
+			data: DataHold::to_compress(data),
+			min,
+			size,
+		}
+	}
+	pub fn data(&self) -> &DataHold {
+		&self.data
+	}
+}
+
+impl MapEntities for ReceiveImagePatch {
+	fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+		self.ent = entity_mapper.map_entity(self.ent);
+	}
+}
+
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct ApplyChange(pub bool, pub Entity);
 
@@ -95,6 +129,85 @@ impl MapEntities for ApplyChange {
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct MessageCtx(pub String);
 
+/// Admin only, server ignore others. Server send it to target before disconnecting
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum ModerateNet {
+	Kick(ClientId),
+	Ban(ClientId),
+}
+
+/// Answer to `[protocol id: u64 LE][len: u8][password][resume: u128 LE]`,
+/// token then new resume follow only when [AuthReply::Accepted]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthReply {
+	Accepted,
+	WrongPassword,
+	Banned,
+	VersionMismatch,
+}
+
+impl AuthReply {
+	pub fn to_byte(self) -> u8 {
+		self as u8
+	}
+	pub fn from_byte(byte: u8) -> Option<Self> {
+		[
+			Self::Accepted,
+			Self::WrongPassword,
+			Self::Banned,
+			Self::VersionMismatch,
+		]
+		.into_iter()
+		.find(|reply| reply.to_byte() == byte)
+	}
+	pub fn describe(&self) -> &'static str {
+		match self {
+			AuthReply::Accepted => "Accepted by server.",
+			AuthReply::WrongPassword => "Wrong server password.",
+			AuthReply::Banned => "You are banned from this server.",
+			AuthReply::VersionMismatch => "Version mismatch, update to the same version as server.",
+		}
+	}
+}
+
+/// Sent once on connect, what late joiner must receive before asking [JoinState]
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct JoinManifest {
+	pub objects: u32,
+	pub images: u32,
+}
+
+/// Client ready, every object in [JoinManifest] is replicated
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct RequestJoinState;
+
+/// Snapshot of what components replication can't carry consistently
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct JoinState {
+	/// `PointToPoint` pairs of every path
+	pub relations: Vec<(Entity, Entity)>,
+	/// Bottom to top
+	pub z_order: Vec<Entity>,
+	/// Other users selection
+	pub selections: Vec<(Entity, SelectedObject)>,
+}
+
+impl MapEntities for JoinState {
+	fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+		self.relations.iter_mut().for_each(|(ent1, ent2)| {
+			*ent1 = entity_mapper.map_entity(*ent1);
+			*ent2 = entity_mapper.map_entity(*ent2);
+		});
+		self.z_order
+			.iter_mut()
+			.for_each(|ent| *ent = entity_mapper.map_entity(*ent));
+		self.selections.iter_mut().for_each(|(ent_user, selected)| {
+			*ent_user = entity_mapper.map_entity(*ent_user);
+			selected.map_entities(entity_mapper);
+		});
+	}
+}
+
 #[derive(Resource, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MovedPoint {
 	pub world: Vec2,
@@ -121,13 +121,19 @@ pub enum EditorPosition {
 #[derive(Resource)]
 pub struct ConnectTokenRequestTask {
 	pub auth_backend_addr: SocketAddr,
-	pub task: Option<Task<Option<ConnectToken>>>,
+	pub password: String,
+	/// Given by server with last token, 0 when none
+	pub resume: u128,
+	/// Ok hold resume for next connection, Error is message to display
+	pub task: Option<Task<Result<(ConnectToken, u128), String>>>,
 }
 
 impl Default for ConnectTokenRequestTask {
 	fn default() -> Self {
 		Self {
 			auth_backend_addr: SERVER_ADDR_BACKEND,
+			password: String::new(),
+			resume: 0,
 			task: None,
 		}
 	}
@@ -155,6 +161,9 @@ pub(super) struct ServerPort;
 #[derive(Component)]
 pub(super) struct ConnectionIP;
 
+#[derive(Component)]
+pub(super) struct ConnectionPassword;
+
 pub(super) trait SetTextOnly {
 	fn set_text_only(&mut self, font_system: &mut CosmicFontSystem, text: &str);
 }
@@ -388,6 +388,13 @@ fn editor_ui(
 									ServerPort,
 									&mut font_system,
 								);
+								input_node(
+									tab,
+									"Password".to_owned(),
+									String::new(),
+									ConnectionPassword,
+									&mut font_system,
+								);
 							});
 
 							dock.add_tab("Messages".to_owned(), |tab| {
@@ -594,6 +601,10 @@ fn handle_local_message(
 				cmd.trigger(DisplayMsgEvent(rest.trim().to_owned()));
 				return;
 			}
+			if first == ">rename" {
+				cmd.trigger(RenameSelected(rest.trim().to_owned()));
+				return;
+			}
 		};
 
 		if identity.is_client() {
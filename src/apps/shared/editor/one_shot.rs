@@ -6,6 +6,8 @@ use local_ip_address::local_ip;
 use server::{ServerConfig, ServerTransport};
 use std::net::IpAddr;
 
+use crate::apps::server::ServerSettings;
+
 use super::*;
 
 pub(super) fn client_system(
@@ -15,6 +17,7 @@ pub(super) fn client_system(
 		Query<(Entity, &mut CosmicBuffer), With<ConnectionIP>>,
 		Query<(Entity, &mut CosmicBuffer), With<ClientPort>>,
 	)>,
+	query_password: Query<&CosmicBuffer, With<ConnectionPassword>>,
 	mut client_config: ResMut<ClientConfig>,
 	mut connect_token_request: ResMut<ConnectTokenRequestTask>,
 	mut font_system: ResMut<CosmicFontSystem>,
@@ -50,6 +53,10 @@ pub(super) fn client_system(
 	let mut auth_back_sock = SERVER_ADDR_BACKEND;
 	auth_back_sock.set_ip(ip);
 	connect_token_request.auth_backend_addr = auth_back_sock;
+	connect_token_request.password = query_password
+		.get_single()
+		.map(|buffer| buffer.get_text())
+		.unwrap_or_default();
 	let state = match server_state.get() {
 		RdioClientState::Offline => RdioClientState::Online,
 		RdioClientState::Online => RdioClientState::Offline,
@@ -64,7 +71,9 @@ pub(super) fn server_system(
 		Query<(Entity, &mut CosmicBuffer), With<ConnectionIP>>,
 		Query<(Entity, &mut CosmicBuffer), With<ServerPort>>,
 	)>,
+	query_password: Query<&CosmicBuffer, With<ConnectionPassword>>,
 	mut server_config: ResMut<ServerConfig>,
+	mut server_settings: ResMut<ServerSettings>,
 	mut font_system: ResMut<CosmicFontSystem>,
 ) {
 	let Ok(ip) = query_addr.p0().single().1.get_text().parse::<IpAddr>() else {
@@ -87,6 +96,11 @@ pub(super) fn server_system(
 	};
 	socker.set_ip(ip);
 	socker.set_port(port);
+	server_settings.password = query_password
+		.get_single()
+		.map(|buffer| buffer.get_text())
+		.ok()
+		.filter(|password| !password.is_empty());
 	let state = match server_state.get() {
 		RdioServerState::Offline => RdioServerState::Online,
 		RdioServerState::Online => RdioServerState::Offline,
@@ -1,8 +1,11 @@
 use bevy::{
-	ecs::component::{ComponentHooks, StorageType},
+	ecs::{
+		component::{ComponentHooks, StorageType},
+		entity::MapEntities,
+	},
 	prelude::*,
 };
-use image::RgbaImage;
+use image::{Rgba, RgbaImage};
 use lightyear::prelude::ClientId;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
@@ -111,13 +114,157 @@ pub enum DrawType {
 	#[default]
 	Normal,
 	Replace,
-	// Overlay,
 	Behind,
+	/// Only where image already has pixels
+	Atop,
+	/// Remove by brush alpha
+	Erase,
+	Multiply,
+	Screen,
+	Overlay,
+	Darken,
+	Lighten,
+	ColorDodge,
+	ColorBurn,
+	Add,
+	Difference,
+}
+
+impl DrawType {
+	/// CSS `mix-blend-mode`, none for normal or what CSS can not do
+	pub fn to_css(&self) -> Option<&'static str> {
+		Some(match self {
+			DrawType::Multiply => "multiply",
+			DrawType::Screen => "screen",
+			DrawType::Overlay => "overlay",
+			DrawType::Darken => "darken",
+			DrawType::Lighten => "lighten",
+			DrawType::ColorDodge => "color-dodge",
+			DrawType::ColorBurn => "color-burn",
+			DrawType::Add => "plus-lighter",
+			DrawType::Difference => "difference",
+			DrawType::Normal
+			| DrawType::Replace
+			| DrawType::Behind
+			| DrawType::Atop
+			| DrawType::Erase => return None,
+		})
+	}
+	/// Separable blend of one channel in 0 to 1, source for non blending type
+	fn blend_channel(&self, backdrop: f32, source: f32) -> f32 {
+		match self {
+			DrawType::Multiply => backdrop * source,
+			DrawType::Screen => backdrop + source - backdrop * source,
+			DrawType::Overlay => {
+				if backdrop <= 0.5 {
+					2.0 * backdrop * source
+				} else {
+					let doubled = 2.0 * backdrop - 1.0;
+					source + doubled - source * doubled
+				}
+			},
+			DrawType::Darken => backdrop.min(source),
+			DrawType::Lighten => backdrop.max(source),
+			DrawType::ColorDodge => {
+				if backdrop <= 0.0 {
+					0.0
+				} else if source >= 1.0 {
+					1.0
+				} else {
+					(backdrop / (1.0 - source)).min(1.0)
+				}
+			},
+			DrawType::ColorBurn => {
+				if backdrop >= 1.0 {
+					1.0
+				} else if source <= 0.0 {
+					0.0
+				} else {
+					1.0 - ((1.0 - backdrop) / source).min(1.0)
+				}
+			},
+			DrawType::Add => (backdrop + source).min(1.0),
+			DrawType::Difference => (backdrop - source).abs(),
+			_ => source,
+		}
+	}
+	/// Source drawn on backdrop, W3C compositing with non premultiplied pixel
+	pub fn composite(&self, backdrop: Rgba<u8>, source: Rgba<u8>) -> Rgba<u8> {
+		let alpha_b = backdrop.0[3] as f32 / 255.0;
+		let alpha_s = source.0[3] as f32 / 255.0;
+		// Weight of source only, both and backdrop only area
+		let (alpha_o, weight_s, weight_both, weight_b) = match self {
+			DrawType::Replace => return source,
+			DrawType::Behind => (
+				alpha_b + alpha_s * (1.0 - alpha_b),
+				alpha_s * (1.0 - alpha_b),
+				0.0,
+				alpha_b,
+			),
+			DrawType::Atop => (alpha_b, 0.0, alpha_s * alpha_b, (1.0 - alpha_s) * alpha_b),
+			DrawType::Erase => (
+				alpha_b * (1.0 - alpha_s),
+				0.0,
+				0.0,
+				alpha_b * (1.0 - alpha_s),
+			),
+			_ => (
+				alpha_s + alpha_b * (1.0 - alpha_s),
+				alpha_s * (1.0 - alpha_b),
+				alpha_s * alpha_b,
+				(1.0 - alpha_s) * alpha_b,
+			),
+		};
+		if alpha_o <= 0.0 {
+			return Rgba([0; 4]);
+		}
+		let mut result = [0; 4];
+		result
+			.iter_mut()
+			.take(3)
+			.enumerate()
+			.for_each(|(n, channel)| {
+				let color_b = backdrop.0[n] as f32 / 255.0;
+				let color_s = source.0[n] as f32 / 255.0;
+				let color_o = weight_s * color_s
+					+ weight_both * self.blend_channel(color_b, color_s)
+					+ weight_b * color_b;
+				*channel = (color_o / alpha_o * 255.0).round().clamp(0.0, 255.0) as u8;
+			});
+		result[3] = (alpha_o * 255.0).round() as u8;
+		Rgba(result)
+	}
 }
 
 #[derive(Default, Deref, DerefMut)]
 pub struct ProcessImage(pub RgbaImage);
 
+impl ProcessImage {
+	/// `<image>` with PNG data URI, `position` is top left in world
+	pub fn to_svg(&self, position: Vec2) -> Option<(svg::node::element::Image, IRect)> {
+		use base64::{engine::general_purpose::STANDARD, Engine};
+		let mut png = std::io::Cursor::new(Vec::new());
+		self.write_to(&mut png, image::ImageFormat::Png)
+			.map_err(|e| error!("Failed to encode PNG: {:?}", e))
+			.ok()?;
+		let min = (position * Vec2::new(1.0, -1.0)).as_ivec2();
+		let size = UVec2::from(self.dimensions()).as_ivec2();
+		let image = svg::node::element::Image::new()
+			.set("x", min.x)
+			.set("y", min.y)
+			.set("width", size.x)
+			.set("height", size.y)
+			.set(
+				"href",
+				format!(
+					"data:image/png;base64,{}",
+					STANDARD.encode(png.into_inner())
+				),
+			);
+		Some((image, IRect::from_corners(min, min + size)))
+	}
+}
+
 impl Component for ProcessImage {
 	const STORAGE_TYPE: StorageType = StorageType::Table;
 	fn register_component_hooks(_hooks: &mut ComponentHooks) {
@@ -138,9 +285,104 @@ impl Component for ProcessImage {
 	}
 }
 
+/// Pixels user selected on one image, painting there is clipped to it
+#[derive(Component, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PixelMask(pub Option<MaskData>);
+
+impl MapEntities for PixelMask {
+	fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+		if let Some(mask) = &mut self.0 {
+			mask.map_entities(entity_mapper);
+		}
+	}
+}
+
+impl PixelMask {
+	/// Mask bits when it belong to this image and still fit its size
+	pub fn bits_for(&self, ent: Entity, size: UVec2) -> Option<Vec<u8>> {
+		let mask = self.0.as_ref()?;
+		if mask.ent != ent || mask.size != size {
+			return None;
+		}
+		mask.data.uncompress().ok()
+	}
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq)]
+pub struct MaskData {
+	pub ent: Entity,
+	pub size: UVec2,
+	/// One byte per pixel, zero is outside
+	pub data: DataHold,
+	/// Polygons in image pixel, y go down
+	pub outline: Vec<Vec<Vec2>>,
+}
+
+impl MapEntities for MaskData {
+	fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+		self.ent = entity_mapper.map_entity(self.ent);
+	}
+}
+
+impl MaskData {
+	/// [None] when outline cover no pixel
+	pub fn from_outline(ent: Entity, size: UVec2, outline: Vec<Vec2>) -> Option<Self> {
+		if outline.len() < 3 {
+			return None;
+		}
+		let bits = fill_polygon(size, &outline);
+		bits.iter().any(|bit| *bit != 0).then(|| Self {
+			ent,
+			size,
+			data: DataHold::to_compress(&bits),
+			outline: vec![outline],
+		})
+	}
+	/// Add other selection on the same image
+	pub fn union(&mut self, other: MaskData) {
+		if self.ent != other.ent || self.size != other.size {
+			*self = other;
+			return;
+		}
+		let (Ok(mut bits), Ok(other_bits)) = (self.data.uncompress(), other.data.uncompress())
+		else {
+			*self = other;
+			return;
+		};
+		bits.iter_mut()
+			.zip(other_bits)
+			.for_each(|(bit, other_bit)| *bit = (*bit).max(other_bit));
+		self.data = DataHold::to_compress(&bits);
+		self.outline.extend(other.outline);
+	}
+}
+
 #[derive(Component, Clone, Default, Deref, DerefMut)]
 pub struct InUse(pub HashSet<ClientId>);
 
 #[derive(Component, Reflect, Default, Clone, Serialize, Deserialize, PartialEq)]
 #[reflect(Component)]
 pub struct BlurScale(pub f32);
+
+#[derive(Component, Reflect, Clone, Serialize, Deserialize, PartialEq)]
+#[reflect(Component)]
+pub struct BucketSettings {
+	/// Largest difference on any channel still counted as similar
+	pub tolerance: u8,
+	/// Only pixels connected to the clicked one
+	pub contiguous: bool,
+	pub anti_alias: bool,
+	/// Compare colors of every image together instead of the filled one
+	pub sample_merged: bool,
+}
+
+impl Default for BucketSettings {
+	fn default() -> Self {
+		Self {
+			tolerance: 32,
+			contiguous: true,
+			anti_alias: true,
+			sample_merged: false,
+		}
+	}
+}
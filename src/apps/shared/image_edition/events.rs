@@ -1,7 +1,7 @@
-use bevy::prelude::*;
+use bevy::{ecs::entity::MapEntities, prelude::*};
 use serde::{Deserialize, Serialize};
 
-use super::DataHold;
+use super::{DataHold, MaskData};
 
 #[derive(Event, Serialize, Deserialize)]
 pub struct ImageNetwork {
@@ -22,3 +22,47 @@ impl ImageNetwork {
 		&self.data
 	}
 }
+
+/// Server apply on sender selected images, group transform around their bounding box
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum ImageTransformNet {
+	FlipHorizontal,
+	FlipVertical,
+	/// Clockwise degree, right angle never resample
+	Rotate(f32, RotateFilter),
+}
+
+#[derive(Reflect, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum RotateFilter {
+	#[default]
+	Nearest,
+	Bilinear,
+	Bicubic,
+}
+
+/// Server crop sender selected images to this world rectangle, corner in any order
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct CropImageNet(pub Vec2, pub Vec2);
+
+/// Floating selection of sender, only pixels change on server
+#[derive(Clone, Serialize, Deserialize)]
+pub enum PixelSelectNet {
+	/// Clear masked pixels, sender hold them as floating
+	Lift(MaskData),
+	/// Stamp floating pixels, `min` is top left in image pixel
+	Anchor {
+		ent: Entity,
+		min: IVec2,
+		size: UVec2,
+		data: DataHold,
+	},
+}
+
+impl MapEntities for PixelSelectNet {
+	fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+		match self {
+			PixelSelectNet::Lift(mask) => mask.map_entities(entity_mapper),
+			PixelSelectNet::Anchor { ent, .. } => *ent = entity_mapper.map_entity(*ent),
+		}
+	}
+}
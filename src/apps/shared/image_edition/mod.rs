@@ -6,11 +6,12 @@ use bevy::render::render_resource::Extent3d;
 use bevy::sprite::Anchor;
 use bevy::{math::I64Vec2, prelude::*};
 use image::*;
-use imageops::{blur, overlay, replace, resize, FilterType};
+use imageops::{blur, crop_imm, overlay, replace, resize, FilterType};
 use imageproc::drawing::{draw_filled_circle_mut, BresenhamLineIter};
 use lightyear::prelude::ClientMessageEvent;
 use lz4_flex::block::{compress_prepend_size, decompress_size_prepended, DecompressError};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 
 pub mod components;
 pub mod events;
@@ -26,6 +27,7 @@ impl Plugin for ImageProcessPlugin {
 		app.register_type::<DataHold>()
 			.register_type::<BrushRef>()
 			.observe(pencel_draw)
+			.observe(bucket_fill)
 			.add_systems(
 				Update,
 				(
@@ -49,6 +51,12 @@ impl DataHold {
 		let compressed = compress_prepend_size(data);
 		Self::Compressed(compressed)
 	}
+	/// Compress in place when still raw
+	pub fn compress(&mut self) {
+		if let DataHold::Uncompress(raw) = self {
+			*self = Self::to_compress(raw);
+		}
+	}
 	pub fn uncompress(&self) -> Result<Vec<u8>, DecompressError> {
 		match self {
 			DataHold::Uncompress(v) => Ok(v.clone()),
@@ -171,6 +179,7 @@ fn pencel_draw(
 			&DrawingSpacing,
 			&DrawPiled,
 			&DrawType,
+			&PixelMask,
 		),
 		With<UserId>,
 	>,
@@ -186,7 +195,7 @@ fn pencel_draw(
 	>,
 ) {
 	let CursorFromTo { from, to } = trigger.event().0;
-	let (selected_obj, user_id, draw_spacing, draw_pile, draw_type) =
+	let (selected_obj, user_id, draw_spacing, draw_pile, draw_type, pixel_mask) =
 		query_user.get(trigger.entity()).unwrap();
 
 	selected_obj.group.iter().for_each(|ent_obj| {
@@ -205,11 +214,11 @@ fn pencel_draw(
 			.skip(3)
 			.step_by(4)
 			.collect::<Vec<_>>();
+		let limit: UVec2 = process_img.dimensions().into();
 
 		let uvec: UVec2 = draw_pile.dimensions().into();
 		let I64Vec2 { x, y } = (uvec / 2).as_i64vec2();
 
-		let limit: UVec2 = process_img.dimensions().into();
 		let draw_size = uvec.as_vec2();
 		let expand = (draw_size.x.max(draw_size.y) / 2.0).ceil() + 0.5;
 		let rected = Rect::from_corners(Vec2::ZERO, limit.as_vec2()).inflate(expand);
@@ -223,6 +232,14 @@ fn pencel_draw(
 			.pixel_pos_central(obj_pos)
 			.clamp(further.min, further.max);
 
+		// Only the stroke area is kept to restore pixels outside mask
+		let stroke = stroke_rect(from, to, obj_pos, uvec, limit);
+		let op_clip = pixel_mask.bits_for(*ent_obj, limit).map(|bits| {
+			let size = stroke.size();
+			let before = crop_imm(&process_img.0, stroke.min.x, stroke.min.y, size.x, size.y);
+			(bits, before.to_image())
+		});
+
 		let draw_type = match trigger.event().1 {
 			DrawingWay::Color => draw_type,
 			DrawingWay::Erase => &DrawType::Replace,
@@ -281,6 +298,32 @@ fn pencel_draw(
 				overlay(&mut new_rgba, &process_img.0, 0, 0);
 				process_img.0 = new_rgba;
 			},
+			blend_type => {
+				for (at_x, at_y) in BresenhamLineIter::new(start.into(), end.into())
+					.step_by(draw_spacing.get().into())
+				{
+					let pos = IVec2::new(at_x, at_y).as_vec2();
+					if !rected.contains(pos) {
+						continue;
+					}
+					blend_stamp(
+						&mut process_img.0,
+						&draw_pile.0,
+						I64Vec2::new(at_x as i64 - x, at_y as i64 - y),
+						blend_type,
+						100,
+					);
+				}
+			},
+		}
+
+		if let Some((bits, before)) = op_clip {
+			before.enumerate_pixels().for_each(|(x, y, before_pix)| {
+				let (at_x, at_y) = (stroke.min.x + x, stroke.min.y + y);
+				if bits[(at_y * limit.x + at_x) as usize] == 0 {
+					process_img.put_pixel(at_x, at_y, *before_pix);
+				}
+			});
 		}
 
 		if alpha_lock.contains(&user_id.0) {
@@ -291,7 +334,283 @@ fn pencel_draw(
 	});
 }
 
-fn process_image_changed(
+/// Pixels of image a stroke segment may change, clamped inside `limit`
+pub fn stroke_rect(
+	from: CursorPos,
+	to: CursorPos,
+	obj_pos: Vec2,
+	stamp_size: UVec2,
+	limit: UVec2,
+) -> URect {
+	let half = stamp_size.as_vec2() / 2.0 + 2.0;
+	let start = from.pixel_pos_central(obj_pos);
+	let end = to.pixel_pos_central(obj_pos);
+	let min = (start.min(end) - half).floor().max(Vec2::ZERO);
+	let max = (start.max(end) + half).ceil().min(limit.as_vec2());
+	if min.cmpgt(max).any() {
+		return URect::default();
+	}
+	URect::from_corners(min.as_uvec2(), max.as_uvec2())
+}
+
+/// Brush stamp with top left at `at` faded by `opacity`, outside image is skipped
+fn blend_stamp(
+	img: &mut RgbaImage,
+	stamp: &RgbaImage,
+	at: I64Vec2,
+	draw_type: &DrawType,
+	opacity: i8,
+) {
+	let limit = I64Vec2::new(img.width() as i64, img.height() as i64);
+	let stamp_size = I64Vec2::new(stamp.width() as i64, stamp.height() as i64);
+	let begin = (-at).max(I64Vec2::ZERO);
+	let end = (limit - at).min(stamp_size);
+	let opacity = opacity.clamp(0, 100) as u16;
+	for stamp_y in begin.y..end.y {
+		for stamp_x in begin.x..end.x {
+			let mut source = *stamp.get_pixel(stamp_x as u32, stamp_y as u32);
+			if opacity < 100 {
+				source.0[3] = (source.0[3] as u16 * opacity / 100) as u8;
+			}
+			let pix = img.get_pixel_mut((at.x + stamp_x) as u32, (at.y + stamp_y) as u32);
+			*pix = draw_type.composite(*pix, source);
+		}
+	}
+}
+
+/// Coverage of pixels similar to seed, anti alias soften the edge outside
+fn fill_coverage(sample: &RgbaImage, seed: UVec2, settings: &BucketSettings) -> Vec<u8> {
+	let (width, height) = sample.dimensions();
+	let target = *sample.get_pixel(seed.x, seed.y);
+	let is_similar = |pix: &Rgba<u8>| {
+		pix.0
+			.iter()
+			.zip(target.0)
+			.all(|(channel, seed_channel)| channel.abs_diff(seed_channel) <= settings.tolerance)
+	};
+	let mut region = vec![0; (width * height) as usize];
+	if settings.contiguous {
+		region[(seed.y * width + seed.x) as usize] = u8::MAX;
+		let mut stack = vec![seed];
+		while let Some(at) = stack.pop() {
+			let neighbours = [
+				at.x.checked_sub(1).map(|x| UVec2::new(x, at.y)),
+				(at.x + 1 < width).then(|| UVec2::new(at.x + 1, at.y)),
+				at.y.checked_sub(1).map(|y| UVec2::new(at.x, y)),
+				(at.y + 1 < height).then(|| UVec2::new(at.x, at.y + 1)),
+			];
+			neighbours.into_iter().flatten().for_each(|next| {
+				let n = (next.y * width + next.x) as usize;
+				if region[n] == 0 && is_similar(sample.get_pixel(next.x, next.y)) {
+					region[n] = u8::MAX;
+					stack.push(next);
+				}
+			});
+		}
+	} else {
+		sample
+			.pixels()
+			.zip(region.iter_mut())
+			.filter(|(pix, _)| is_similar(pix))
+			.for_each(|(_, cover)| *cover = u8::MAX);
+	}
+	if !settings.anti_alias {
+		return region;
+	}
+	let mut softened = region.clone();
+	for y in 0..height {
+		for x in 0..width {
+			let n = (y * width + x) as usize;
+			if region[n] != 0 {
+				continue;
+			}
+			let mut sum = 0;
+			for near_y in y.saturating_sub(1)..=(y + 1).min(height - 1) {
+				for near_x in x.saturating_sub(1)..=(x + 1).min(width - 1) {
+					sum += region[(near_y * width + near_x) as usize] as u32;
+				}
+			}
+			softened[n] = (sum / 9) as u8;
+		}
+	}
+	softened
+}
+
+/// Object pixels with how it mix, top left in pixel with y down
+#[derive(Clone)]
+pub struct CompositeLayer<'a> {
+	pub img: Cow<'a, RgbaImage>,
+	pub pos: IVec2,
+	pub z: i16,
+	pub opacity: i8,
+	pub blend: DrawType,
+}
+
+impl CompositeLayer<'_> {
+	/// Draw on target whose top left sit at `origin`
+	pub fn draw_on(&self, target: &mut RgbaImage, origin: IVec2) {
+		let at = (self.pos - origin).as_i64vec2();
+		blend_stamp(target, &self.img, at, &self.blend, self.opacity);
+	}
+}
+
+/// Layers drawn bottom to top by z inside `rect`, same as on screen
+pub fn compose_layers(mut layers: Vec<CompositeLayer>, rect: IRect) -> RgbaImage {
+	layers.sort_by_key(|layer| layer.z);
+	let size = rect.size().as_uvec2();
+	let mut composed = RgbaImage::new(size.x, size.y);
+	layers
+		.iter()
+		.for_each(|layer| layer.draw_on(&mut composed, rect.min));
+	composed
+}
+
+/// World position to pixel position with y down
+pub fn world_to_pixel(pos: Vec2) -> IVec2 {
+	(pos * Vec2::new(1.0, -1.0)).round().as_ivec2()
+}
+
+fn bucket_fill(
+	trigger: Trigger<BucketFill>,
+	query_user: Query<
+		(
+			&SelectedObject,
+			&UserId,
+			&PaintInk,
+			&BucketSettings,
+			&PixelMask,
+		),
+		With<UserId>,
+	>,
+	mut query_object: Query<
+		(
+			&mut ProcessImage,
+			&ObjectPosition,
+			&ObjectZLayer,
+			&PixelLock,
+			&AlphaLock,
+			&ObjectAccess,
+			&ShownOpacity,
+			&ObjectBlend,
+		),
+		With<ObjectWorld>,
+	>,
+) {
+	let BucketFill(cur_pos) = *trigger.event();
+	let Ok((selected_obj, user_id, paint, settings, pixel_mask)) = query_user.get(trigger.entity())
+	else {
+		return;
+	};
+	let [red, green, blue, alpha] = paint.0.to_u8_array();
+
+	selected_obj.group.iter().for_each(|ent_obj| {
+		let Ok((process_img, obj_pos, _, pix_lock, _, access, ..)) = query_object.get(*ent_obj)
+		else {
+			return;
+		};
+		if pix_lock.contains(&user_id.0) || !access.targets(&user_id.0) {
+			return;
+		}
+		let limit: UVec2 = process_img.dimensions().into();
+		let seed = cur_pos.pixel_to_img(obj_pos.0);
+		if seed.cmplt(Vec2::ZERO).any() || seed.cmpge(limit.as_vec2()).any() {
+			return;
+		}
+		let coverage = if settings.sample_merged {
+			let layers = query_object
+				.iter()
+				.map(
+					|(img, pos, z_layer, _, _, _, opacity, blend)| CompositeLayer {
+						img: Cow::Borrowed(&img.0),
+						pos: world_to_pixel(pos.0),
+						z: z_layer.0,
+						opacity: opacity.0,
+						blend: blend.0,
+					},
+				)
+				.collect();
+			let origin = world_to_pixel(obj_pos.0);
+			let rect = IRect::from_corners(origin, origin + limit.as_ivec2());
+			fill_coverage(&compose_layers(layers, rect), seed.as_uvec2(), settings)
+		} else {
+			fill_coverage(process_img, seed.as_uvec2(), settings)
+		};
+		let op_bits = pixel_mask.bits_for(*ent_obj, limit);
+
+		let Ok((mut process_img, _, _, _, alpha_lock, ..)) = query_object.get_mut(*ent_obj) else {
+			return;
+		};
+		let is_alpha_lock = alpha_lock.contains(&user_id.0);
+		process_img.pixels_mut().enumerate().for_each(|(n, pix)| {
+			let cover = op_bits
+				.as_ref()
+				.map_or(coverage[n], |bits| coverage[n].min(bits[n]));
+			if cover == 0 {
+				return;
+			}
+			let stay_alpha = pix.0[3];
+			let cover_alpha = (alpha as u16 * cover as u16 / u8::MAX as u16) as u8;
+			pix.blend(&Rgba([red, green, blue, cover_alpha]));
+			if is_alpha_lock {
+				pix.0[3] = stay_alpha;
+			}
+		});
+	});
+}
+
+/// Even odd fill sampled at pixel center, outline in image pixel
+fn fill_polygon(size: UVec2, outline: &[Vec2]) -> Vec<u8> {
+	let mut bits = vec![0; (size.x * size.y) as usize];
+	let edges = outline
+		.iter()
+		.zip(outline.iter().cycle().skip(1))
+		.collect::<Vec<_>>();
+	(0..size.y).for_each(|y| {
+		let center_y = y as f32 + 0.5;
+		let mut crossed = edges
+			.iter()
+			.filter(|(from, to)| (from.y <= center_y) != (to.y <= center_y))
+			.map(|(from, to)| from.x + (center_y - from.y) / (to.y - from.y) * (to.x - from.x))
+			.collect::<Vec<_>>();
+		crossed.sort_by(|a, b| a.total_cmp(b));
+		crossed.chunks_exact(2).for_each(|span| {
+			let begin = (span[0] - 0.5).ceil().clamp(0.0, size.x as f32) as u32;
+			let end = (span[1] - 0.5).ceil().clamp(0.0, size.x as f32) as u32;
+			(begin..end).for_each(|x| bits[(y * size.x + x) as usize] = u8::MAX);
+		});
+	});
+	bits
+}
+
+/// Smallest pixel rect holding every selected bit
+pub fn mask_bound(bits: &[u8], size: UVec2) -> Option<URect> {
+	bits.iter()
+		.enumerate()
+		.filter(|(_, bit)| **bit != 0)
+		.map(|(n, _)| UVec2::new(n as u32 % size.x, n as u32 / size.x))
+		.fold(None, |bound: Option<URect>, pixel| {
+			let pixel_rect = URect::from_corners(pixel, pixel + UVec2::ONE);
+			Some(bound.map_or(pixel_rect, |rect| rect.union(pixel_rect)))
+		})
+}
+
+/// Selected pixels cropped to mask bound, outside mask is transparent
+pub fn masked_pixels(img: &RgbaImage, bits: &[u8]) -> Option<(UVec2, RgbaImage)> {
+	let size = UVec2::from(img.dimensions());
+	let bound = mask_bound(bits, size)?;
+	let bound_size = bound.size();
+	let picked = RgbaImage::from_fn(bound_size.x, bound_size.y, |x, y| {
+		let at = bound.min + UVec2::new(x, y);
+		if bits[(at.y * size.x + at.x) as usize] == 0 {
+			Rgba([0; 4])
+		} else {
+			*img.get_pixel(at.x, at.y)
+		}
+	});
+	Some((bound.min, picked))
+}
+
+pub fn process_image_changed(
 	mut image_assets: ResMut<Assets<Image>>,
 	query_object: Query<(&Handle<Image>, &ProcessImage), Changed<ProcessImage>>,
 ) {
@@ -329,6 +648,23 @@ fn apply_change_img(
 	});
 }
 
+/// [ResizeKind::Scale] stretch pixels, [ResizeKind::Resize] keep pixels where they are in world
+pub fn resample_image(
+	img: &RgbaImage,
+	size: UVec2,
+	kind: ResizeKind,
+	offset: I64Vec2,
+) -> RgbaImage {
+	match kind {
+		ResizeKind::Scale => resize(img, size.x, size.y, FilterType::Nearest),
+		ResizeKind::Resize => {
+			let mut resized_img = RgbaImage::new(size.x, size.y);
+			overlay(&mut resized_img, img, offset.x, offset.y);
+			resized_img
+		},
+	}
+}
+
 fn resize_img_fr(
 	mut cmd: Commands,
 	mut image_assets: ResMut<Assets<Image>>,
@@ -344,7 +680,7 @@ fn resize_img_fr(
 	query_user
 		.iter()
 		.for_each(|(selected_obj, scale_action, kind, scale_pos)| {
-			let Some(ScaleKind::Pixel(mut size)) = scale_action.0 else {
+			let Some(scale) = scale_action.0 else {
 				return;
 			};
 
@@ -371,62 +707,22 @@ fn resize_img_fr(
 					return;
 				};
 
-				let Vec2 {
-					x: prev_x,
-					y: prev_y,
-				} = previous_img_data.pos;
-
 				let size_img = prev_imged.size();
-				let img_float = size_img.as_vec2();
-				let img_float_pos = img_float - 1.0;
+				let prev_pos = previous_img_data.pos;
+				let (new_size, new_pos) = scale_pos
+					.apply(size_img.as_vec2(), prev_pos, scale)
+					.unwrap_or((size_img.as_vec2(), prev_pos));
 				let ori_img =
 					RgbaImage::from_vec(size_img.x, size_img.y, prev_imged.data.clone()).unwrap();
-
-				match scale_pos {
-					ScalePosition::Top => {
-						transform.translation.y = (prev_y - size.y).max(prev_y - img_float_pos.y);
-						size *= Vec2::new(0.0, -1.0);
-					},
-					ScalePosition::Bottom => size.x = 0.0,
-					ScalePosition::Left => {
-						transform.translation.x = prev_x - size.x.max(-img_float_pos.x);
-						size.y = 0.0;
-					},
-					ScalePosition::Right => size *= Vec2::new(-1.0, 0.0),
-					ScalePosition::TopLeft => {
-						transform.translation.x = prev_x - size.x.max(-img_float_pos.x);
-						transform.translation.y = (prev_y - size.y).max(prev_y - img_float_pos.y);
-						size *= Vec2::new(1.0, -1.0);
-					},
-					ScalePosition::TopRight => {
-						transform.translation.y = (prev_y - size.y).max(prev_y - img_float_pos.y);
-						size *= -1.0;
-					},
-					ScalePosition::BottomLeft => {
-						transform.translation.x = prev_x - size.x.max(-img_float_pos.x);
-					},
-					ScalePosition::BottomRight => size *= Vec2::new(-1.0, 1.0),
-					ScalePosition::Middle => return,
-				}
-
-				let calculation = (img_float + size).clamp(Vec2::ONE, Vec2::splat(2000.0));
-
-				let UVec2 { x, y } = calculation.as_uvec2();
-
-				let data = match kind {
-					ResizeKind::Scale => {
-						let resized_img = resize(&ori_img, x, y, FilterType::Nearest);
-						resized_img.into_vec()
-					},
-					ResizeKind::Resize => {
-						let mut resized_img = RgbaImage::new(x, y);
-						overlay(&mut resized_img, &ori_img, 0, 0);
-						resized_img.into_vec()
-					},
-				};
+				let offset = Vec2::new(prev_pos.x - new_pos.x, new_pos.y - prev_pos.y);
+				let data =
+					resample_image(&ori_img, new_size.as_uvec2(), *kind, offset.as_i64vec2())
+						.into_vec();
+				transform.translation.x = new_pos.x;
+				transform.translation.y = new_pos.y;
 
 				image_assets.remove(img_handle.id());
-				*img_handle = image_assets.rgba8_image(data, calculation.as_uvec2());
+				*img_handle = image_assets.rgba8_image(data, new_size.as_uvec2());
 			});
 		});
 }
@@ -447,85 +743,3 @@ fn update_img_aabb(
 		aabb.center.y = -y;
 	});
 }
-
-// #[derive(PartialEq)]
-// enum FlipWay {
-//     Horizontal,
-//     Vertical,
-// }
-
-// fn img_fliper(
-//     image_assets: &mut Assets<Image>,
-//     single_image: &Query<(&Handle<Image>, &SharingName), (With<RdioImage>, With<MainSelectObject>)>,
-//     flip_way: FlipWay,
-// ) {
-//     let Ok((handle_img, _)) = single_image.get_single() else {
-//         return;
-//     };
-//     let detail_img = image_assets.get_mut(handle_img).unwrap();
-//     let size_img = detail_img.size();
-//     let rgba_img = RgbaImage::from_vec(size_img.x, size_img.y, detail_img.data.clone()).unwrap();
-//     let rotated_img = match flip_way {
-//         FlipWay::Horizontal => flip_horizontal(&rgba_img),
-//         FlipWay::Vertical => flip_vertical(&rgba_img),
-//     };
-//     detail_img.data = rotated_img.to_vec();
-// }
-
-// pub(super) fn crop_2d(
-//     mut query: Query<&mut Selection, With<UserId>>,
-//     mut image_assets: ResMut<Assets<Image>>,
-//     mut objects: Query<(&mut Transform, &Handle<Image>), (With<MainActiveObj>, With<RdioImage>)>,
-//     actions_key: Res<ActionState<SettingsAction>>,
-//     mut screen2d: GlobalScreen2D,
-// ) {
-//     let Ok((mut transform_obj, handle_img_obj)) = objects.get_single_mut() else {
-//         return;
-//     };
-//     let Some(cur_world) = screen2d.cursor_world() else {
-//         return;
-//     };
-//     let real_world_ray = cur_world.round();
-//     let pos_pix = transform_obj.translation.truncate();
-//     let imgy_size = detail_img.size();
-//     let relative = (real_world_ray - pos_pix) * Vec2::new(1.0, -1.0);
-//     let fix_ray = relative.clamp(Vec2::ZERO, imgy_size.as_vec2());
-
-//     actions_key
-//         .just_pressed(&SettingsAction::Primary)
-//         .then(|| cropper.0 = Some(fix_ray));
-//     actions_key
-//         .pressed(&SettingsAction::Primary)
-//         .then(|| cropper.1 = Some(fix_ray));
-//     receive_click
-//         .just_pressed(&SettingsAction::Escape)
-//         .then(|| {
-//             cropper.0 = None;
-//             cropper.1 = None;
-//         });
-
-//     let (Some(pin_begin), Some(pin_end)) = (cropper.0, cropper.1) else {
-//         return;
-//     };
-
-//     let rectness = Rect::from_corners(pin_begin, pin_end);
-//     let i_real_pick = rectness.min.as_uvec2();
-//     let well_pos =
-//         transform_obj.translation.truncate() + Vec2::new(rectness.min.x, -rectness.min.y);
-
-//     if receive_click.just_pressed(&SettingsAction::Enter) && !rectness.is_empty() {
-//         let sizey = rectness.size().as_uvec2();
-//         cropper.0 = None;
-//         let mut imgy =
-//             RgbaImage::from_vec(imgy_size.x, imgy_size.y, detail_img.data.clone()).unwrap();
-//         let croped_img =
-//             crop::<RgbaImage>(&mut imgy, i_real_pick.x, i_real_pick.y, sizey.x, sizey.y).to_image();
-//         transform_obj.translation = well_pos.extend(transform_obj.translation.z);
-//         detail_img.texture_descriptor.size = Extent3d {
-//             width: croped_img.width(),
-//             height: croped_img.height(),
-//             depth_or_array_layers: 1,
-//         };
-//         detail_img.data = croped_img.to_vec();
-//     }
-// }
@@ -1,23 +1,23 @@
-pub mod image_edition;
-mod processing;
-pub mod proto;
-pub mod pub_edition;
-// pub mod rdio;
 pub mod clients;
 pub mod editor;
 pub mod helperful_tool;
+pub mod image_edition;
 pub mod path_edition;
+mod processing;
+pub mod proto;
+pub mod pub_edition;
+pub mod rdio;
 pub mod text_edition;
 pub mod wolrd_view;
 
 pub mod prelude {
-	pub use super::image_edition::*;
-	pub use super::proto::*;
-	pub use super::pub_edition::*;
-	// pub use super::rdio::*;
 	pub use super::clients::*;
 	pub use super::editor::lib::*;
+	pub use super::image_edition::*;
 	pub use super::path_edition::*;
+	pub use super::proto::*;
+	pub use super::pub_edition::*;
+	pub use super::rdio::*;
 	pub use super::text_edition::*;
 }
 
@@ -30,15 +30,21 @@ use proto::ProtocolPlugin;
 use std::time::Duration;
 use wolrd_view::LocalViewPlugin;
 
-pub(super) struct AppSharedPlugin;
+pub struct AppSharedPlugin;
 impl Plugin for AppSharedPlugin {
 	fn build(&self, app: &mut App) {
 		// app.configure_sets(Update, ProcessObject::Process.after(ProcessObject::Input));
 
+		app.add_plugins((EditorPlugin, LocalViewPlugin, SharedEditionPlugin));
+	}
+}
+
+/// Protocol and object edition, without any window or UI
+pub struct SharedEditionPlugin;
+impl Plugin for SharedEditionPlugin {
+	fn build(&self, app: &mut App) {
 		app.add_plugins((
-			EditorPlugin,
 			ProtocolPlugin,
-			LocalViewPlugin,
 			PathPlugin,
 			ImageProcessPlugin,
 			TextWorldPlugin,
@@ -49,6 +55,21 @@ impl Plugin for AppSharedPlugin {
 }
 
 pub const FIXED_TIMESTEP_HZ: f64 = 64.0;
+/// Different build refuse each other
+pub const PROTOCOL_ID: u64 = protocol_id(env!("CARGO_PKG_VERSION"));
+
+/// FNV-1a of version
+const fn protocol_id(version: &str) -> u64 {
+	let bytes = version.as_bytes();
+	let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+	let mut index = 0;
+	while index < bytes.len() {
+		hash ^= bytes[index] as u64;
+		hash = hash.wrapping_mul(0x0100_0000_01b3);
+		index += 1;
+	}
+	hash
+}
 
 pub fn shared_config() -> SharedConfig {
 	SharedConfig {
@@ -1,10 +1,16 @@
 use super::*;
+use crate::tool_tip::lib::ToolName;
 use bevy::{
 	ecs::component::{ComponentHooks, StorageType},
 	sprite::Mesh2dHandle,
 };
 use lightyear::prelude::ClientId;
+use lyon_algorithms::measure::{PathMeasurements, SampleType};
 use serde::{Deserialize, Serialize};
+use std::{
+	f32::consts::{FRAC_PI_2, PI, TAU},
+	ops::Range,
+};
 use strum::EnumIter;
 
 #[derive(Component, Reflect, Default, Clone, Copy, EnumIter, PartialEq)]
@@ -23,25 +29,317 @@ pub struct ObjectPoint;
 #[aery(Counted)]
 pub struct PointToPoint;
 
+/// Segment from previous point, curve end at point position
 #[derive(Component, Reflect, Clone, Default, Serialize, Deserialize, PartialEq)]
 #[reflect(Component)]
 pub enum PointType {
 	#[default]
 	LineTo,
 	QuadraticBezier {
-		to: Vec2,
+		ctrl: Vec2,
 	},
-	/// One of these unwanted 1 / 2
+	/// `to` follow point position, only kept for saved files
 	CubricBezier {
 		ctrl1: Vec2,
 		ctrl2: Vec2,
 		to: Vec2,
 	},
+	/// Point position is the center
 	Arc {
 		radii: Vec2,
 		sweep_angle: f32,
 		x_rotation: f32,
 	},
+	/// Begin another outline, the one before is closed same as the path
+	MoveTo,
+}
+
+impl PointType {
+	/// Control handles, the one near previous point first
+	pub fn handles(&self) -> Vec<Vec2> {
+		match *self {
+			PointType::QuadraticBezier { ctrl } => vec![ctrl],
+			PointType::CubricBezier { ctrl1, ctrl2, .. } => vec![ctrl1, ctrl2],
+			PointType::LineTo | PointType::Arc { .. } | PointType::MoveTo => Vec::new(),
+		}
+	}
+	/// False when there is no such handle
+	pub fn set_handle(&mut self, index: u8, to: Vec2) -> bool {
+		let handle = match (self, index) {
+			(PointType::QuadraticBezier { ctrl }, 0) => ctrl,
+			(PointType::CubricBezier { ctrl1, .. }, 0) => ctrl1,
+			(PointType::CubricBezier { ctrl2, .. }, 1) => ctrl2,
+			_ => return false,
+		};
+		*handle = to;
+		true
+	}
+	pub fn kind(&self) -> SegmentKind {
+		match self {
+			PointType::LineTo => SegmentKind::Line,
+			PointType::QuadraticBezier { .. } => SegmentKind::Quadratic,
+			PointType::CubricBezier { .. } => SegmentKind::Cubic,
+			PointType::Arc { .. } => SegmentKind::Arc,
+			PointType::MoveTo => SegmentKind::Move,
+		}
+	}
+	/// Where segment beginning at `from` end, `position` is the point position
+	pub fn end(&self, from: Vec2, position: Vec2) -> Vec2 {
+		let PointType::Arc {
+			radii,
+			sweep_angle,
+			x_rotation,
+		} = *self
+		else {
+			return position;
+		};
+		let Some((_, start)) = self.arc_start(from, position) else {
+			return position;
+		};
+		on_ellipse(position, radii, x_rotation, start + sweep_angle)
+	}
+	/// Arc begin on its ellipse at parametric angle of `from`, with the angle
+	pub fn arc_start(&self, from: Vec2, position: Vec2) -> Option<(Vec2, f32)> {
+		let PointType::Arc {
+			radii, x_rotation, ..
+		} = *self
+		else {
+			return None;
+		};
+		let begin = Vec2::from_angle(-x_rotation).rotate(from - position)
+			/ (radii.signum() * radii.abs().max(Vec2::splat(f32::EPSILON)));
+		let start = begin.y.atan2(begin.x);
+		Some((on_ellipse(position, radii, x_rotation, start), start))
+	}
+	/// Arc beginning when `from` is off its ellipse, joined by straight line
+	pub fn arc_lead(&self, from: Vec2, position: Vec2) -> Option<Vec2> {
+		self.arc_start(from, position)
+			.map(|(start, _)| start)
+			.filter(|start| start.distance(from) > 1e-3)
+	}
+	/// Arc as cubic pieces, same geometry for mesh, raster and bound
+	pub fn arc_curves(&self, from: Vec2, position: Vec2) -> Vec<(Vec2, Vec2, Vec2)> {
+		let PointType::Arc {
+			radii,
+			sweep_angle,
+			x_rotation,
+		} = *self
+		else {
+			return Vec::new();
+		};
+		let Some((_, start)) = self.arc_start(from, position) else {
+			return Vec::new();
+		};
+		arc_cubics(position, radii, x_rotation, start, sweep_angle)
+	}
+	/// Ends of arc split in pieces up to half turn, as SVG `A` can not draw full turn
+	pub fn arc_ends(&self, from: Vec2, position: Vec2) -> Vec<Vec2> {
+		let PointType::Arc {
+			radii,
+			sweep_angle,
+			x_rotation,
+		} = *self
+		else {
+			return Vec::new();
+		};
+		let count = (sweep_angle.abs() / PI).ceil().max(1.0) as usize;
+		(1..=count)
+			.map(|n| {
+				PointType::Arc {
+					radii,
+					sweep_angle: sweep_angle * n as f32 / count as f32,
+					x_rotation,
+				}
+				.end(from, position)
+			})
+			.collect()
+	}
+	/// Same begin and end in other kind, give new point position with it
+	pub fn convert(&self, kind: SegmentKind, from: Vec2, position: Vec2) -> (Vec2, PointType) {
+		let end = self.end(from, position);
+		match kind {
+			SegmentKind::Line => (end, PointType::LineTo),
+			SegmentKind::Quadratic => (
+				end,
+				PointType::QuadraticBezier {
+					ctrl: from.lerp(end, 0.5),
+				},
+			),
+			SegmentKind::Cubic => (
+				end,
+				PointType::CubricBezier {
+					ctrl1: from.lerp(end, 1.0 / 3.0),
+					ctrl2: from.lerp(end, 2.0 / 3.0),
+					to: end,
+				},
+			),
+			// Half circle
+			SegmentKind::Arc => (
+				from.lerp(end, 0.5),
+				PointType::Arc {
+					radii: Vec2::splat(from.distance(end) / 2.0),
+					sweep_angle: PI,
+					x_rotation: 0.0,
+				},
+			),
+			SegmentKind::Move => (end, PointType::MoveTo),
+		}
+	}
+}
+
+/// Point of ellipse at parametric `angle`
+pub fn on_ellipse(center: Vec2, radii: Vec2, x_rotation: f32, angle: f32) -> Vec2 {
+	center + Vec2::from_angle(x_rotation).rotate(radii * Vec2::from_angle(angle))
+}
+
+/// Elliptical arc as cubic pieces up to quarter turn, each with control points and end
+pub fn arc_cubics(
+	center: Vec2,
+	radii: Vec2,
+	x_rotation: f32,
+	start: f32,
+	sweep: f32,
+) -> Vec<(Vec2, Vec2, Vec2)> {
+	let count = (sweep.abs() / FRAC_PI_2).ceil().max(1.0) as usize;
+	let step = sweep / count as f32;
+	let handle = 4.0 / 3.0 * (step / 4.0).tan();
+	let rotation = Vec2::from_angle(x_rotation);
+	let tangent = |angle: f32| rotation.rotate(radii * Vec2::from_angle(angle).perp());
+	(0..count)
+		.map(|n| {
+			let begin = start + step * n as f32;
+			let end = begin + step;
+			(
+				on_ellipse(center, radii, x_rotation, begin) + tangent(begin) * handle,
+				on_ellipse(center, radii, x_rotation, end) - tangent(end) * handle,
+				on_ellipse(center, radii, x_rotation, end),
+			)
+		})
+		.collect()
+}
+
+#[derive(Reflect, Clone, Copy, EnumIter, PartialEq, Serialize, Deserialize)]
+pub enum SegmentKind {
+	#[reflect(@ToolName("To Line"))]
+	Line,
+	#[reflect(@ToolName("To Quadratic Curve"))]
+	Quadratic,
+	#[reflect(@ToolName("To Cubic Curve"))]
+	Cubic,
+	#[reflect(@ToolName("To Arc"))]
+	Arc,
+	#[reflect(@ToolName("To New Outline"))]
+	Move,
+}
+
+/// How handles on both sides of point follow each other
+#[derive(Component, Reflect, Clone, Copy, Default, EnumIter, PartialEq, Serialize, Deserialize)]
+pub enum NodeMode {
+	#[default]
+	#[reflect(@ToolName("Corner Node"))]
+	Corner,
+	/// Same direction
+	#[reflect(@ToolName("Smooth Node"))]
+	Smooth,
+	/// Same direction and length
+	#[reflect(@ToolName("Symmetric Node"))]
+	Symmetric,
+}
+
+impl NodeMode {
+	/// Handle on other side after `handle` move around `node`
+	pub fn mirror(&self, node: Vec2, handle: Vec2, opposite: Vec2) -> Option<Vec2> {
+		let direction = (node - handle).try_normalize()?;
+		match self {
+			NodeMode::Corner => None,
+			NodeMode::Smooth => Some(node + direction * node.distance(opposite)),
+			NodeMode::Symmetric => Some(node * 2.0 - handle),
+		}
+	}
+}
+
+/// Most corners of polygon and star, fewest are 3
+pub const MAX_SHAPE_CORNERS: u8 = 64;
+
+/// Closed outline drawn by dragging a box
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ShapeKind {
+	Rectangle,
+	Ellipse,
+	Polygon { sides: u8 },
+	Star { points: u8 },
+}
+
+impl ShapeKind {
+	pub fn name(&self) -> &'static str {
+		match self {
+			ShapeKind::Rectangle => "Rectangle",
+			ShapeKind::Ellipse => "Ellipse",
+			ShapeKind::Polygon { .. } => "Polygon",
+			ShapeKind::Star { .. } => "Star",
+		}
+	}
+	/// Square box keeping drag direction
+	pub fn constrain(from: Vec2, to: Vec2) -> Vec2 {
+		let delta = to - from;
+		let side = delta.abs().max_element();
+		from + Vec2::new(side.copysign(delta.x), side.copysign(delta.y))
+	}
+	/// Outline fitting the box, first point only begin the path
+	pub fn points(&self, from: Vec2, to: Vec2) -> Vec<(Vec2, PointType)> {
+		let rect = Rect::from_corners(from, to);
+		let center = rect.center();
+		let radii = rect.half_size();
+		let around = |n: usize, ratio: &dyn Fn(usize) -> f32| {
+			(0..n)
+				.map(|i| {
+					let angle = FRAC_PI_2 + TAU * i as f32 / n as f32;
+					(
+						center + radii * ratio(i) * Vec2::from_angle(angle),
+						PointType::LineTo,
+					)
+				})
+				.collect::<Vec<_>>()
+		};
+		match *self {
+			ShapeKind::Rectangle => [
+				rect.min,
+				Vec2::new(rect.max.x, rect.min.y),
+				rect.max,
+				Vec2::new(rect.min.x, rect.max.y),
+			]
+			.into_iter()
+			.map(|position| (position, PointType::LineTo))
+			.collect(),
+			ShapeKind::Ellipse => {
+				// Handle length of quarter circle as cubic bezier
+				const KAPPA: f32 = 0.552_284_8;
+				let on = |angle: f32| center + radii * Vec2::from_angle(angle);
+				let mut points = vec![(on(0.0), PointType::LineTo)];
+				(0..4).for_each(|quarter| {
+					let begin = FRAC_PI_2 * quarter as f32;
+					let end = begin + FRAC_PI_2;
+					let to = on(end);
+					let ctrl1 = on(begin) + radii * Vec2::from_angle(begin).perp() * KAPPA;
+					let ctrl2 = to - radii * Vec2::from_angle(end).perp() * KAPPA;
+					points.push((to, PointType::CubricBezier { ctrl1, ctrl2, to }));
+				});
+				points
+			},
+			ShapeKind::Polygon { sides } => {
+				around(sides.clamp(3, MAX_SHAPE_CORNERS) as usize, &|_| 1.0)
+			},
+			ShapeKind::Star { points } => {
+				around(points.clamp(3, MAX_SHAPE_CORNERS) as usize * 2, &|i| {
+					if i % 2 == 0 {
+						1.0
+					} else {
+						0.5
+					}
+				})
+			},
+		}
+	}
 }
 
 #[derive(Bundle, Default)]
@@ -51,6 +349,7 @@ pub struct PointBundle {
 	position: ObjectPosition,
 	obj_z: ObjectZLayer,
 	point_type: PointType,
+	node: NodeMode,
 }
 
 impl PointBundle {
@@ -68,6 +367,7 @@ pub struct RdioPathBundle {
 	mark: ObjectPath,
 	z_pos: ObjectZLayer,
 	stroke: StrokeNet,
+	dash: StrokeDash,
 	fill: FillNet,
 	close: PathClose,
 }
@@ -146,6 +446,9 @@ impl Default for StrokeNet {
 	}
 }
 
+/// Widest stroke set from stroke panel
+pub const MAX_STROKE_WIDTH: f32 = 100.0;
+
 impl From<StrokeNet> for Stroke {
 	fn from(value: StrokeNet) -> Self {
 		Stroke {
@@ -155,6 +458,96 @@ impl From<StrokeNet> for Stroke {
 	}
 }
 
+/// Dash and gap lengths along stroke like SVG `stroke-dasharray`, empty for solid stroke
+#[derive(Component, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct StrokeDash {
+	pub array: Vec<f32>,
+	pub offset: f32,
+}
+
+impl StrokeDash {
+	/// Patterns cycled by stroke panel
+	pub const PRESETS: [&'static [f32]; 5] = [
+		&[],
+		&[10.0, 5.0],
+		&[5.0, 5.0],
+		&[2.0, 6.0],
+		&[15.0, 5.0, 5.0, 5.0],
+	];
+	/// Longest dash array
+	pub const MAX_LEN: usize = 16;
+	/// Shortest dash and gap period
+	pub const MIN_PERIOD: f32 = 0.5;
+	/// Most pattern repeats along one stroke, rest of stroke is drawn solid
+	pub const MAX_REPEATS: usize = 10_000;
+	/// Within limits every client can draw, empty array is solid
+	pub fn is_valid(&self) -> bool {
+		let period = self.array.iter().sum::<f32>();
+		self.offset.is_finite()
+			&& self.array.len() <= Self::MAX_LEN
+			&& self.array.iter().all(|len| len.is_finite() && *len >= 0.0)
+			&& (self.array.is_empty() || (period.is_finite() && period >= Self::MIN_PERIOD))
+	}
+	/// Dash and gap pairs, odd array repeated, None when it draw solid
+	pub fn pattern(&self) -> Option<Vec<f32>> {
+		if self.array.is_empty() || !self.is_valid() {
+			return None;
+		}
+		let mut pattern = self.array.clone();
+		if pattern.len() % 2 == 1 {
+			pattern.extend_from_within(..);
+		}
+		Some(pattern)
+	}
+	/// Drawn ranges along stroke of `length`, shifted back by offset
+	pub fn ranges(&self, length: f32) -> Vec<Range<f32>> {
+		let Some(pattern) = self.pattern() else {
+			return vec![0.0..length];
+		};
+		let period = pattern.iter().sum::<f32>();
+		let mut at = -self.offset.rem_euclid(period);
+		let mut ranges = Vec::new();
+		let mut repeats = 0;
+		while at < length {
+			if repeats == Self::MAX_REPEATS {
+				ranges.push(at.max(0.0)..length);
+				break;
+			}
+			repeats += 1;
+			pattern.chunks_exact(2).for_each(|dash_gap| {
+				let (begin, end) = (at.max(0.0), (at + dash_gap[0]).min(length));
+				if begin < end {
+					ranges.push(begin..end);
+				}
+				at += dash_gap[0] + dash_gap[1];
+			});
+		}
+		ranges
+	}
+	/// Each dash of `path` as own sub path, None when it draw solid
+	pub fn dash_path(&self, path: &lyon_path::Path) -> Option<lyon_path::Path> {
+		self.pattern()?;
+		let measurements = PathMeasurements::from_path(path, 0.01);
+		let mut sampler = measurements.create_sampler(path, SampleType::Distance);
+		let mut builder = lyon_path::Path::builder();
+		self.ranges(sampler.length())
+			.into_iter()
+			.for_each(|range| sampler.split_range(range, &mut builder));
+		Some(builder.build())
+	}
+	/// Value of `stroke-dasharray`
+	pub fn to_svg(&self) -> Option<String> {
+		let pattern = self.pattern()?;
+		Some(
+			pattern
+				.iter()
+				.map(|len| len.to_string())
+				.collect::<Vec<_>>()
+				.join(" "),
+		)
+	}
+}
+
 #[derive(Component, Clone, Serialize, Deserialize, PartialEq)]
 pub struct FillNet {
 	pub options: FillOptions,
@@ -1,6 +1,9 @@
 use bevy::{ecs::entity::MapEntities, prelude::*};
+use bevy_prototype_lyon::prelude::LineCap;
 use serde::{Deserialize, Serialize};
 
+use super::{NodeMode, SegmentKind, ShapeKind, StrokeDash};
+
 #[derive(Event, Clone, Serialize, Deserialize)]
 pub struct RequestingPointRelation(pub Entity);
 
@@ -9,3 +12,53 @@ impl MapEntities for RequestingPointRelation {
 		self.0 = entity_mapper.map_entity(self.0);
 	}
 }
+
+/// Server spawn closed path in sender [PaintInk](crate::apps::shared::prelude::PaintInk), corners in world
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct NewShapeNet {
+	pub kind: ShapeKind,
+	pub from: Vec2,
+	pub to: Vec2,
+}
+
+/// Curve edit of sender, convert and node mode act on selected points
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum CurveEditNet {
+	/// Drag handle of segment ending at `ent`, see [PointType::handles](super::PointType::handles)
+	Handle {
+		ent: Entity,
+		index: u8,
+		to: Vec2,
+	},
+	Convert(SegmentKind),
+	Node(NodeMode),
+}
+
+impl MapEntities for CurveEditNet {
+	fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+		if let CurveEditNet::Handle { ent, .. } = self {
+			*ent = entity_mapper.map_entity(*ent);
+		}
+	}
+}
+
+/// Stroke panel edit of `path`, values replace current one
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StrokeEditNet {
+	pub path: Entity,
+	pub edit: StrokeEdit,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum StrokeEdit {
+	Width(f32),
+	StartCap(LineCap),
+	EndCap(LineCap),
+	Dash(StrokeDash),
+}
+
+impl MapEntities for StrokeEditNet {
+	fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+		self.path = entity_mapper.map_entity(self.path);
+	}
+}
@@ -43,8 +43,11 @@ pub mod lyon_to_zeno {
 }
 
 pub mod lyon_to_raqote {
+	use bevy::math::Vec2;
 	use bevy_prototype_lyon::prelude::*;
-	use raqote::{LineCap as RaLineCap, LineJoin as RaLineJoin};
+	use lyon_path::PathEvent;
+	use raqote::{LineCap as RaLineCap, LineJoin as RaLineJoin, PathBuilder};
+	use std::f32::consts::TAU;
 	pub trait LyonToRaqote {
 		type Other;
 		fn to_raqote(&self) -> Self::Other;
@@ -71,4 +74,93 @@ pub mod lyon_to_raqote {
 			}
 		}
 	}
+
+	/// Same path with y flipped into image space
+	pub fn to_raqote_path(path: &lyon_path::Path) -> raqote::Path {
+		let mut pb = PathBuilder::new();
+		path.iter().for_each(|event| match event {
+			PathEvent::Begin { at } => pb.move_to(at.x, -at.y),
+			PathEvent::Line { to, .. } => pb.line_to(to.x, -to.y),
+			PathEvent::Quadratic { ctrl, to, .. } => pb.quad_to(ctrl.x, -ctrl.y, to.x, -to.y),
+			PathEvent::Cubic {
+				ctrl1, ctrl2, to, ..
+			} => pb.cubic_to(ctrl1.x, -ctrl1.y, ctrl2.x, -ctrl2.y, to.x, -to.y),
+			PathEvent::End { close, .. } => {
+				if close {
+					pb.close();
+				}
+			},
+		});
+		pb.finish()
+	}
+
+	/// Ends of every open sub path in image space, with outward direction and whether it is start
+	pub fn open_ends(path: &lyon_path::Path) -> Vec<(Vec2, Vec2, bool)> {
+		let flip = |point: lyon_path::math::Point| Vec2::new(point.x, -point.y);
+		let mut ends = Vec::new();
+		let mut start: Option<(Vec2, Vec2)> = None;
+		let mut last: Option<(Vec2, Vec2)> = None;
+		path.iter().for_each(|event| {
+			let (from, next, prev, to) = match event {
+				PathEvent::Begin { .. } => {
+					(start, last) = (None, None);
+					return;
+				},
+				PathEvent::Line { from, to } => (from, to, from, to),
+				PathEvent::Quadratic { from, ctrl, to } => (from, ctrl, ctrl, to),
+				PathEvent::Cubic {
+					from,
+					ctrl1,
+					ctrl2,
+					to,
+				} => (from, ctrl1, ctrl2, to),
+				PathEvent::End { close, .. } => {
+					if let (false, Some(start), Some(last)) = (close, start, last) {
+						ends.push((start.0, start.1, true));
+						ends.push((last.0, last.1, false));
+					}
+					return;
+				},
+			};
+			let (from, next, prev, to) = (flip(from), flip(next), flip(prev), flip(to));
+			// Control point on end give no direction, fall back to other end
+			let outward = |end: Vec2, near: Vec2, far: Vec2| {
+				(end - near)
+					.try_normalize()
+					.or((end - far).try_normalize())
+					.unwrap_or(Vec2::X)
+			};
+			start.get_or_insert((from, outward(from, next, to)));
+			last = Some((to, outward(to, prev, from)));
+		});
+		ends
+	}
+
+	/// Cap shape at `point` facing `outward`, Butt has none
+	pub fn cap_path(cap: LineCap, point: Vec2, outward: Vec2, width: f32) -> Option<raqote::Path> {
+		let half = width / 2.0;
+		let mut pb = PathBuilder::new();
+		match cap {
+			LineCap::Butt => return None,
+			LineCap::Round => pb.arc(point.x, point.y, half, 0.0, TAU),
+			LineCap::Square => {
+				let side = outward.perp() * half;
+				let ahead = outward * half;
+				[
+					point + side,
+					point + side + ahead,
+					point - side + ahead,
+					point - side,
+				]
+				.into_iter()
+				.enumerate()
+				.for_each(|(n, corner)| match n {
+					0 => pb.move_to(corner.x, corner.y),
+					_ => pb.line_to(corner.x, corner.y),
+				});
+			},
+		}
+		pb.close();
+		Some(pb.finish())
+	}
 }
@@ -12,18 +12,24 @@ use bevy::{
 };
 use bevy_mod_picking::{picking_core::PickSet, prelude::*};
 use bevy_prototype_lyon::prelude::*;
+use lyon_path::PathEvent;
 use std::collections::HashSet;
-use svg::node::element::SVG;
+use svg::{
+	node::element::{path::Data, Group as SvgGroup, Path as SvgPath, SVG},
+	Node as SvgNode,
+};
 use zeno::Placement;
 
 pub mod components;
 pub mod events;
 mod lyon_to;
+mod svg_import;
 
 pub use components::*;
 pub use events::*;
 use lightyear::prelude::client::Predicted;
 use lyon_to::*;
+pub use svg_import::*;
 
 pub const PICK_RANGE: f32 = 5.0;
 
@@ -33,7 +39,10 @@ impl Plugin for PathPlugin {
 		app.observe(connect_relations::<PointToPoint>)
 			.add_systems(PreUpdate, point_picking.in_set(PickSet::Backend))
 			.add_systems(Update, path_render_as)
-			.add_systems(PostUpdate, (update_svg, update_aabb_path).chain());
+			.add_systems(
+				PostUpdate,
+				(update_svg, (update_aabb_path, dash_stroke)).chain(),
+			);
 	}
 }
 
@@ -114,6 +123,7 @@ fn update_svg(
 				Changed<Children>,
 				Changed<RenderPathAs>,
 				Changed<StrokeNet>,
+				Changed<StrokeDash>,
 				Changed<FillNet>,
 			)>,
 			With<ObjectPath>,
@@ -196,18 +206,103 @@ fn update_aabb_path(
 		});
 }
 
+/// Stroke of dashed path in [RenderPathAs::Path], drawn on child while path keep its fill
+#[derive(Component)]
+pub struct DashedStroke;
+
+fn dash_stroke(
+	mut cmd: Commands,
+	mut query_path: Query<
+		(
+			Entity,
+			&Path,
+			&mut Stroke,
+			&StrokeNet,
+			&StrokeDash,
+			&ShownOpacity,
+			Option<&Children>,
+		),
+		(
+			With<ObjectPath>,
+			Or<(
+				Changed<Path>,
+				Changed<Stroke>,
+				Changed<StrokeDash>,
+				Changed<ShownOpacity>,
+			)>,
+		),
+	>,
+	mut query_dashed: Query<(&mut Path, &mut Stroke), (With<DashedStroke>, Without<ObjectPath>)>,
+) {
+	query_path.iter_mut().for_each(
+		|(ent_path, path, mut stroke, stroke_net, dash, opacity, op_children)| {
+			let op_dashed = op_children.and_then(|children| {
+				children
+					.iter()
+					.copied()
+					.find(|ent| query_dashed.contains(*ent))
+			});
+			let mut shown: Stroke = stroke_net.clone().into();
+			shown
+				.color
+				.set_alpha(stroke_net.color.alpha() * opacity.0 as f32 / 100.0);
+			let Some(dashed) = dash.dash_path(&path.0) else {
+				if let Some(ent_dashed) = op_dashed {
+					*stroke = shown;
+					cmd.entity(ent_dashed).despawn_recursive();
+				}
+				return;
+			};
+			if stroke.color != Color::NONE {
+				stroke.color = Color::NONE;
+			}
+			if let Some((mut dashed_path, mut dashed_stroke)) =
+				op_dashed.and_then(|ent| query_dashed.get_mut(ent).ok())
+			{
+				dashed_path.0 = dashed;
+				*dashed_stroke = shown;
+				return;
+			}
+			let ent_dashed = cmd
+				.spawn((
+					DashedStroke,
+					ShapeBundle {
+						path: Path(dashed),
+						spatial: SpatialBundle::from_transform(Transform::from_xyz(0.0, 0.0, 0.5)),
+						..default()
+					},
+					shown,
+					Pickable::IGNORE,
+				))
+				.id();
+			cmd.entity(ent_path).add_child(ent_dashed);
+		},
+	);
+}
+
 fn path_render_as(
 	mut cmd: Commands,
 	mut image_assets: ResMut<Assets<Image>>,
 	query_path: Query<
-		(Entity, &RenderPathAs, &StrokeNet, &FillNet),
+		(
+			Entity,
+			&RenderPathAs,
+			&StrokeNet,
+			&FillNet,
+			Option<&Children>,
+		),
 		(Changed<RenderPathAs>, With<ObjectWorld>),
 	>,
+	query_dashed: Query<(), With<DashedStroke>>,
 ) {
-	query_path
-		.iter()
-		.for_each(|(ent_path, render_as, stroke, fill)| match render_as {
+	query_path.iter().for_each(
+		|(ent_path, render_as, stroke, fill, op_children)| match render_as {
 			RenderPathAs::Image => {
+				op_children
+					.into_iter()
+					.flatten()
+					.filter(|ent| query_dashed.contains(**ent))
+					.for_each(|ent| cmd.entity(*ent).despawn_recursive());
 				cmd.entity(ent_path)
 					.remove::<PathAsSvgBundle>()
 					.insert(PathAsImageBundle {
@@ -226,7 +321,8 @@ fn path_render_as(
 						fill.clone().into(),
 					));
 			},
-		});
+		},
+	);
 }
 
 #[derive(SystemParam)]
@@ -252,107 +348,153 @@ pub struct PathParam<'w, 's> {
 		With<ObjectPoint>,
 	>,
 	pub root_point: Query<'w, 's, (Entity, &'static Parent), Root<PointToPoint>>,
+	pub query_point_ent: Query<'w, 's, (Entity, Relations<PointToPoint>), With<ObjectPoint>>,
+	pub query_dash: Query<'w, 's, &'static StrokeDash>,
 }
 
 impl<'w, 's> PathParam<'w, 's> {
-	fn to_render_path(&self, path_ent: Entity) -> Option<Path> {
+	/// Points of path in [PointToPoint] order, beginning from root
+	pub fn points(&self, path_ent: Entity) -> Option<Vec<(Vec2, PointType)>> {
 		let (first_point, _) = self
 			.root_point
 			.iter()
 			.find(|(_, holder)| holder.get() == path_ent)?;
-		let (.., close) = self.query_path.get(path_ent).ok()?;
 
-		let mut path_builder = PathBuilder::new();
-
-		let mut is_first = true;
+		let mut points = Vec::new();
 		self.query_point
 			.traverse::<PointToPoint>([first_point])
 			.track_self()
 			.for_each(|a, _, b, _| {
-				let a_pos = a.0.xy();
-				let xy_pos = b.0.xy();
-
-				if is_first {
-					is_first = false;
-					path_builder.move_to(a_pos);
-				}
-
-				match *b.1 {
-					PointType::LineTo => {
-						path_builder.line_to(xy_pos);
-					},
-					PointType::QuadraticBezier { to } => {
-						path_builder.quadratic_bezier_to(xy_pos, to);
-					},
-					PointType::CubricBezier { ctrl1, ctrl2, to } => {
-						path_builder.cubic_bezier_to(ctrl1, ctrl2, to);
-					},
-					PointType::Arc {
-						radii,
-						sweep_angle,
-						x_rotation,
-					} => path_builder.arc(xy_pos, radii, sweep_angle, x_rotation),
+				if points.is_empty() {
+					points.push((a.0.xy(), a.1.clone()));
 				}
+				points.push((b.0.xy(), b.1.clone()));
 			});
-
-		close.0.then(|| path_builder.close());
-
-		Some(path_builder.build())
+		if points.is_empty() {
+			let ((position, point_type), _) = self.query_point.get(first_point).ok()?;
+			points.push((position.xy(), point_type.clone()));
+		}
+		Some(points)
 	}
-	pub fn to_image(&self, path_ent: Entity) -> Option<(Vec<u8>, Placement)> {
-		// use raqote::*;
-		use zeno::*;
-		let (first_point, _) = self
+	/// Point entities in same order as [PathParam::points]
+	pub fn point_ents(&self, path_ent: Entity) -> Vec<Entity> {
+		let Some((first_point, _)) = self
 			.root_point
 			.iter()
-			.find(|(_, holder)| holder.get() == path_ent)?;
-		let (children, stroke, fill, close) = self.query_path.get(path_ent).ok()?;
-		let mut path_builder: Vec<Command> = Vec::with_capacity(children.len());
-		let mut pb = raqote::PathBuilder::new();
-		let mut is_first = true;
-
-		self.query_point
+			.find(|(_, holder)| holder.get() == path_ent)
+		else {
+			return Vec::new();
+		};
+		let mut ents = vec![first_point];
+		self.query_point_ent
+			.traverse::<PointToPoint>([first_point])
+			.track_self()
+			.for_each(|_, _, b, _| {
+				ents.push(*b);
+			});
+		ents
+	}
+	/// Every connected pair as in [ConnectRelations::new]
+	pub fn point_pairs(&self, path_ent: Entity) -> Vec<(Entity, Entity)> {
+		let Some((first_point, _)) = self
+			.root_point
+			.iter()
+			.find(|(_, holder)| holder.get() == path_ent)
+		else {
+			return Vec::new();
+		};
+		let mut pairs = Vec::new();
+		self.query_point_ent
 			.traverse::<PointToPoint>([first_point])
 			.track_self()
 			.for_each(|a, _, b, _| {
-				let a_pos = a.0.xy() * Vec2::new(1.0, -1.0);
-				let first_arr = a_pos.to_array();
-				let xy_pos = b.0.xy() * Vec2::new(1.0, -1.0);
-				let to_arr = xy_pos.to_array();
-
-				if is_first {
-					is_first = false;
-					path_builder.move_to(first_arr);
-					pb.move_to(a_pos.x, a_pos.y);
+				pairs.push((*a, *b));
+			});
+		pairs
+	}
+	fn to_render_path(&self, path_ent: Entity) -> Option<Path> {
+		let points = self.points(path_ent)?;
+		let (.., close) = self.query_path.get(path_ent).ok()?;
+
+		let mut path_builder = PathBuilder::new();
+
+		let mut from = Vec2::ZERO;
+		points
+			.iter()
+			.enumerate()
+			.for_each(|(n, (xy_pos, point_type))| {
+				let xy_pos = *xy_pos;
+				if n == 0 {
+					path_builder.move_to(xy_pos);
+					from = xy_pos;
+					return;
 				}
 
-				match *b.1 {
+				match *point_type {
 					PointType::LineTo => {
-						path_builder.line_to(to_arr);
-						pb.line_to(xy_pos.x, xy_pos.y);
+						path_builder.line_to(xy_pos);
 					},
-					PointType::QuadraticBezier { to } => {
-						path_builder.quad_to(to_arr, to.to_array());
-						pb.quad_to(xy_pos.x, xy_pos.y, to.x, to.y);
+					PointType::MoveTo => {
+						if close.0 {
+							path_builder.close();
+						}
+						path_builder.move_to(xy_pos);
 					},
-					PointType::CubricBezier { ctrl1, ctrl2, to } => {
-						path_builder.curve_to(ctrl1.to_array(), ctrl2.to_array(), to.to_array());
-						pb.cubic_to(ctrl1.x, ctrl1.y, ctrl2.x, ctrl2.y, to.x, to.y);
+					PointType::QuadraticBezier { ctrl } => {
+						path_builder.quadratic_bezier_to(ctrl, xy_pos);
 					},
-					PointType::Arc {
-						radii,
-						sweep_angle,
-						x_rotation: _,
-					} => {
-						pb.arc(xy_pos.x, xy_pos.y, radii.x, 0.0, sweep_angle);
-						// path_builder.arc_to(rx, ry, angle, size, sweep, to);
+					PointType::CubricBezier { ctrl1, ctrl2, .. } => {
+						path_builder.cubic_bezier_to(ctrl1, ctrl2, xy_pos);
+					},
+					PointType::Arc { .. } => {
+						if let Some(start) = point_type.arc_lead(from, xy_pos) {
+							path_builder.line_to(start);
+						}
+						point_type.arc_curves(from, xy_pos).into_iter().for_each(
+							|(ctrl1, ctrl2, to)| {
+								path_builder.cubic_bezier_to(ctrl1, ctrl2, to);
+							},
+						);
 					},
 				}
+				from = point_type.end(from, xy_pos);
 			});
 
-		close.0.then(|| {
-			path_builder.close();
-			pb.close();
+		close.0.then(|| path_builder.close());
+
+		Some(path_builder.build())
+	}
+	pub fn to_image(&self, path_ent: Entity) -> Option<(Vec<u8>, Placement)> {
+		use zeno::*;
+		let path = self.to_render_path(path_ent)?.0;
+		let (_, stroke, fill, _) = self.query_path.get(path_ent).ok()?;
+		let dashed = self
+			.query_dash
+			.get(path_ent)
+			.ok()
+			.and_then(|dash| dash.dash_path(&path));
+		let flip = |point: lyon_path::math::Point| [point.x, -point.y];
+		let mut path_builder: Vec<Command> = Vec::new();
+		path.iter().for_each(|event| match event {
+			PathEvent::Begin { at } => {
+				path_builder.move_to(flip(at));
+			},
+			PathEvent::Line { to, .. } => {
+				path_builder.line_to(flip(to));
+			},
+			PathEvent::Quadratic { ctrl, to, .. } => {
+				path_builder.quad_to(flip(ctrl), flip(to));
+			},
+			PathEvent::Cubic {
+				ctrl1, ctrl2, to, ..
+			} => {
+				path_builder.curve_to(flip(ctrl1), flip(ctrl2), flip(to));
+			},
+			PathEvent::End { close, .. } => {
+				if close {
+					path_builder.close();
+				}
+			},
 		});
 
 		let StrokeOptions {
@@ -395,90 +537,153 @@ impl<'w, 's> PathParam<'w, 's> {
 			-placement.left as f32,
 			-placement.top as f32,
 		));
-		let path_finish = pb.finish();
 
 		let draw_op = DrawOptions::new();
 		let [r, g, b, a] = fill.color.to_srgba().to_u8_array();
 		dt.fill(
-			&path_finish,
+			&to_raqote_path(&path),
 			&Source::Solid(SolidSource::from_unpremultiplied_argb(a, b, g, r)),
 			&draw_op,
 		);
 
+		let stroke_path = dashed.as_ref().unwrap_or(&path);
+		let style = StrokeStyle {
+			cap: start_cap.to_raqote(),
+			width: line_width,
+			join: line_join.to_raqote(),
+			miter_limit,
+			..default()
+		};
 		let [r, g, b, a] = stroke.color.to_srgba().to_u8_array();
-		dt.stroke(
-			&path_finish,
-			&Source::Solid(SolidSource::from_unpremultiplied_argb(a, b, g, r)),
-			&StrokeStyle {
-				cap: start_cap.to_raqote(),
-				width: line_width,
-				join: line_join.to_raqote(),
-				miter_limit,
-				// dash_array: todo!(),
-				// dash_offset: todo!(),
-				..default()
-			},
-			&draw_op,
-		);
+		if start_cap == end_cap {
+			dt.stroke(
+				&to_raqote_path(stroke_path),
+				&Source::Solid(SolidSource::from_unpremultiplied_argb(a, b, g, r)),
+				&style,
+				&draw_op,
+			);
+		} else {
+			// raqote has one cap for both ends, so ends get capped by hand in a layer
+			// where overlap of cap and line does not stack alpha
+			let opaque = Source::Solid(SolidSource::from_unpremultiplied_argb(255, b, g, r));
+			dt.push_layer(a as f32 / 255.0);
+			dt.stroke(
+				&to_raqote_path(stroke_path),
+				&opaque,
+				&StrokeStyle {
+					cap: raqote::LineCap::Butt,
+					..style
+				},
+				&draw_op,
+			);
+			open_ends(stroke_path)
+				.into_iter()
+				.filter_map(|(point, outward, is_start)| {
+					let cap = if is_start { start_cap } else { end_cap };
+					cap_path(cap, point, outward, line_width)
+				})
+				.for_each(|cap| dt.fill(&cap, &opaque, &draw_op));
+			dt.pop_layer();
+		}
 
 		Some((dt.get_data_u8().to_vec(), placement))
 	}
-	pub fn to_svg(&self, path_ent: Entity) -> Option<SVG> {
-		use svg::{
-			node::element::{path::Data, Path},
-			Document,
-		};
+	/// Path element in SVG space, with bound of its stroke
+	pub fn to_svg_path(&self, path_ent: Entity) -> Option<(SvgPath, IRect)> {
 		use zeno::*;
 
-		let (first_point, _) = self
-			.root_point
-			.iter()
-			.find(|(_, holder)| holder.get() == path_ent)?;
-
-		let (children, stroke, fill, close) = self.query_path.get(path_ent).ok()?;
-		let mut path_builder: Vec<Command> = Vec::with_capacity(children.len());
+		let points = self.points(path_ent)?;
+		let (_, stroke, fill, close) = self.query_path.get(path_ent).ok()?;
+		let flip = |point: Vec2| point * Vec2::new(1.0, -1.0);
+		let mut path_builder: Vec<Command> = Vec::with_capacity(points.len());
 		let mut data = Data::new();
-		let mut is_first = true;
 
-		self.query_point
-			.traverse::<PointToPoint>([first_point])
-			.track_self()
-			.for_each(|a, _, b, _| {
-				let Vec2 { x, y } = a.0.xy() * Vec2::new(1.0, -1.0);
-				let first_arr = (x, y);
-				let Vec2 { x, y } = b.0.xy() * Vec2::new(1.0, -1.0);
-				let to_arr = (x, y);
-
-				if is_first {
-					is_first = false;
-					path_builder.move_to(first_arr);
-					data = data.clone().move_to(first_arr);
+		let mut from = Vec2::ZERO;
+		points
+			.iter()
+			.enumerate()
+			.for_each(|(n, (position, point_type))| {
+				let to = flip(*position);
+				if n == 0 {
+					path_builder.move_to(to.to_array());
+					data = data.clone().move_to((to.x, to.y));
+					from = *position;
+					return;
 				}
-
-				match *b.1 {
+				match *point_type {
 					PointType::LineTo => {
-						path_builder.line_to(to_arr);
-						data = data.clone().line_to(to_arr);
+						path_builder.line_to(to.to_array());
+						data = data.clone().line_to((to.x, to.y));
 					},
-					PointType::QuadraticBezier { to: Vec2 { x, y } } => {
-						path_builder.quad_to(to_arr, [x, y]);
-						data = data.clone().quadratic_curve_to((x, y));
+					PointType::MoveTo => {
+						if close.0 {
+							path_builder.close();
+							data = data.clone().close();
+						}
+						path_builder.move_to(to.to_array());
+						data = data.clone().move_to((to.x, to.y));
 					},
-					PointType::CubricBezier { ctrl1, ctrl2, to } => {
+					PointType::QuadraticBezier { ctrl } => {
+						let ctrl = flip(ctrl);
+						path_builder.quad_to(ctrl.to_array(), to.to_array());
+						data = data
+							.clone()
+							.quadratic_curve_to((ctrl.x, ctrl.y, to.x, to.y));
+					},
+					PointType::CubricBezier { ctrl1, ctrl2, .. } => {
+						let ctrl1 = flip(ctrl1);
+						let ctrl2 = flip(ctrl2);
 						path_builder.curve_to(ctrl1.to_array(), ctrl2.to_array(), to.to_array());
-						// data = data.clone().cubic_curve_to(ctrl1.to_array(), ctrl2.to_array(), to.to_array());
+						data = data
+							.clone()
+							.cubic_curve_to(vec![ctrl1.x, ctrl1.y, ctrl2.x, ctrl2.y, to.x, to.y]);
 					},
 					PointType::Arc {
-						radii: _,
-						sweep_angle: _,
-						x_rotation: _,
+						radii,
+						sweep_angle,
+						x_rotation,
 					} => {
-						// data = data.arc_to(rx, ry, angle, size, sweep, to);
+						if let Some(start) = point_type.arc_lead(from, *position) {
+							let start = flip(start);
+							path_builder.line_to(start.to_array());
+							data = data.clone().line_to((start.x, start.y));
+						}
+						point_type.arc_curves(from, *position).into_iter().for_each(
+							|(ctrl1, ctrl2, to)| {
+								let (ctrl1, ctrl2, to) = (flip(ctrl1), flip(ctrl2), flip(to));
+								path_builder.curve_to(
+									ctrl1.to_array(),
+									ctrl2.to_array(),
+									to.to_array(),
+								);
+							},
+						);
+						// Flipped y turn counter clockwise into clockwise, so does mirrored radii
+						let angle = -x_rotation.to_degrees();
+						let sweep = (sweep_angle < 0.0) != (radii.x * radii.y < 0.0);
+						point_type
+							.arc_ends(from, *position)
+							.into_iter()
+							.map(flip)
+							.for_each(|end| {
+								data = data.clone().elliptical_arc_to(vec![
+									radii.x.abs(),
+									radii.y.abs(),
+									angle,
+									0.0,
+									sweep as u8 as f32,
+									end.x,
+									end.y,
+								]);
+							});
 					},
 				}
+				from = point_type.end(from, *position);
 			});
-
-		close.0.then(|| data = data.clone().close());
+		if close.0 {
+			path_builder.close();
+			data = data.clone().close();
+		}
 
 		let StrokeOptions {
 			start_cap,
@@ -489,18 +694,20 @@ impl<'w, 's> PathParam<'w, 's> {
 			..
 		} = stroke.options;
 		let FillOptions { fill_rule, .. } = fill.options;
-		let placement = {
-			Mask::new(&path_builder)
-				.style(
-					Stroke::new(line_width)
-						.join(line_join.to_zeno())
-						.miter_limit(miter_limit)
-						.cap(Cap::Round)
-						.caps(start_cap.to_zeno(), end_cap.to_zeno()),
-				)
-				.render()
-				.1
-		};
+		let Placement {
+			left,
+			top,
+			width,
+			height,
+		} = Mask::new(&path_builder)
+			.style(
+				Stroke::new(line_width)
+					.join(line_join.to_zeno())
+					.miter_limit(miter_limit)
+					.caps(start_cap.to_zeno(), end_cap.to_zeno()),
+			)
+			.render()
+			.1;
 		let fill_rule = match fill_rule {
 			FillRule::EvenOdd => "evenodd",
 			FillRule::NonZero => "nonzero",
@@ -511,25 +718,70 @@ impl<'w, 's> PathParam<'w, 's> {
 			LineJoin::Round => "round",
 			LineJoin::Bevel => "bevel",
 		};
-		let path = Path::new()
-			.set("fill", fill.color.to_srgba().to_hex())
+		let line_cap = match start_cap {
+			LineCap::Butt => "butt",
+			LineCap::Square => "square",
+			LineCap::Round => "round",
+		};
+		let fill_color = fill.color.to_srgba();
+		let stroke_color = stroke.color.to_srgba();
+		let mut path = SvgPath::new()
+			.set("fill", fill_color.with_alpha(1.0).to_hex())
+			.set("fill-opacity", fill_color.alpha)
 			.set("fill-rule", fill_rule)
-			.set("stroke", stroke.color.to_srgba().to_hex())
+			.set("stroke", stroke_color.with_alpha(1.0).to_hex())
+			.set("stroke-opacity", stroke_color.alpha)
 			.set("stroke-width", line_width)
-			.set("stroke-miterlimit", miter_limit)
+			.set("stroke-linecap", line_cap)
 			.set("stroke-linejoin", line_join)
+			.set("stroke-miterlimit", miter_limit)
 			.set("d", data);
-		let document = Document::new()
-			.set(
-				"viewBox",
-				(
-					placement.left - 5,
-					placement.top - 5,
-					placement.left + placement.width as i32 + 5,
-					placement.top + placement.height as i32 + 5,
-				),
-			)
-			.add(path);
-		Some(document)
+		if let Ok(dash) = self.query_dash.get(path_ent) {
+			if let Some(array) = dash.to_svg() {
+				path = path
+					.set("stroke-dasharray", array)
+					.set("stroke-dashoffset", dash.offset);
+			}
+		}
+		let min = IVec2::new(left, top);
+		let bound = IRect::from_corners(min, min + UVec2::new(width, height).as_ivec2());
+		Some((path, bound))
+	}
+	/// Single path document, origin at top left of the path
+	pub fn to_svg(&self, path_ent: Entity, opacity: i8) -> Option<SVG> {
+		let (path, bound) = self.to_svg_path(path_ent)?;
+		let path = path.set(
+			"transform",
+			format!("translate({} {})", -bound.min.x, -bound.min.y),
+		);
+		Some(svg_document(
+			vec![(Box::new(path), bound, opacity)],
+			bound.min,
+		))
 	}
 }
+
+/// Elements bottom to top with bound and opacity in percent, `origin` of SVG space at top left
+pub fn svg_document(elements: Vec<(Box<dyn SvgNode>, IRect, i8)>, origin: IVec2) -> SVG {
+	let bound = elements
+		.iter()
+		.map(|(_, bound, _)| *bound)
+		.reduce(|bound, rect| bound.union(rect))
+		.unwrap_or_default();
+	let min = bound.min - origin;
+	let size = bound.size();
+	elements.into_iter().fold(
+		SVG::new()
+			.set("width", size.x)
+			.set("height", size.y)
+			.set("viewBox", (min.x, min.y, size.x, size.y)),
+		|document, (element, _, opacity)| match opacity < 100 {
+			true => document.add(
+				SvgGroup::new()
+					.set("opacity", opacity as f32 / 100.0)
+					.add(element),
+			),
+			false => document.add(element),
+		},
+	)
+}
@@ -0,0 +1,852 @@
+use super::*;
+use bevy::{color::palettes::css, math::Affine2};
+use std::f32::consts::TAU;
+use svg::{
+	node::{
+		element::{
+			path::{Command, Data, Position},
+			tag::Type,
+		},
+		Attributes,
+	},
+	parser::Event,
+};
+
+pub const SVG_EXTENSION: &str = "svg";
+
+#[derive(Debug)]
+pub enum SvgFileError {
+	Read(std::io::Error),
+	Parse(svg::parser::Error),
+	NoShape,
+}
+
+/// Shapes as path objects, outlines of one shape in one path, `<g>` as groups, in
+/// document order
+pub fn svg_to_rdio(name: &str, content: &str) -> Result<SavedRdio, SvgFileError> {
+	let parser = svg::read(content).map_err(SvgFileError::Read)?;
+	// World y axis go up
+	let mut styles = vec![SvgStyle {
+		transform: Affine2::from_scale(Vec2::new(1.0, -1.0)),
+		..default()
+	}];
+	// Container tags, with its group place if any
+	let mut opened: Vec<Option<usize>> = Vec::new();
+	let mut skip_depth = 0;
+	let mut objects = vec![saved_group(name, 1.0)];
+
+	for event in parser {
+		let (tag, tag_type, attributes) = match event {
+			Event::Tag(tag, tag_type, attributes) => (tag, tag_type, attributes),
+			Event::Error(e) => return Err(SvgFileError::Parse(e)),
+			_ => continue,
+		};
+		if skip_depth > 0 {
+			match tag_type {
+				Type::Start => skip_depth += 1,
+				Type::End => skip_depth -= 1,
+				Type::Empty => {},
+			}
+			continue;
+		}
+		let is_hidden = attr(&attributes, "display").is_some_and(|display| display == "none");
+		match (tag, tag_type) {
+			("defs" | "clipPath" | "mask" | "marker" | "pattern" | "symbol", Type::Start) => {
+				skip_depth = 1;
+			},
+			("svg" | "g" | "a" | "switch", Type::Start) if is_hidden => skip_depth = 1,
+			("svg" | "g" | "a" | "switch", Type::Start) => {
+				let mut style = styles.last().unwrap().inherit(&attributes);
+				if tag == "svg" {
+					style.transform = style.transform * viewport(&attributes, !opened.is_empty());
+				}
+				let group_at = (tag == "g").then(|| {
+					let opacity = parse_opacity(attr(&attributes, "opacity")).unwrap_or(1.0);
+					objects.push(saved_group(&element_name(tag, &attributes), opacity));
+					objects.len() - 1
+				});
+				styles.push(style);
+				opened.push(group_at);
+			},
+			("svg" | "g" | "a" | "switch", Type::End) => {
+				if opened.is_empty() {
+					continue;
+				}
+				styles.pop();
+				if let Some(at) = opened.pop().flatten() {
+					close_group(&mut objects, at);
+				}
+			},
+			(_, Type::End) => {},
+			_ if is_hidden => {},
+			_ => {
+				let Some(outline) = element_outline(tag, &attributes) else {
+					continue;
+				};
+				let style = styles.last().unwrap().inherit(&attributes);
+				let opacity = parse_opacity(attr(&attributes, "opacity")).unwrap_or(1.0);
+				let sub_paths = outline
+					.0
+					.iter()
+					.filter(|sub_path| !sub_path.segments.is_empty())
+					.collect::<Vec<_>>();
+				let Some(first) = sub_paths.first() else {
+					continue;
+				};
+				let saved_path = |sub_paths: &[&SubPath]| SavedObject {
+					name: element_name(tag, &attributes),
+					opacity: ObjectOpacity((opacity * 100.0).round() as i8, 100),
+					blend: ObjectBlend::default(),
+					move_lock: false,
+					kind: SavedKind::Path {
+						stroke: style.stroke_net(),
+						dash: style.stroke_dash(),
+						fill: style.fill_net(tag != "line"),
+						close: sub_paths[0].close,
+						points: sub_paths
+							.iter()
+							.enumerate()
+							.flat_map(|(n, sub_path)| {
+								let mut points = sub_path.to_points(style.transform);
+								if n > 0 {
+									points[0].1 = PointType::MoveTo;
+								}
+								points
+							})
+							.collect(),
+					},
+				};
+				// Path has one closing for all its outlines
+				if sub_paths
+					.iter()
+					.all(|sub_path| sub_path.close == first.close)
+				{
+					objects.push(saved_path(&sub_paths));
+				} else {
+					let name = element_name(tag, &attributes);
+					warn!(
+						"Outlines of {} differ in closing, imported apart with holes filled",
+						name
+					);
+					let at = objects.len();
+					objects.push(saved_group(&name, 1.0));
+					objects.extend(sub_paths.iter().map(|sub_path| saved_path(&[*sub_path])));
+					close_group(&mut objects, at);
+				}
+			},
+		}
+	}
+
+	match objects.len() {
+		1 => Err(SvgFileError::NoShape),
+		// Only one object, no need of file group
+		2 => Ok(SavedRdio {
+			objects: objects.split_off(1),
+		}),
+		_ => {
+			close_group(&mut objects, 0);
+			Ok(SavedRdio { objects })
+		},
+	}
+}
+
+fn saved_group(name: &str, opacity: f32) -> SavedObject {
+	SavedObject {
+		name: name.to_owned(),
+		opacity: ObjectOpacity((opacity * 100.0).round() as i8, 100),
+		blend: ObjectBlend::default(),
+		move_lock: false,
+		kind: SavedKind::Group { len: 0 },
+	}
+}
+
+/// Count members of group placed at `at`, empty group removed
+fn close_group(objects: &mut Vec<SavedObject>, at: usize) {
+	let len = objects.len() - at - 1;
+	if len == 0 {
+		objects.truncate(at);
+	} else if let SavedKind::Group { len: group_len } = &mut objects[at].kind {
+		*group_len = len;
+	}
+}
+
+fn element_name(tag: &str, attributes: &Attributes) -> String {
+	attr(attributes, "id").unwrap_or_else(|| tag.to_owned())
+}
+
+fn attr(attributes: &Attributes, name: &str) -> Option<String> {
+	attributes.get(name).map(|value| value.to_string())
+}
+
+/// Inherited presentation of element
+#[derive(Clone)]
+struct SvgStyle {
+	transform: Affine2,
+	fill: Option<Color>,
+	stroke: Option<Color>,
+	stroke_width: f32,
+	dash_array: Vec<f32>,
+	dash_offset: f32,
+	fill_rule: FillRule,
+	fill_opacity: f32,
+	stroke_opacity: f32,
+}
+
+impl Default for SvgStyle {
+	fn default() -> Self {
+		Self {
+			transform: Affine2::IDENTITY,
+			fill: Some(Color::BLACK),
+			stroke: None,
+			stroke_width: 1.0,
+			dash_array: Vec::new(),
+			dash_offset: 0.0,
+			fill_rule: FillRule::NonZero,
+			fill_opacity: 1.0,
+			stroke_opacity: 1.0,
+		}
+	}
+}
+
+impl SvgStyle {
+	/// Attribute first, then `style` declarations override it
+	fn inherit(&self, attributes: &Attributes) -> Self {
+		let mut style = self.clone();
+		if let Some(transform) = attr(attributes, "transform") {
+			style.transform = self.transform * parse_transform(&transform);
+		}
+		let declared = attr(attributes, "style").unwrap_or_default();
+		let properties = [
+			"fill",
+			"stroke",
+			"stroke-width",
+			"stroke-dasharray",
+			"stroke-dashoffset",
+			"fill-rule",
+			"fill-opacity",
+			"stroke-opacity",
+		]
+		.into_iter()
+		.filter_map(|property| Some((property, attr(attributes, property)?)))
+		.chain(declared.split(';').filter_map(|declaration| {
+			let (property, value) = declaration.split_once(':')?;
+			Some((property.trim(), value.trim().to_owned()))
+		}))
+		.collect::<Vec<_>>();
+		properties
+			.into_iter()
+			.for_each(|(property, value)| match property {
+				"fill" => style.fill = parse_paint(&value).unwrap_or(style.fill),
+				"stroke" => style.stroke = parse_paint(&value).unwrap_or(style.stroke),
+				"stroke-width" => {
+					style.stroke_width = parse_number(Some(value)).unwrap_or(style.stroke_width);
+				},
+				"stroke-dasharray" => style.dash_array = parse_numbers(&value),
+				"stroke-dashoffset" => {
+					style.dash_offset = parse_number(Some(value)).unwrap_or(style.dash_offset);
+				},
+				"fill-rule" => {
+					style.fill_rule = match value.as_str() {
+						"evenodd" => FillRule::EvenOdd,
+						_ => FillRule::NonZero,
+					};
+				},
+				"fill-opacity" => {
+					style.fill_opacity = parse_opacity(Some(value)).unwrap_or(style.fill_opacity);
+				},
+				"stroke-opacity" => {
+					style.stroke_opacity =
+						parse_opacity(Some(value)).unwrap_or(style.stroke_opacity);
+				},
+				_ => {},
+			});
+		style
+	}
+	fn stroke_net(&self) -> StrokeNet {
+		let scale = self.transform.matrix2.determinant().abs().sqrt();
+		StrokeNet {
+			options: StrokeOptions::DEFAULT.with_line_width(self.stroke_width * scale),
+			color: self.stroke.map_or(Color::NONE, |color| {
+				color.with_alpha(color.alpha() * self.stroke_opacity)
+			}),
+		}
+	}
+	/// Dash beyond what can be drawn is solid, as SVG does with invalid array
+	fn stroke_dash(&self) -> StrokeDash {
+		let scale = self.transform.matrix2.determinant().abs().sqrt();
+		let dash = StrokeDash {
+			array: self.dash_array.iter().map(|len| len * scale).collect(),
+			offset: self.dash_offset * scale,
+		};
+		match dash.is_valid() {
+			true => dash,
+			false => StrokeDash::default(),
+		}
+	}
+	/// `<line>` is never filled
+	fn fill_net(&self, is_filled: bool) -> FillNet {
+		FillNet {
+			options: FillOptions::default().with_fill_rule(self.fill_rule),
+			color: self
+				.fill
+				.filter(|_| is_filled)
+				.map_or(Color::NONE, |color| {
+					color.with_alpha(color.alpha() * self.fill_opacity)
+				}),
+		}
+	}
+}
+
+/// `None` when unknown, `Some(None)` for no paint
+fn parse_paint(value: &str) -> Option<Option<Color>> {
+	let value = value.trim();
+	if let Some(hex) = value.strip_prefix('#') {
+		return Srgba::hex(hex).ok().map(|color| Some(color.into()));
+	}
+	if let Some(args) = value
+		.strip_prefix("rgba(")
+		.or_else(|| value.strip_prefix("rgb("))
+	{
+		let channels = args
+			.trim_end_matches(')')
+			.split(',')
+			.map(|channel| {
+				let channel = channel.trim();
+				match channel.strip_suffix('%') {
+					Some(percent) => percent.parse::<f32>().ok().map(|v| v / 100.0),
+					None => channel.parse::<f32>().ok().map(|v| v / 255.0),
+				}
+			})
+			.collect::<Option<Vec<_>>>()?;
+		let alpha = channels.get(3).map_or(1.0, |alpha| alpha * 255.0);
+		return match channels[..] {
+			[r, g, b, ..] => Some(Some(Color::srgba(r, g, b, alpha))),
+			_ => None,
+		};
+	}
+	let named = match value {
+		"none" | "transparent" => return Some(None),
+		"black" | "currentColor" => css::BLACK,
+		"white" => css::WHITE,
+		"red" => css::RED,
+		"green" => css::GREEN,
+		"lime" => css::LIME,
+		"blue" => css::BLUE,
+		"yellow" => css::YELLOW,
+		"orange" => css::ORANGE,
+		"purple" => css::PURPLE,
+		"fuchsia" | "magenta" => css::FUCHSIA,
+		"aqua" | "cyan" => css::AQUA,
+		"gray" | "grey" => css::GRAY,
+		"silver" => css::SILVER,
+		"maroon" => css::MAROON,
+		"navy" => css::NAVY,
+		"olive" => css::OLIVE,
+		"teal" => css::TEAL,
+		_ => return None,
+	};
+	Some(Some(named.into()))
+}
+
+/// Unit suffix like `px` is ignored
+fn parse_number(value: Option<String>) -> Option<f32> {
+	value?
+		.trim()
+		.trim_end_matches(|c: char| c.is_ascii_alphabetic() || c == '%')
+		.parse()
+		.ok()
+}
+
+/// Number or percent, clamped to 0 to 1
+fn parse_opacity(value: Option<String>) -> Option<f32> {
+	let value = value?;
+	let opacity = match value.trim().strip_suffix('%') {
+		Some(percent) => percent.trim().parse::<f32>().ok()? / 100.0,
+		None => parse_number(Some(value))?,
+	};
+	opacity.is_finite().then(|| opacity.clamp(0.0, 1.0))
+}
+
+fn parse_numbers(value: &str) -> Vec<f32> {
+	value
+		.split(|c: char| c.is_whitespace() || c == ',')
+		.filter_map(|number| number.parse().ok())
+		.collect()
+}
+
+/// `viewBox` fitted into `width` and `height` with uniform scale centered, stretched
+/// for `preserveAspectRatio="none"`. Nested `<svg>` is also placed at `x` and `y`
+fn viewport(attributes: &Attributes, is_nested: bool) -> Affine2 {
+	// Percent is of viewport outside the file, left as is
+	let length = |name: &str| {
+		attr(attributes, name)
+			.filter(|value| !value.trim_end().ends_with('%'))
+			.and_then(|value| parse_number(Some(value)))
+			.filter(|value| value.is_finite())
+	};
+	let place = match is_nested {
+		true => Vec2::new(length("x").unwrap_or(0.0), length("y").unwrap_or(0.0)),
+		false => Vec2::ZERO,
+	};
+	let view_box = attr(attributes, "viewBox")
+		.map(|value| parse_numbers(&value))
+		.unwrap_or_default();
+	let [min_x, min_y, width, height] = view_box[..] else {
+		return Affine2::from_translation(place);
+	};
+	let view_size = Vec2::new(width, height);
+	if !view_size.is_finite()
+		|| view_size.min_element() <= 0.0
+		|| !min_x.is_finite()
+		|| !min_y.is_finite()
+	{
+		return Affine2::from_translation(place);
+	}
+	let size = Vec2::new(
+		length("width")
+			.filter(|width| *width > 0.0)
+			.unwrap_or(width),
+		length("height")
+			.filter(|height| *height > 0.0)
+			.unwrap_or(height),
+	);
+	let aspect = attr(attributes, "preserveAspectRatio").unwrap_or_default();
+	let (scale, offset) = if aspect.trim() == "none" {
+		(size / view_size, Vec2::ZERO)
+	} else {
+		let scale = (size / view_size).min_element();
+		(Vec2::splat(scale), (size - view_size * scale) / 2.0)
+	};
+	Affine2::from_translation(place + offset)
+		* Affine2::from_scale(scale)
+		* Affine2::from_translation(-Vec2::new(min_x, min_y))
+}
+
+/// Functions applied right to left like SVG
+fn parse_transform(value: &str) -> Affine2 {
+	value
+		.split(')')
+		.filter_map(|function| {
+			let (name, args) = function.split_once('(')?;
+			let args = parse_numbers(args);
+			let arg = |n: usize| args.get(n).copied();
+			let name = name.trim_matches(|c: char| c.is_whitespace() || c == ',');
+			Some(match (name, arg(0)?) {
+				("matrix", _) => Affine2::from_cols_array(&args.get(..6)?.try_into().ok()?),
+				("translate", x) => Affine2::from_translation(Vec2::new(x, arg(1).unwrap_or(0.0))),
+				("scale", x) => Affine2::from_scale(Vec2::new(x, arg(1).unwrap_or(x))),
+				("rotate", angle) => {
+					let center = Vec2::new(arg(1).unwrap_or(0.0), arg(2).unwrap_or(0.0));
+					Affine2::from_translation(center)
+						* Affine2::from_angle(angle.to_radians())
+						* Affine2::from_translation(-center)
+				},
+				("skewX", angle) => Affine2::from_cols(
+					Vec2::X,
+					Vec2::new(angle.to_radians().tan(), 1.0),
+					Vec2::ZERO,
+				),
+				("skewY", angle) => Affine2::from_cols(
+					Vec2::new(1.0, angle.to_radians().tan()),
+					Vec2::Y,
+					Vec2::ZERO,
+				),
+				_ => return None,
+			})
+		})
+		.fold(Affine2::IDENTITY, |transform, function| {
+			transform * function
+		})
+}
+
+fn element_outline(tag: &str, attributes: &Attributes) -> Option<Outline> {
+	let number = |name: &str| parse_number(attr(attributes, name));
+	let mut outline = Outline::default();
+	match tag {
+		"path" => outline.data(&attr(attributes, "d")?),
+		"rect" => {
+			let min = Vec2::new(number("x").unwrap_or(0.0), number("y").unwrap_or(0.0));
+			let size = Vec2::new(number("width")?, number("height")?);
+			if size.min_element() <= 0.0 {
+				return None;
+			}
+			let radii = match (number("rx"), number("ry")) {
+				(Some(rx), Some(ry)) => Vec2::new(rx, ry),
+				(Some(r), None) | (None, Some(r)) => Vec2::splat(r),
+				(None, None) => Vec2::ZERO,
+			}
+			.clamp(Vec2::ZERO, size / 2.0);
+			if radii.min_element() <= 0.0 {
+				outline.shape(ShapeKind::Rectangle.points(min, min + size));
+			} else {
+				let max = min + size;
+				outline.move_to(Vec2::new(min.x + radii.x, min.y));
+				[
+					(
+						Vec2::new(max.x - radii.x, min.y),
+						Vec2::new(max.x, min.y + radii.y),
+					),
+					(
+						Vec2::new(max.x, max.y - radii.y),
+						Vec2::new(max.x - radii.x, max.y),
+					),
+					(
+						Vec2::new(min.x + radii.x, max.y),
+						Vec2::new(min.x, max.y - radii.y),
+					),
+					(
+						Vec2::new(min.x, min.y + radii.y),
+						Vec2::new(min.x + radii.x, min.y),
+					),
+				]
+				.into_iter()
+				.for_each(|(side_end, corner_end)| {
+					outline.line_to(side_end);
+					outline.arc_to(radii, 0.0, false, true, corner_end);
+				});
+				outline.close();
+			}
+		},
+		"circle" | "ellipse" => {
+			let center = Vec2::new(number("cx").unwrap_or(0.0), number("cy").unwrap_or(0.0));
+			let radii = match tag {
+				"circle" => Vec2::splat(number("r")?),
+				_ => Vec2::new(number("rx")?, number("ry")?),
+			};
+			if radii.min_element() <= 0.0 {
+				return None;
+			}
+			outline.shape(ShapeKind::Ellipse.points(center - radii, center + radii));
+		},
+		"line" => {
+			outline.move_to(Vec2::new(
+				number("x1").unwrap_or(0.0),
+				number("y1").unwrap_or(0.0),
+			));
+			outline.line_to(Vec2::new(
+				number("x2").unwrap_or(0.0),
+				number("y2").unwrap_or(0.0),
+			));
+		},
+		"polyline" | "polygon" => {
+			let numbers = parse_numbers(&attr(attributes, "points")?);
+			numbers
+				.chunks_exact(2)
+				.map(|xy| Vec2::new(xy[0], xy[1]))
+				.enumerate()
+				.for_each(|(n, point)| match n {
+					0 => outline.move_to(point),
+					_ => outline.line_to(point),
+				});
+			if tag == "polygon" {
+				outline.close();
+			}
+		},
+		_ => return None,
+	}
+	Some(outline)
+}
+
+/// Segment in SVG user space, end kept aside
+enum Segment {
+	Line,
+	Quadratic(Vec2),
+	Cubic(Vec2, Vec2),
+	Arc {
+		center: Vec2,
+		radii: Vec2,
+		x_rotation: f32,
+		start: f32,
+		sweep: f32,
+	},
+}
+
+struct SubPath {
+	begin: Vec2,
+	segments: Vec<(Vec2, Segment)>,
+	close: bool,
+}
+
+impl SubPath {
+	/// Points in [PointToPoint] order
+	fn to_points(&self, transform: Affine2) -> Vec<(Vec2, PointType)> {
+		let at = |point: Vec2| transform.transform_point2(point);
+		let mut points = vec![(at(self.begin), PointType::LineTo)];
+		self.segments
+			.iter()
+			.for_each(|(end, segment)| match *segment {
+				Segment::Line => points.push((at(*end), PointType::LineTo)),
+				Segment::Quadratic(ctrl) => {
+					points.push((at(*end), PointType::QuadraticBezier { ctrl: at(ctrl) }));
+				},
+				Segment::Cubic(ctrl1, ctrl2) => points.push((
+					at(*end),
+					PointType::CubricBezier {
+						ctrl1: at(ctrl1),
+						ctrl2: at(ctrl2),
+						to: at(*end),
+					},
+				)),
+				Segment::Arc {
+					center,
+					radii,
+					x_rotation,
+					start,
+					sweep,
+				} => {
+					let x_axis = transform.matrix2.x_axis;
+					let y_axis = transform.matrix2.y_axis;
+					let scale = x_axis.length();
+					let is_conformal = x_axis.dot(y_axis).abs() <= 1e-4 * scale * scale
+						&& (y_axis.length() - scale).abs() <= 1e-4 * scale;
+					if !is_conformal {
+						// Skewed or stretched arc is no longer elliptical arc of this form
+						arc_cubics(center, radii, x_rotation, start, sweep)
+							.into_iter()
+							.for_each(|(ctrl1, ctrl2, to)| {
+								points.push((
+									at(to),
+									PointType::CubricBezier {
+										ctrl1: at(ctrl1),
+										ctrl2: at(ctrl2),
+										to: at(to),
+									},
+								));
+							});
+						return;
+					}
+					let angle = x_axis.y.atan2(x_axis.x);
+					// Mirrored space run the arc backward
+					let (x_rotation, sweep_angle) = if x_axis.perp_dot(y_axis) < 0.0 {
+						(angle - x_rotation, -sweep)
+					} else {
+						(angle + x_rotation, sweep)
+					};
+					points.push((
+						at(center),
+						PointType::Arc {
+							radii: radii * scale,
+							sweep_angle,
+							x_rotation,
+						},
+					));
+				},
+			});
+		points
+	}
+}
+
+/// Sub paths of one element in SVG user space
+#[derive(Default)]
+struct Outline(Vec<SubPath>);
+
+impl Outline {
+	fn current(&self) -> Vec2 {
+		self.0
+			.last()
+			.map_or(Vec2::ZERO, |sub_path| match sub_path.close {
+				true => sub_path.begin,
+				false => sub_path
+					.segments
+					.last()
+					.map_or(sub_path.begin, |(end, _)| *end),
+			})
+	}
+	fn move_to(&mut self, to: Vec2) {
+		self.0.push(SubPath {
+			begin: to,
+			segments: Vec::new(),
+			close: false,
+		});
+	}
+	fn push(&mut self, end: Vec2, segment: Segment) {
+		if self.0.last().map_or(true, |sub_path| sub_path.close) {
+			self.move_to(self.current());
+		}
+		if let Some(sub_path) = self.0.last_mut() {
+			sub_path.segments.push((end, segment));
+		}
+	}
+	fn line_to(&mut self, to: Vec2) {
+		self.push(to, Segment::Line);
+	}
+	fn close(&mut self) {
+		if let Some(sub_path) = self.0.last_mut() {
+			sub_path.close = true;
+		}
+	}
+	/// Endpoint to center parameterization, SVG spec F.6.5
+	fn arc_to(&mut self, radii: Vec2, x_rotation: f32, large: bool, sweep: bool, to: Vec2) {
+		let from = self.current();
+		if from == to {
+			return;
+		}
+		let mut radii = radii.abs();
+		if radii.min_element() <= 0.0 {
+			self.line_to(to);
+			return;
+		}
+		let half = Vec2::from_angle(-x_rotation).rotate((from - to) / 2.0);
+		let lambda = (half / radii).length_squared();
+		if lambda > 1.0 {
+			radii *= lambda.sqrt();
+		}
+		let (rx2, ry2) = (radii.x * radii.x, radii.y * radii.y);
+		let (hx2, hy2) = (half.x * half.x, half.y * half.y);
+		let sign = if large == sweep { -1.0 } else { 1.0 };
+		let coef = sign
+			* ((rx2 * ry2 - rx2 * hy2 - ry2 * hx2) / (rx2 * hy2 + ry2 * hx2))
+				.max(0.0)
+				.sqrt();
+		let center_rotated =
+			coef * Vec2::new(radii.x * half.y / radii.y, -radii.y * half.x / radii.x);
+		let center = Vec2::from_angle(x_rotation).rotate(center_rotated) + (from + to) / 2.0;
+		let begin = (half - center_rotated) / radii;
+		let end = (-half - center_rotated) / radii;
+		let mut sweep_angle = begin.perp_dot(end).atan2(begin.dot(end));
+		if sweep && sweep_angle < 0.0 {
+			sweep_angle += TAU;
+		} else if !sweep && sweep_angle > 0.0 {
+			sweep_angle -= TAU;
+		}
+		self.push(
+			to,
+			Segment::Arc {
+				center,
+				radii,
+				x_rotation,
+				start: begin.y.atan2(begin.x),
+				sweep: sweep_angle,
+			},
+		);
+	}
+	/// Outline from [ShapeKind] points
+	fn shape(&mut self, points: Vec<(Vec2, PointType)>) {
+		points
+			.into_iter()
+			.enumerate()
+			.for_each(|(n, (position, point_type))| match (n, point_type) {
+				(0, _) => self.move_to(position),
+				(_, PointType::QuadraticBezier { ctrl }) => {
+					self.push(position, Segment::Quadratic(ctrl));
+				},
+				(_, PointType::CubricBezier { ctrl1, ctrl2, .. }) => {
+					self.push(position, Segment::Cubic(ctrl1, ctrl2));
+				},
+				_ => self.line_to(position),
+			});
+		self.close();
+	}
+	/// Path `d` attribute
+	fn data(&mut self, value: &str) {
+		let Ok(data) = Data::parse(value) else {
+			warn!("Invalid path data: {}", value);
+			return;
+		};
+		// Previous control point for smooth curve
+		let mut last_cubic: Option<Vec2> = None;
+		let mut last_quadratic: Option<Vec2> = None;
+		data.iter().for_each(|command| {
+			let (cubic, quadratic) = match command {
+				Command::Move(position, params) => {
+					params.chunks_exact(2).enumerate().for_each(|(n, xy)| {
+						let to = self.relative(position, xy[0], xy[1]);
+						match n {
+							0 => self.move_to(to),
+							_ => self.line_to(to),
+						}
+					});
+					(None, None)
+				},
+				Command::Line(position, params) => {
+					params.chunks_exact(2).for_each(|xy| {
+						let to = self.relative(position, xy[0], xy[1]);
+						self.line_to(to);
+					});
+					(None, None)
+				},
+				Command::HorizontalLine(position, params) => {
+					params.iter().for_each(|x| {
+						let from = self.current();
+						let to = self.relative(position, *x, 0.0);
+						self.line_to(Vec2::new(to.x, from.y));
+					});
+					(None, None)
+				},
+				Command::VerticalLine(position, params) => {
+					params.iter().for_each(|y| {
+						let from = self.current();
+						let to = self.relative(position, 0.0, *y);
+						self.line_to(Vec2::new(from.x, to.y));
+					});
+					(None, None)
+				},
+				Command::QuadraticCurve(position, params) => {
+					params.chunks_exact(4).fold((None, None), |_, xy| {
+						let ctrl = self.relative(position, xy[0], xy[1]);
+						let to = self.relative(position, xy[2], xy[3]);
+						self.push(to, Segment::Quadratic(ctrl));
+						(None, Some(ctrl))
+					})
+				},
+				Command::SmoothQuadraticCurve(position, params) => {
+					params
+						.chunks_exact(2)
+						.fold((None, last_quadratic), |(_, last), xy| {
+							let from = self.current();
+							let ctrl = last.map_or(from, |ctrl| from * 2.0 - ctrl);
+							let to = self.relative(position, xy[0], xy[1]);
+							self.push(to, Segment::Quadratic(ctrl));
+							(None, Some(ctrl))
+						})
+				},
+				Command::CubicCurve(position, params) => {
+					params.chunks_exact(6).fold((None, None), |_, xy| {
+						let ctrl1 = self.relative(position, xy[0], xy[1]);
+						let ctrl2 = self.relative(position, xy[2], xy[3]);
+						let to = self.relative(position, xy[4], xy[5]);
+						self.push(to, Segment::Cubic(ctrl1, ctrl2));
+						(Some(ctrl2), None)
+					})
+				},
+				Command::SmoothCubicCurve(position, params) => {
+					params
+						.chunks_exact(4)
+						.fold((last_cubic, None), |(last, _), xy| {
+							let from = self.current();
+							let ctrl1 = last.map_or(from, |ctrl| from * 2.0 - ctrl);
+							let ctrl2 = self.relative(position, xy[0], xy[1]);
+							let to = self.relative(position, xy[2], xy[3]);
+							self.push(to, Segment::Cubic(ctrl1, ctrl2));
+							(Some(ctrl2), None)
+						})
+				},
+				Command::EllipticalArc(position, params) => {
+					params.chunks_exact(7).for_each(|arc| {
+						let to = self.relative(position, arc[5], arc[6]);
+						self.arc_to(
+							Vec2::new(arc[0], arc[1]),
+							arc[2].to_radians(),
+							arc[3] != 0.0,
+							arc[4] != 0.0,
+							to,
+						);
+					});
+					(None, None)
+				},
+				Command::Close => {
+					self.close();
+					(None, None)
+				},
+			};
+			last_cubic = cubic;
+			last_quadratic = quadratic;
+		});
+	}
+	fn relative(&self, position: &Position, x: f32, y: f32) -> Vec2 {
+		match position {
+			Position::Absolute => Vec2::new(x, y),
+			Position::Relative => self.current() + Vec2::new(x, y),
+		}
+	}
+}
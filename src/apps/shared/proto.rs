@@ -17,6 +17,7 @@ impl Plugin for ProtocolPlugin {
 
 		app.add_plugins((
 			VerifyActionPlugin::<PenDraw>::default(),
+			VerifyActionPlugin::<BucketFill>::default(),
 			VerifyActionPlugin::<ObjectBirNet>::default(),
 			NetToLocalPlugin::<StrokeNet, Stroke>::default(),
 			NetToLocalPlugin::<FillNet, Fill>::default(),
@@ -39,20 +40,40 @@ impl Plugin for ProtocolPlugin {
 			.add_map_entities();
 
 		app.register_message::<MessageCtx>(Bidirectional);
+		app.register_message::<ModerateNet>(Bidirectional);
+		app.register_message::<JoinManifest>(ServerToClient);
+		app.register_message::<RequestJoinState>(ClientToServer);
+		app.register_message::<JoinState>(ServerToClient)
+			.add_map_entities();
 		app.register_message::<MarkerType>(Bidirectional);
 		app.register_message::<ImageNetwork>(ClientToServer);
+		app.register_message::<ImageTransformNet>(ClientToServer);
+		app.register_message::<CropImageNet>(ClientToServer);
+		app.register_message::<NewShapeNet>(ClientToServer);
+		app.register_message::<CurveEditNet>(ClientToServer)
+			.add_map_entities();
+		app.register_message::<StrokeEditNet>(ClientToServer)
+			.add_map_entities();
+		app.register_message::<PixelSelectNet>(ClientToServer)
+			.add_map_entities();
 		app.register_message::<ObjectActionToServer>(ClientToServer)
 			.add_map_entities();
 		app.register_message::<PerActionNet>(ClientToServer)
 			.add_map_entities();
+		app.register_message::<RenameObjectNet>(ClientToServer)
+			.add_map_entities();
 
 		app.register_message::<RequestImageData>(ClientToServer)
 			.add_map_entities();
 		app.register_message::<ApplyChange>(Bidirectional)
 			.add_map_entities();
 		app.register_message::<MovedPoint>(ClientToServer);
+		app.register_message::<SavedRdio>(ClientToServer);
+		app.register_message::<ImportRdio>(ClientToServer);
 		app.register_message::<ReceiveImageData>(ServerToClient)
 			.add_map_entities();
+		app.register_message::<ReceiveImagePatch>(ServerToClient)
+			.add_map_entities();
 
 		app.add_channel::<MainChannel>(ChannelSettings {
 			mode: ChannelMode::OrderedReliable(ReliableSettings::default()),
@@ -72,6 +93,8 @@ impl Plugin for ProtocolPlugin {
 		// Global stat
 		app.register_component::<ObjectOpacity>(Bidirectional)
 			.add_prediction(Full);
+		app.register_component::<ObjectBlend>(Bidirectional)
+			.add_prediction(Full);
 		app.register_component::<SharingName>(Bidirectional)
 			.add_prediction(Full);
 
@@ -98,6 +121,11 @@ impl Plugin for ProtocolPlugin {
 			.add_prediction(Full);
 		app.register_component::<BlurScale>(Bidirectional)
 			.add_prediction(Full);
+		app.register_component::<BucketSettings>(Bidirectional)
+			.add_prediction(Full);
+		app.register_component::<PixelMask>(Bidirectional)
+			.add_prediction(Full)
+			.add_map_entities();
 
 		app.register_component::<SelectedObject>(Bidirectional)
 			.add_prediction(Full)
@@ -130,11 +158,21 @@ impl Plugin for ProtocolPlugin {
 			.add_prediction(Once);
 		app.register_component::<PointType>(ServerToClient)
 			.add_prediction(Full);
+		app.register_component::<NodeMode>(ServerToClient)
+			.add_prediction(Full);
 		app.register_component::<PathClose>(ServerToClient)
 			.add_prediction(Simple);
+		app.register_component::<StrokeDash>(ServerToClient)
+			.add_prediction(Full);
 		// Text
 		app.register_component::<TextValue>(ServerToClient)
 			.add_prediction(Full);
+		// Group
+		app.register_component::<ObjectGroup>(ServerToClient)
+			.add_prediction(Once);
+		app.register_component::<InGroup>(ServerToClient)
+			.add_prediction(Full)
+			.add_map_entities();
 
 		// Expermental
 		app.register_component::<ResizePinPoint>(Bidirectional)
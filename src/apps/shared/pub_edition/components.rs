@@ -1,7 +1,10 @@
 use std::{collections::HashSet, path::PathBuf};
 
 use bevy::{
-	ecs::component::{ComponentHooks, StorageType},
+	ecs::{
+		component::{ComponentHooks, StorageType},
+		entity::MapEntities,
+	},
 	prelude::*,
 };
 use bevy_mod_picking::prelude::*;
@@ -9,7 +12,15 @@ use i_cant_believe_its_not_bsn::Maybe;
 use lightyear::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use crate::{apps::shared::MainChannel, trait_bevy::ToolPath};
+use crate::{
+	apps::shared::{
+		prelude::{DrawType, BEGIN_OBJ_Z_INDEX},
+		MainChannel,
+	},
+	camera_control::lib::CAMERA_VIEW_RANGE,
+	tool_tip::lib::ToolName,
+	trait_bevy::ToolPath,
+};
 
 use super::ObjectActionToServer;
 
@@ -44,6 +55,7 @@ pub struct ObjectAccess(pub NetworkTarget);
 pub trait EditPermission {
 	fn add_id(&mut self, ref_client_id: ClientId);
 	fn remove_id(&mut self, ref_client_id: &ClientId);
+	fn replace_id(&mut self, old_client_id: &ClientId, new_client_id: ClientId);
 }
 
 impl EditPermission for NetworkTarget {
@@ -81,11 +93,37 @@ impl EditPermission for NetworkTarget {
 			NetworkTarget::Single(_client_id) => *self = NetworkTarget::None,
 		}
 	}
+	fn replace_id(&mut self, old_client_id: &ClientId, new_client_id: ClientId) {
+		match self {
+			NetworkTarget::AllExcept(vec) | NetworkTarget::Only(vec) => {
+				vec.iter_mut()
+					.filter(|client_id| *client_id == old_client_id)
+					.for_each(|client_id| *client_id = new_client_id);
+			},
+			NetworkTarget::AllExceptSingle(client_id) | NetworkTarget::Single(client_id) => {
+				if client_id == old_client_id {
+					*client_id = new_client_id;
+				}
+			},
+			NetworkTarget::None | NetworkTarget::All => {},
+		}
+	}
 }
 
 #[derive(Component, Clone, Default, Serialize, Deserialize, PartialEq, PartialOrd, Eq, Ord)]
 pub struct ObjectZLayer(pub i16);
 
+/// Objects in order with a z of their own. Any later object share the last z, so
+/// order among them is lost on screen and in export
+pub const MAX_Z_OBJECTS: usize = (CAMERA_VIEW_RANGE - 1000.0 - BEGIN_OBJ_Z_INDEX) as usize;
+
+impl ObjectZLayer {
+	/// Z of the `n`th object in order, clamped at [MAX_Z_OBJECTS] to stay in camera
+	pub fn at(n: usize) -> Self {
+		Self(BEGIN_OBJ_Z_INDEX as i16 + n.min(MAX_Z_OBJECTS) as i16)
+	}
+}
+
 #[derive(
 	Component, Reflect, Clone, Default, Deref, DerefMut, Serialize, Deserialize, PartialEq,
 )]
@@ -98,17 +136,23 @@ impl Component for ObjectWorld {
 	const STORAGE_TYPE: StorageType = StorageType::Table;
 	fn register_component_hooks(_hooks: &mut ComponentHooks) {
 		_hooks.on_add(|mut world, entity, _component_id| {
-			world.commands().entity(entity).insert(PickableBundle {
-				pickable: Pickable {
-					should_block_lower: false,
+			world.commands().entity(entity).insert((
+				PickableBundle {
+					pickable: Pickable {
+						should_block_lower: false,
+						..default()
+					},
 					..default()
 				},
-				..default()
-			});
+				ShownOpacity::default(),
+			));
 		});
 	}
 }
 
+/// Longest object name in characters
+pub const MAX_NAME_LEN: usize = 64;
+
 #[derive(Component, Clone, Deref, Serialize, Deserialize, PartialEq)]
 pub struct SharingName(pub String);
 
@@ -130,6 +174,7 @@ pub struct NetObjectBundle {
 	name: SharingName,
 	move_lock: MoveLock,
 	opacity: ObjectOpacity,
+	blend: ObjectBlend,
 	owner: Maybe<(ObjectOwner, ObjectAccess)>,
 }
 
@@ -152,6 +197,54 @@ impl Default for ObjectOpacity {
 	}
 }
 
+/// Opacity multiplied by every group holding the object, used by display and export
+#[derive(Component, Clone, Copy, Deref, PartialEq)]
+pub struct ShownOpacity(pub i8);
+
+impl Default for ShownOpacity {
+	fn default() -> Self {
+		Self(100)
+	}
+}
+
+/// Folder of objects, sit right below its members in z order
+#[derive(Component, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ObjectGroup;
+
+/// Group holding this object, group can sit in other group
+#[derive(Component, Clone, Copy, Deref, Serialize, Deserialize, PartialEq)]
+pub struct InGroup(pub Entity);
+
+impl MapEntities for InGroup {
+	fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+		self.0 = entity_mapper.map_entity(self.0);
+	}
+}
+
+/// Client only, members hidden from layers and directory
+#[derive(Component, Default)]
+pub struct GroupFolded;
+
+#[derive(Bundle, Default)]
+pub struct ObjectGroupBundle {
+	object: NetObjectBundle,
+	mark: ObjectGroup,
+	pos_z: ObjectZLayer,
+}
+
+impl ObjectGroupBundle {
+	pub fn new(name: &str, owner: ClientId) -> Self {
+		Self {
+			object: NetObjectBundle::new(name, owner),
+			..default()
+		}
+	}
+}
+
+/// How the object mix with everything below it
+#[derive(Component, Clone, Copy, Default, Deref, PartialEq, Deserialize, Serialize)]
+pub struct ObjectBlend(pub DrawType);
+
 #[allow(clippy::enum_variant_names)]
 #[derive(Event, Reflect, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum ObjectActionNet {
@@ -162,6 +255,17 @@ pub enum ObjectActionNet {
 	LayerUp,
 	#[reflect(@ToolPath("go-down.png"))]
 	LayerDown,
+	#[reflect(@ToolName("Bring to Front"))]
+	LayerTop,
+	#[reflect(@ToolName("Send to Back"))]
+	LayerBottom,
+	/// Move right above other object, join its group
+	LayerAbove(Entity),
+	/// Move right below other object, join its group
+	LayerBelow(Entity),
+	/// Set opacity in percent
+	Opacity(i8),
+	Blend(DrawType),
 }
 
 #[derive(Event, Reflect, Clone, Copy, Serialize, Deserialize)]
@@ -42,6 +42,16 @@ pub struct ObjectActionToServer {
 	pub action: ObjectActionNet,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RenameObjectNet {
+	pub obj_ent: Entity,
+	pub name: String,
+}
+
+/// Rename main selected object, typed as `>rename` in chat
+#[derive(Event, Clone)]
+pub struct RenameSelected(pub String);
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct PerActionNet {
 	pub obj_ent: Entity,
@@ -68,6 +78,17 @@ impl<T: Clone> MapEntities for ToClientEntDataEvent<T> {
 }
 
 impl MapEntities for ObjectActionToServer {
+	fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+		self.obj_ent = entity_mapper.map_entity(self.obj_ent);
+		if let ObjectActionNet::LayerAbove(ent_other) | ObjectActionNet::LayerBelow(ent_other) =
+			&mut self.action
+		{
+			*ent_other = entity_mapper.map_entity(*ent_other);
+		}
+	}
+}
+
+impl MapEntities for RenameObjectNet {
 	fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
 		self.obj_ent = entity_mapper.map_entity(self.obj_ent);
 	}
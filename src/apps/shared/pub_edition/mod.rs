@@ -5,8 +5,10 @@ use client::Predicted;
 pub use components::*;
 pub use events::*;
 
-use bevy::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_prototype_lyon::draw::{Fill, Stroke};
 use lightyear::prelude::MainSet;
+use std::collections::HashMap;
 
 use super::*;
 
@@ -16,7 +18,10 @@ impl Plugin for PublicPlugin {
 		app.add_event::<ObjectActionNet>()
 			.add_systems(
 				Update,
-				(opacity_obj, insert_view_object.after(MainSet::Receive)),
+				(
+					(inherit_opacity, opacity_obj).chain(),
+					insert_view_object.after(MainSet::Receive),
+				),
 			)
 			.add_systems(
 				PostUpdate,
@@ -51,6 +56,7 @@ fn insert_view_object(
 				Added<ProcessImage>,
 				Added<ObjectPoint>,
 				Added<ObjectPath>,
+				Added<ObjectGroup>,
 			)>,
 		),
 	>,
@@ -85,15 +91,152 @@ fn object_position_to_transform(
 		});
 }
 
-fn opacity_obj(
-	mut query_obj: Query<
-		(&ObjectOpacity, Option<&mut Sprite>),
-		(With<ObjectWorld>, Changed<ObjectOpacity>),
+/// Group opacity multiply into everything inside it
+fn inherit_opacity(
+	mut query_obj: Query<(Entity, &ObjectOpacity, &mut ShownOpacity), With<ObjectWorld>>,
+	query_changed: Query<
+		(),
+		Or<(
+			Changed<ObjectOpacity>,
+			Changed<InGroup>,
+			Added<ShownOpacity>,
+		)>,
 	>,
+	mut removed: RemovedComponents<InGroup>,
+	group_param: GroupParam,
 ) {
-	query_obj.iter_mut().for_each(|(opacity, op_sprite)| {
-		if let Some(mut sprite) = op_sprite {
-			sprite.color.set_alpha(opacity.0 as f32 / 100.0);
+	if query_changed.is_empty() && removed.read().count() == 0 {
+		return;
+	}
+	let shown = query_obj
+		.iter()
+		.map(|(ent_obj, opacity, _)| {
+			let value = group_param
+				.ancestors(ent_obj)
+				.into_iter()
+				.filter_map(|ent_group| query_obj.get(ent_group).ok())
+				.fold(opacity.0 as i32, |value, (_, group_opacity, _)| {
+					value * group_opacity.0 as i32 / 100
+				});
+			(ent_obj, value as i8)
+		})
+		.collect::<Vec<_>>();
+	shown.into_iter().for_each(|(ent_obj, value)| {
+		if let Ok((_, _, mut shown_opacity)) = query_obj.get_mut(ent_obj) {
+			shown_opacity.set_if_neq(ShownOpacity(value));
 		}
 	});
 }
+
+/// Group tree from [InGroup], missing group count as no group
+#[derive(SystemParam)]
+pub struct GroupParam<'w, 's> {
+	pub query_in_group: Query<'w, 's, (Entity, &'static InGroup)>,
+	pub query_group: Query<'w, 's, (), With<ObjectGroup>>,
+}
+
+impl<'w, 's> GroupParam<'w, 's> {
+	pub fn parent(&self, ent: Entity) -> Option<Entity> {
+		let (_, in_group) = self.query_in_group.get(ent).ok()?;
+		self.query_group.contains(in_group.0).then_some(in_group.0)
+	}
+	/// Every valid object to group pair, for use while world is borrowed
+	pub fn parents(&self) -> HashMap<Entity, Entity> {
+		self.query_in_group
+			.iter()
+			.filter_map(|(ent, _)| Some((ent, self.parent(ent)?)))
+			.collect()
+	}
+	/// Nearest group first
+	pub fn ancestors(&self, ent: Entity) -> Vec<Entity> {
+		let mut ancestors = Vec::new();
+		let mut current = self.parent(ent);
+		while let Some(ent_group) = current {
+			if ancestors.contains(&ent_group) {
+				break;
+			}
+			ancestors.push(ent_group);
+			current = self.parent(ent_group);
+		}
+		ancestors
+	}
+	pub fn is_inside(&self, ent: Entity, ent_group: Entity) -> bool {
+		self.ancestors(ent).contains(&ent_group)
+	}
+	/// Everything inside group at any depth, outer first
+	pub fn descendants(&self, ent_group: Entity) -> Vec<Entity> {
+		let mut inside = self
+			.query_in_group
+			.iter()
+			.filter_map(|(ent, _)| {
+				let depth = self
+					.ancestors(ent)
+					.iter()
+					.position(|ent| ent == &ent_group)?;
+				Some((depth, ent))
+			})
+			.collect::<Vec<_>>();
+		inside.sort_by_key(|(depth, _)| *depth);
+		inside.into_iter().map(|(_, ent)| ent).collect()
+	}
+	/// Selection with groups opened into what inside, group stay before its members
+	pub fn expand(&self, selected: impl Iterator<Item = Entity>) -> Vec<Entity> {
+		let mut expanded = Vec::new();
+		selected.for_each(|ent| {
+			std::iter::once(ent)
+				.chain(self.descendants(ent))
+				.for_each(|ent| {
+					if !expanded.contains(&ent) {
+						expanded.push(ent);
+					}
+				});
+		});
+		expanded
+	}
+}
+
+/// Blended image already hold its opacity in displayed pixels
+fn opacity_obj(
+	mut query_obj: Query<
+		(
+			&ShownOpacity,
+			&ObjectBlend,
+			Has<ProcessImage>,
+			Option<&mut Sprite>,
+			Option<(&StrokeNet, &FillNet, &mut Stroke, &mut Fill)>,
+			Option<&mut Text>,
+		),
+		(
+			With<ObjectWorld>,
+			Or<(
+				Changed<ShownOpacity>,
+				Changed<ObjectBlend>,
+				Changed<StrokeNet>,
+				Changed<FillNet>,
+				Added<Stroke>,
+				Added<Text>,
+			)>,
+		),
+	>,
+) {
+	query_obj
+		.iter_mut()
+		.for_each(|(opacity, blend, is_image, op_sprite, op_path, op_text)| {
+			let alpha = opacity.0 as f32 / 100.0;
+			if let Some(mut sprite) = op_sprite {
+				let is_baked = is_image && blend.0 != DrawType::Normal;
+				sprite.color.set_alpha(if is_baked { 1.0 } else { alpha });
+			}
+			if let Some((stroke_net, fill_net, mut stroke, mut fill)) = op_path {
+				stroke.color = stroke_net
+					.color
+					.with_alpha(stroke_net.color.alpha() * alpha);
+				fill.color = fill_net.color.with_alpha(fill_net.color.alpha() * alpha);
+			}
+			if let Some(mut text) = op_text {
+				text.sections
+					.iter_mut()
+					.for_each(|section| section.style.color.set_alpha(alpha));
+			}
+		});
+}
@@ -0,0 +1,850 @@
+use std::collections::HashSet;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use lightyear::prelude::ClientId;
+use lz4_flex::block::{compress_prepend_size, decompress_size_prepended, DecompressError};
+use serde::{Deserialize, Serialize};
+
+use super::prelude::*;
+
+pub const RDIO_EXTENSION: &str = "rd";
+/// Increase when [SavedRdio] layout change, older file stay readable
+pub const RDIO_VERSION: u16 = 5;
+const RDIO_MAGIC: &[u8; 4] = b"RDIO";
+/// Objects and path points one opened document may hold
+pub const MAX_OPEN_OBJECTS: usize = 100_000;
+/// Total image pixels one opened document may hold
+pub const MAX_OPEN_PIXELS: u64 = 256 * 1024 * 1024;
+
+/// Whole document, objects ordered from bottom to top layer
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct SavedRdio {
+	pub objects: Vec<SavedObject>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SavedObject {
+	pub name: String,
+	pub opacity: ObjectOpacity,
+	pub blend: ObjectBlend,
+	/// Lock given to whoever spawn the document, session ids are never saved
+	pub move_lock: bool,
+	pub kind: SavedKind,
+}
+
+/// Objects added on top of the board, any user may send it unlike opening [SavedRdio]
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ImportRdio(pub SavedRdio);
+
+/// Version 1 layout, before [ObjectBlend]
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+struct SavedRdioV1 {
+	objects: Vec<SavedObjectV1>,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+struct SavedObjectV1 {
+	name: String,
+	opacity: ObjectOpacity,
+	move_lock: HashSet<ClientId>,
+	kind: SavedKindV3,
+}
+
+/// Version 3 layout, before [StrokeDash]
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+struct SavedRdioV3 {
+	objects: Vec<SavedObjectV3>,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+struct SavedObjectV3 {
+	name: String,
+	opacity: ObjectOpacity,
+	blend: ObjectBlend,
+	move_lock: HashSet<ClientId>,
+	kind: SavedKindV3,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+enum SavedKindV3 {
+	Image {
+		position: Vec2,
+		size: UVec2,
+		data: DataHold,
+		pixel_lock: HashSet<ClientId>,
+		alpha_lock: HashSet<ClientId>,
+	},
+	Path {
+		stroke: StrokeNet,
+		fill: FillNet,
+		close: bool,
+		points: Vec<(Vec2, PointType)>,
+	},
+	Text {
+		position: Vec2,
+		value: String,
+	},
+	Group {
+		len: usize,
+	},
+}
+
+/// Version 4 layout, locks saved as session client ids
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+struct SavedRdioV4 {
+	objects: Vec<SavedObjectV4>,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+struct SavedObjectV4 {
+	name: String,
+	opacity: ObjectOpacity,
+	blend: ObjectBlend,
+	move_lock: HashSet<ClientId>,
+	kind: SavedKindV4,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+enum SavedKindV4 {
+	Image {
+		position: Vec2,
+		size: UVec2,
+		data: DataHold,
+		pixel_lock: HashSet<ClientId>,
+		alpha_lock: HashSet<ClientId>,
+	},
+	Path {
+		stroke: StrokeNet,
+		dash: StrokeDash,
+		fill: FillNet,
+		close: bool,
+		points: Vec<(Vec2, PointType)>,
+	},
+	Text {
+		position: Vec2,
+		value: String,
+	},
+	Group {
+		len: usize,
+	},
+}
+
+impl From<SavedKindV3> for SavedKindV4 {
+	fn from(kind: SavedKindV3) -> Self {
+		match kind {
+			SavedKindV3::Image {
+				position,
+				size,
+				data,
+				pixel_lock,
+				alpha_lock,
+			} => SavedKindV4::Image {
+				position,
+				size,
+				data,
+				pixel_lock,
+				alpha_lock,
+			},
+			SavedKindV3::Path {
+				stroke,
+				fill,
+				close,
+				points,
+			} => SavedKindV4::Path {
+				stroke,
+				dash: StrokeDash::default(),
+				fill,
+				close,
+				points,
+			},
+			SavedKindV3::Text { position, value } => SavedKindV4::Text { position, value },
+			SavedKindV3::Group { len } => SavedKindV4::Group { len },
+		}
+	}
+}
+
+impl From<SavedKindV4> for SavedKind {
+	fn from(kind: SavedKindV4) -> Self {
+		match kind {
+			SavedKindV4::Image {
+				position,
+				size,
+				data,
+				pixel_lock,
+				alpha_lock,
+			} => SavedKind::Image {
+				position,
+				size,
+				data,
+				pixel_lock: !pixel_lock.is_empty(),
+				alpha_lock: !alpha_lock.is_empty(),
+			},
+			SavedKindV4::Path {
+				stroke,
+				dash,
+				fill,
+				close,
+				points,
+			} => SavedKind::Path {
+				stroke,
+				dash,
+				fill,
+				close,
+				points,
+			},
+			SavedKindV4::Text { position, value } => SavedKind::Text { position, value },
+			SavedKindV4::Group { len } => SavedKind::Group { len },
+		}
+	}
+}
+
+impl From<SavedRdioV4> for SavedRdio {
+	fn from(saved: SavedRdioV4) -> Self {
+		let objects = saved
+			.objects
+			.into_iter()
+			.map(|saved_obj| SavedObject {
+				name: saved_obj.name,
+				opacity: saved_obj.opacity,
+				blend: saved_obj.blend,
+				move_lock: !saved_obj.move_lock.is_empty(),
+				kind: saved_obj.kind.into(),
+			})
+			.collect();
+		SavedRdio { objects }
+	}
+}
+
+impl From<SavedRdioV3> for SavedRdioV4 {
+	fn from(saved: SavedRdioV3) -> Self {
+		let objects = saved
+			.objects
+			.into_iter()
+			.map(|saved_obj| SavedObjectV4 {
+				name: saved_obj.name,
+				opacity: saved_obj.opacity,
+				blend: saved_obj.blend,
+				move_lock: saved_obj.move_lock,
+				kind: saved_obj.kind.into(),
+			})
+			.collect();
+		SavedRdioV4 { objects }
+	}
+}
+
+impl From<SavedRdioV1> for SavedRdioV4 {
+	fn from(saved: SavedRdioV1) -> Self {
+		let objects = saved
+			.objects
+			.into_iter()
+			.map(|saved_obj| SavedObjectV4 {
+				name: saved_obj.name,
+				opacity: saved_obj.opacity,
+				blend: ObjectBlend::default(),
+				move_lock: saved_obj.move_lock,
+				kind: saved_obj.kind.into(),
+			})
+			.collect();
+		SavedRdioV4 { objects }
+	}
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum SavedKind {
+	Image {
+		position: Vec2,
+		size: UVec2,
+		data: DataHold,
+		pixel_lock: bool,
+		alpha_lock: bool,
+	},
+	/// Points in [PointToPoint] order
+	Path {
+		stroke: StrokeNet,
+		dash: StrokeDash,
+		fill: FillNet,
+		close: bool,
+		points: Vec<(Vec2, PointType)>,
+	},
+	Text {
+		position: Vec2,
+		value: String,
+	},
+	/// Next `len` saved objects sit inside it
+	Group {
+		len: usize,
+	},
+}
+
+#[derive(Debug)]
+pub enum RdioFileError {
+	NotRdio,
+	NewerVersion(u16),
+	Decompress(DecompressError),
+	Encoding(bincode::Error),
+}
+
+impl SavedRdio {
+	/// Magic | Version (LE) | LZ4 Bincode
+	pub fn to_bytes(&self) -> Result<Vec<u8>, RdioFileError> {
+		let encoded = bincode::serialize(self).map_err(RdioFileError::Encoding)?;
+		let mut bytes = RDIO_MAGIC.to_vec();
+		bytes.extend(RDIO_VERSION.to_le_bytes());
+		bytes.extend(compress_prepend_size(&encoded));
+		Ok(bytes)
+	}
+	pub fn from_bytes(bytes: &[u8]) -> Result<Self, RdioFileError> {
+		let Some((magic, rest)) = bytes.split_first_chunk::<4>() else {
+			return Err(RdioFileError::NotRdio);
+		};
+		if magic != RDIO_MAGIC {
+			return Err(RdioFileError::NotRdio);
+		}
+		let Some((version, body)) = rest.split_first_chunk::<2>() else {
+			return Err(RdioFileError::NotRdio);
+		};
+		let version = u16::from_le_bytes(*version);
+		if version > RDIO_VERSION {
+			return Err(RdioFileError::NewerVersion(version));
+		}
+		let decoded = decompress_size_prepended(body).map_err(RdioFileError::Decompress)?;
+		if version < 2 {
+			return bincode::deserialize::<SavedRdioV1>(&decoded)
+				.map(|saved| SavedRdioV4::from(saved).into())
+				.map_err(RdioFileError::Encoding);
+		}
+		if version < 4 {
+			return bincode::deserialize::<SavedRdioV3>(&decoded)
+				.map(|saved| SavedRdioV4::from(saved).into())
+				.map_err(RdioFileError::Encoding);
+		}
+		if version < 5 {
+			return bincode::deserialize::<SavedRdioV4>(&decoded)
+				.map(SavedRdio::from)
+				.map_err(RdioFileError::Encoding);
+		}
+		bincode::deserialize(&decoded).map_err(RdioFileError::Encoding)
+	}
+	/// Reject document too large to open on the board
+	pub fn within_limit(&self) -> bool {
+		let mut count = 0;
+		let mut pixels = 0;
+		self.objects.iter().for_each(|saved_obj| {
+			count += 1;
+			match &saved_obj.kind {
+				SavedKind::Image { size, .. } => pixels += size.as_u64vec2().element_product(),
+				SavedKind::Path { points, .. } => count += points.len(),
+				_ => {},
+			}
+		});
+		count <= MAX_OPEN_OBJECTS && pixels <= MAX_OPEN_PIXELS
+	}
+	/// Compress image left raw by [RdioParam::collect_raw], heavy so run off main thread
+	pub fn compress_images(&mut self) {
+		self.objects.iter_mut().for_each(|saved_obj| {
+			if let SavedKind::Image { data, .. } = &mut saved_obj.kind {
+				data.compress();
+			}
+		});
+	}
+	/// Server only, spawn in saved order so [ObjectZLayer] stay the same, objects returned in it
+	pub fn spawn_objects(self, cmd: &mut Commands, owner: ClientId) -> Vec<Entity> {
+		// Open groups with how many objects left inside
+		let mut groups: Vec<(Entity, usize)> = Vec::new();
+		let mut spawned = Vec::new();
+		self.objects.into_iter().for_each(|saved_obj| {
+			let len = match saved_obj.kind {
+				SavedKind::Group { len } => len,
+				_ => 0,
+			};
+			let op_group = groups.last().map(|(ent_group, _)| *ent_group);
+			groups.iter_mut().for_each(|(_, left)| *left -= 1);
+			groups.retain(|(_, left)| *left > 0);
+			let Some((ent_obj, _)) = saved_obj.spawn(cmd, owner) else {
+				return;
+			};
+			if let Some(ent_group) = op_group {
+				cmd.entity(ent_obj).insert(InGroup(ent_group));
+			}
+			if len > 0 {
+				groups.push((ent_obj, len));
+			}
+			spawned.push(ent_obj);
+		});
+		spawned
+	}
+}
+
+impl SavedObject {
+	/// Server only, return object entity and its points in saved order
+	pub fn spawn(self, cmd: &mut Commands, owner: ClientId) -> Option<(Entity, Vec<Entity>)> {
+		let SavedObject {
+			name,
+			opacity,
+			blend,
+			move_lock,
+			kind,
+		} = self;
+		let common = (opacity, blend, MoveLock(owner_lock(move_lock, owner)));
+		match kind {
+			SavedKind::Image {
+				position,
+				size,
+				data,
+				pixel_lock,
+				alpha_lock,
+			} => {
+				let Ok(buf) = data.uncompress() else {
+					warn!("Unable to uncompress image: {}", name);
+					return None;
+				};
+				if buf.len() != size.element_product() as usize * 4 {
+					warn!("Image size not match data: {}", name);
+					return None;
+				}
+				let ent_obj = cmd
+					.spawn(RdioImageBundle::new(&name, size, buf, position, owner))
+					.insert((
+						common,
+						PixelLock(owner_lock(pixel_lock, owner)),
+						AlphaLock(owner_lock(alpha_lock, owner)),
+					))
+					.id();
+				Some((ent_obj, Vec::new()))
+			},
+			SavedKind::Path {
+				stroke,
+				dash,
+				fill,
+				close,
+				points,
+			} => {
+				// Document may come from any client
+				let dash = match dash.is_valid() {
+					true => dash,
+					false => StrokeDash::default(),
+				};
+				let ent_points = points
+					.into_iter()
+					.map(|(position, point_type)| {
+						cmd.spawn(PointBundle::new(position))
+							.insert(point_type)
+							.id()
+					})
+					.collect::<Vec<_>>();
+				let ent_obj = cmd
+					.spawn(RdioPathBundle::new(&name, owner))
+					.insert((common, stroke, dash, fill, PathClose(close)))
+					.push_children(&ent_points)
+					.id();
+				ent_points.windows(2).for_each(|pair| {
+					cmd.trigger(ConnectRelations::<PointToPoint>::new(pair[0], pair[1]));
+				});
+				Some((ent_obj, ent_points))
+			},
+			SavedKind::Text { position, value } => {
+				let ent_obj = cmd
+					.spawn(WorldTextBundle::new(&name, value, position, owner))
+					.insert(common)
+					.id();
+				Some((ent_obj, Vec::new()))
+			},
+			SavedKind::Group { .. } => {
+				let ent_obj = cmd
+					.spawn(ObjectGroupBundle::new(&name, owner))
+					.insert(common)
+					.id();
+				Some((ent_obj, Vec::new()))
+			},
+		}
+	}
+}
+
+/// Saved lock is on for spawning owner only
+fn owner_lock(is_locked: bool, owner: ClientId) -> HashSet<ClientId> {
+	is_locked.then_some(owner).into_iter().collect()
+}
+
+#[derive(SystemParam)]
+pub struct RdioParam<'w, 's> {
+	query_object: Query<
+		'w,
+		's,
+		(
+			Entity,
+			&'static SharingName,
+			&'static ObjectZLayer,
+			&'static ObjectOpacity,
+			&'static ObjectBlend,
+			&'static MoveLock,
+			Option<&'static ObjectPosition>,
+			Option<(
+				&'static ProcessImage,
+				&'static PixelLock,
+				&'static AlphaLock,
+			)>,
+			Option<&'static TextValue>,
+			Has<ObjectPath>,
+			Has<ObjectGroup>,
+		),
+		(With<ObjectWorld>, Without<ObjectPoint>),
+	>,
+	path_param: PathParam<'w, 's>,
+	group_param: GroupParam<'w, 's>,
+}
+
+impl<'w, 's> RdioParam<'w, 's> {
+	/// Image still waiting for [ProcessImage] data are skipped
+	pub fn collect(&self) -> SavedRdio {
+		self.collect_as(true)
+	}
+	/// Same as [RdioParam::collect] but image left uncompressed, see [SavedRdio::compress_images]
+	pub fn collect_raw(&self) -> SavedRdio {
+		self.collect_as(false)
+	}
+	fn collect_as(&self, compress: bool) -> SavedRdio {
+		let saved = self
+			.query_object
+			.iter()
+			.sort::<&ObjectZLayer>()
+			.filter_map(|(ent_obj, ..)| Some((ent_obj, self.save_object_as(ent_obj, compress)?)))
+			.collect::<Vec<_>>();
+		// Group only count members actually saved
+		let ents = saved
+			.iter()
+			.map(|(ent_obj, _)| *ent_obj)
+			.collect::<Vec<_>>();
+		let objects = saved
+			.into_iter()
+			.enumerate()
+			.map(|(n, (ent_obj, mut saved_obj))| {
+				if let SavedKind::Group { len } = &mut saved_obj.kind {
+					*len = ents[n + 1..]
+						.iter()
+						.take_while(|ent| self.group_param.is_inside(**ent, ent_obj))
+						.count();
+				}
+				saved_obj
+			})
+			.collect();
+		SavedRdio { objects }
+	}
+	pub fn save_object(&self, ent_obj: Entity) -> Option<SavedObject> {
+		self.save_object_as(ent_obj, true)
+	}
+	fn save_object_as(&self, ent_obj: Entity, compress: bool) -> Option<SavedObject> {
+		let (_, name, _, opacity, blend, move_lock, op_pos, op_img, op_text, is_path, is_group) =
+			self.query_object.get(ent_obj).ok()?;
+		let kind = if let Some((process_img, pixel_lock, alpha_lock)) = op_img {
+			SavedKind::Image {
+				position: op_pos?.0,
+				size: process_img.dimensions().into(),
+				data: match compress {
+					true => DataHold::to_compress(process_img.as_raw()),
+					false => DataHold::Uncompress(process_img.as_raw().clone()),
+				},
+				pixel_lock: !pixel_lock.is_empty(),
+				alpha_lock: !alpha_lock.is_empty(),
+			}
+		} else if let Some(text) = op_text {
+			SavedKind::Text {
+				position: op_pos?.0,
+				value: text.0.clone(),
+			}
+		} else if is_path {
+			let (_, stroke, fill, close) = self.path_param.query_path.get(ent_obj).ok()?;
+			SavedKind::Path {
+				stroke: stroke.clone(),
+				dash: self
+					.path_param
+					.query_dash
+					.get(ent_obj)
+					.cloned()
+					.unwrap_or_default(),
+				fill: fill.clone(),
+				close: close.0,
+				points: self.path_param.points(ent_obj)?,
+			}
+		} else if is_group {
+			SavedKind::Group {
+				len: self.group_param.descendants(ent_obj).len(),
+			}
+		} else {
+			return None;
+		};
+		Some(SavedObject {
+			name: name.0.clone(),
+			opacity: opacity.clone(),
+			blend: *blend,
+			move_lock: !move_lock.is_empty(),
+			kind,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// File of `body` as written by `version`
+	fn file_bytes(version: u16, body: &impl Serialize) -> Vec<u8> {
+		let mut bytes = RDIO_MAGIC.to_vec();
+		bytes.extend(version.to_le_bytes());
+		bytes.extend(compress_prepend_size(&bincode::serialize(body).unwrap()));
+		bytes
+	}
+
+	fn session_ids() -> HashSet<ClientId> {
+		HashSet::from([ClientId::Netcode(7), ClientId::Netcode(42)])
+	}
+
+	fn points() -> Vec<(Vec2, PointType)> {
+		vec![
+			(Vec2::ZERO, PointType::LineTo),
+			(
+				Vec2::new(4.0, 2.0),
+				PointType::QuadraticBezier { ctrl: Vec2::ONE },
+			),
+		]
+	}
+
+	fn path_v3() -> SavedKindV3 {
+		SavedKindV3::Path {
+			stroke: StrokeNet::default(),
+			fill: FillNet::default(),
+			close: true,
+			points: points(),
+		}
+	}
+
+	fn assert_solid_path(kind: &SavedKind) {
+		let SavedKind::Path {
+			dash,
+			close,
+			points: saved_points,
+			..
+		} = kind
+		else {
+			panic!("Path expected");
+		};
+		assert!(*dash == StrokeDash::default());
+		assert!(*close);
+		assert!(*saved_points == points());
+	}
+
+	#[test]
+	fn decode_version_1() {
+		let saved = SavedRdioV1 {
+			objects: vec![SavedObjectV1 {
+				name: "Path".to_owned(),
+				opacity: ObjectOpacity(50, 100),
+				move_lock: session_ids(),
+				kind: path_v3(),
+			}],
+		};
+		let decoded = SavedRdio::from_bytes(&file_bytes(1, &saved)).unwrap();
+		assert_eq!(decoded.objects.len(), 1);
+		let saved_obj = &decoded.objects[0];
+		assert_eq!(saved_obj.name, "Path");
+		assert!(saved_obj.opacity == ObjectOpacity(50, 100));
+		assert!(saved_obj.blend == ObjectBlend::default());
+		assert!(saved_obj.move_lock);
+		assert_solid_path(&saved_obj.kind);
+	}
+
+	#[test]
+	fn decode_version_2_and_3() {
+		let saved = SavedRdioV3 {
+			objects: vec![
+				SavedObjectV3 {
+					name: "Group".to_owned(),
+					opacity: ObjectOpacity::default(),
+					blend: ObjectBlend::default(),
+					move_lock: HashSet::new(),
+					kind: SavedKindV3::Group { len: 2 },
+				},
+				SavedObjectV3 {
+					name: "Path".to_owned(),
+					opacity: ObjectOpacity::default(),
+					blend: ObjectBlend(DrawType::Multiply),
+					move_lock: HashSet::new(),
+					kind: path_v3(),
+				},
+				SavedObjectV3 {
+					name: "Text".to_owned(),
+					opacity: ObjectOpacity::default(),
+					blend: ObjectBlend::default(),
+					move_lock: HashSet::new(),
+					kind: SavedKindV3::Text {
+						position: Vec2::new(1.0, -1.0),
+						value: "Hello".to_owned(),
+					},
+				},
+			],
+		};
+		for version in [2, 3] {
+			let decoded = SavedRdio::from_bytes(&file_bytes(version, &saved)).unwrap();
+			assert_eq!(decoded.objects.len(), 3);
+			assert!(matches!(
+				decoded.objects[0].kind,
+				SavedKind::Group { len: 2 }
+			));
+			assert!(!decoded.objects[0].move_lock);
+			assert!(decoded.objects[1].blend == ObjectBlend(DrawType::Multiply));
+			assert_solid_path(&decoded.objects[1].kind);
+			let SavedKind::Text { position, value } = &decoded.objects[2].kind else {
+				panic!("Text expected");
+			};
+			assert_eq!(*position, Vec2::new(1.0, -1.0));
+			assert_eq!(value, "Hello");
+		}
+	}
+
+	#[test]
+	fn decode_version_4_locks() {
+		let saved = SavedRdioV4 {
+			objects: vec![SavedObjectV4 {
+				name: "Image".to_owned(),
+				opacity: ObjectOpacity::default(),
+				blend: ObjectBlend::default(),
+				move_lock: HashSet::new(),
+				kind: SavedKindV4::Image {
+					position: Vec2::ZERO,
+					size: UVec2::ONE,
+					data: DataHold::Uncompress(vec![255; 4]),
+					pixel_lock: session_ids(),
+					alpha_lock: HashSet::new(),
+				},
+			}],
+		};
+		let decoded = SavedRdio::from_bytes(&file_bytes(4, &saved)).unwrap();
+		assert_eq!(decoded.objects.len(), 1);
+		assert!(!decoded.objects[0].move_lock);
+		let SavedKind::Image {
+			size,
+			pixel_lock,
+			alpha_lock,
+			..
+		} = &decoded.objects[0].kind
+		else {
+			panic!("Image expected");
+		};
+		assert_eq!(*size, UVec2::ONE);
+		assert!(*pixel_lock);
+		assert!(!*alpha_lock);
+	}
+
+	#[test]
+	fn decode_current_version() {
+		let dash = StrokeDash {
+			array: vec![10.0, 5.0],
+			offset: 2.5,
+		};
+		let saved = SavedRdio {
+			objects: vec![SavedObject {
+				name: "Path".to_owned(),
+				opacity: ObjectOpacity(80, 100),
+				blend: ObjectBlend(DrawType::Screen),
+				move_lock: true,
+				kind: SavedKind::Path {
+					stroke: StrokeNet::default(),
+					dash: dash.clone(),
+					fill: FillNet::default(),
+					close: false,
+					points: points(),
+				},
+			}],
+		};
+		let decoded = SavedRdio::from_bytes(&saved.to_bytes().unwrap()).unwrap();
+		assert_eq!(decoded.objects.len(), 1);
+		let saved_obj = &decoded.objects[0];
+		assert!(saved_obj.opacity == ObjectOpacity(80, 100));
+		assert!(saved_obj.blend == ObjectBlend(DrawType::Screen));
+		assert!(saved_obj.move_lock);
+		let SavedKind::Path {
+			dash: saved_dash,
+			close,
+			points: saved_points,
+			..
+		} = &saved_obj.kind
+		else {
+			panic!("Path expected");
+		};
+		assert!(*saved_dash == dash);
+		assert!(!*close);
+		assert!(*saved_points == points());
+	}
+
+	#[test]
+	fn refuse_other_and_newer_file() {
+		assert!(matches!(
+			SavedRdio::from_bytes(b"PNG\0\0\0"),
+			Err(RdioFileError::NotRdio)
+		));
+		let newer = file_bytes(RDIO_VERSION + 1, &SavedRdio::default());
+		assert!(matches!(
+			SavedRdio::from_bytes(&newer),
+			Err(RdioFileError::NewerVersion(version)) if version == RDIO_VERSION + 1
+		));
+	}
+
+	#[test]
+	fn limit_oversized_document() {
+		let saved_obj = |kind| SavedObject {
+			name: "Object".to_owned(),
+			opacity: ObjectOpacity::default(),
+			blend: ObjectBlend::default(),
+			move_lock: false,
+			kind,
+		};
+		let path = |len| {
+			saved_obj(SavedKind::Path {
+				stroke: StrokeNet::default(),
+				dash: StrokeDash::default(),
+				fill: FillNet::default(),
+				close: false,
+				points: vec![(Vec2::ZERO, PointType::LineTo); len],
+			})
+		};
+		let image = |size| {
+			saved_obj(SavedKind::Image {
+				position: Vec2::ZERO,
+				size,
+				data: DataHold::Uncompress(Vec::new()),
+				pixel_lock: false,
+				alpha_lock: false,
+			})
+		};
+		// Path count itself and each of its points
+		let fit = SavedRdio {
+			objects: vec![path(MAX_OPEN_OBJECTS - 1)],
+		};
+		assert!(fit.within_limit());
+		let too_many = SavedRdio {
+			objects: vec![path(MAX_OPEN_OBJECTS)],
+		};
+		assert!(!too_many.within_limit());
+		let groups = SavedRdio {
+			objects: (0..=MAX_OPEN_OBJECTS)
+				.map(|_| saved_obj(SavedKind::Group { len: 0 }))
+				.collect(),
+		};
+		assert!(!groups.within_limit());
+		let pixels = SavedRdio {
+			objects: vec![image(UVec2::new(16 * 1024, 16 * 1024)), image(UVec2::ONE)],
+		};
+		assert!(!pixels.within_limit());
+		let one_image = SavedRdio {
+			objects: vec![image(UVec2::new(16 * 1024, 16 * 1024))],
+		};
+		assert!(one_image.within_limit());
+	}
+}
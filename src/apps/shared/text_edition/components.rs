@@ -12,9 +12,47 @@ pub struct WorldTextBundle {
 	pos_z: ObjectZLayer,
 }
 
+pub const TEXT_SIZE: f32 = 30.0;
+
 #[derive(Component, Clone, Default, Debug, Serialize, Deserialize, PartialEq)]
 pub struct TextValue(pub String);
 
+impl TextValue {
+	/// `<text>` for each line in `style`, `position` is top left in world and `size`
+	/// the laid out size
+	pub fn to_svg(
+		&self,
+		position: Vec2,
+		style: &TextStyle,
+		size: Vec2,
+	) -> (svg::node::element::Group, IRect) {
+		use svg::node::element::{Group, Text};
+		let min = position * Vec2::new(1.0, -1.0);
+		let font_size = style.font_size;
+		let lines = self.0.lines().collect::<Vec<_>>();
+		let line_height = match lines.len() {
+			0 => font_size * 1.2,
+			count => size.y / count as f32,
+		};
+		// Opacity is exported with the object
+		let group = Group::new()
+			.set("font-size", font_size)
+			.set("fill", style.color.to_srgba().with_alpha(1.0).to_hex())
+			.set("xml:space", "preserve");
+		let group = lines.iter().enumerate().fold(group, |group, (n, line)| {
+			group.add(
+				Text::new(*line)
+					.set("x", min.x)
+					.set("y", min.y + font_size + line_height * n as f32),
+			)
+		});
+		(
+			group,
+			IRect::from_corners(min.floor().as_ivec2(), (min + size).ceil().as_ivec2()),
+		)
+	}
+}
+
 impl WorldTextBundle {
 	pub fn new(name: &str, value: String, position: Vec2, owner: ClientId) -> Self {
 		Self {
@@ -29,7 +29,7 @@ fn new_text(
 		text: Text::from_section(
 			value.0.clone(),
 			TextStyle {
-				font_size: 30.0,
+				font_size: TEXT_SIZE,
 				color: Color::BLACK,
 				..default()
 			},
@@ -0,0 +1,303 @@
+use std::{borrow::Cow, collections::HashMap};
+
+use crate::apps::shared::*;
+use bevy::{prelude::*, text::TextLayoutInfo, window::PrimaryWindow};
+use image::{imageops, Rgba, RgbaImage};
+use prelude::*;
+
+/// Blended image show only what it add over layers already drawn below, so nothing
+/// below show twice. Below cover image, path and text same as export, and only the
+/// area touched by changed layers is composed again
+pub(super) fn display_blended(
+	mut image_assets: ResMut<Assets<Image>>,
+	atlas_layouts: Res<Assets<TextureAtlasLayout>>,
+	query_window: Query<&Window, With<PrimaryWindow>>,
+	query_image: Query<
+		(
+			Entity,
+			&Handle<Image>,
+			&ProcessImage,
+			&ObjectPosition,
+			&ObjectZLayer,
+			&ShownOpacity,
+			Ref<ObjectBlend>,
+		),
+		With<ObjectWorld>,
+	>,
+	query_vector: Query<
+		(
+			Entity,
+			Option<(&Text, &TextLayoutInfo, &ObjectPosition)>,
+			&ObjectZLayer,
+			&ShownOpacity,
+			&ObjectBlend,
+		),
+		(With<ObjectWorld>, Or<(With<ObjectPath>, With<TextValue>)>),
+	>,
+	changed_layer: Query<
+		Entity,
+		(
+			With<ObjectWorld>,
+			Without<ObjectPoint>,
+			Or<(
+				Changed<ProcessImage>,
+				Changed<ObjectPosition>,
+				Changed<ObjectZLayer>,
+				Changed<ShownOpacity>,
+				Changed<ObjectBlend>,
+				Changed<TextLayoutInfo>,
+				Changed<PathClose>,
+				Changed<Children>,
+				Changed<StrokeNet>,
+				Changed<StrokeDash>,
+				Changed<FillNet>,
+			)>,
+		),
+	>,
+	changed_point: Query<
+		&Parent,
+		(
+			With<ObjectPoint>,
+			Or<(Changed<ObjectPosition>, Changed<PointType>)>,
+		),
+	>,
+	mut removed: RemovedComponents<ObjectWorld>,
+	path_param: PathParam,
+	mut rasters: Local<HashMap<Entity, (RgbaImage, IVec2)>>,
+	mut areas: Local<HashMap<Entity, IRect>>,
+) {
+	let mut changed = changed_layer.iter().collect::<Vec<_>>();
+	changed.extend(changed_point.iter().map(|parent| parent.get()));
+	changed.extend(removed.read());
+	if changed.is_empty() {
+		return;
+	}
+	changed.sort();
+	changed.dedup();
+	changed.iter().for_each(|ent| {
+		rasters.remove(ent);
+	});
+	let has_blended = query_image
+		.iter()
+		.any(|(.., blend)| blend.0 != DrawType::Normal || blend.is_changed());
+	if !has_blended {
+		// Areas are found again once something blend
+		changed.iter().for_each(|ent| {
+			areas.remove(ent);
+		});
+		return;
+	}
+
+	let scale_factor = query_window
+		.get_single()
+		.map_or(1.0, |window| window.scale_factor());
+	query_vector.iter().for_each(|(ent_obj, op_text, ..)| {
+		if rasters.contains_key(&ent_obj) {
+			return;
+		}
+		let op_raster = match op_text {
+			Some((text, layout, obj_pos)) => {
+				text_to_image(text, layout, &image_assets, &atlas_layouts, scale_factor)
+					.map(|img| (img, world_to_pixel(obj_pos.0)))
+			},
+			None => path_param.to_image(ent_obj).and_then(|(data, placement)| {
+				let img = RgbaImage::from_vec(placement.width, placement.height, data)?;
+				Some((img, IVec2::new(placement.left, placement.top)))
+			}),
+		};
+		if let Some(raster) = op_raster {
+			rasters.insert(ent_obj, raster);
+		}
+	});
+
+	let mut layers = query_image
+		.iter()
+		.map(|(ent_obj, _, img, pos, z_layer, opacity, blend)| {
+			(
+				ent_obj,
+				CompositeLayer {
+					img: Cow::Borrowed(&img.0),
+					pos: world_to_pixel(pos.0),
+					z: z_layer.0,
+					opacity: opacity.0,
+					blend: blend.0,
+				},
+			)
+		})
+		.collect::<Vec<_>>();
+	layers.extend(
+		query_vector
+			.iter()
+			.filter_map(|(ent_obj, _, z_layer, opacity, blend)| {
+				let (img, pos) = rasters.get(&ent_obj)?;
+				Some((
+					ent_obj,
+					CompositeLayer {
+						img: Cow::Borrowed(img),
+						pos: *pos,
+						z: z_layer.0,
+						opacity: opacity.0,
+						blend: blend.0,
+					},
+				))
+			}),
+	);
+
+	// Old and new area of every changed layer
+	let mut dirty = changed
+		.iter()
+		.filter_map(|ent| areas.remove(ent))
+		.collect::<Vec<_>>();
+	layers.iter().for_each(|(ent_obj, layer)| {
+		let size = UVec2::new(layer.img.width(), layer.img.height()).as_ivec2();
+		let rect = IRect::from_corners(layer.pos, layer.pos + size);
+		if areas.insert(*ent_obj, rect).is_none() {
+			dirty.push(rect);
+		}
+	});
+
+	query_image
+		.iter()
+		.filter(|(.., blend)| blend.0 != DrawType::Normal || blend.is_changed())
+		.for_each(
+			|(ent_obj, handle_img, process_img, pos, z_layer, opacity, blend)| {
+				let Some(detail_img) = image_assets.get_mut(handle_img) else {
+					return;
+				};
+				if blend.0 == DrawType::Normal {
+					detail_img.data = process_img.as_bytes().to_vec();
+					return;
+				}
+				let origin = world_to_pixel(pos.0);
+				let size: UVec2 = process_img.dimensions().into();
+				let rect = IRect::from_corners(origin, origin + size.as_ivec2());
+				let region = if changed.contains(&ent_obj) {
+					rect
+				} else {
+					let Some(region) = dirty
+						.iter()
+						.map(|area| area.intersect(rect))
+						.filter(|area| !area.is_empty())
+						.reduce(|region, area| region.union(area))
+					else {
+						return;
+					};
+					region
+				};
+				let below = layers
+					.iter()
+					.filter(|(_, layer)| layer.z < z_layer.0)
+					.map(|(_, layer)| layer.clone())
+					.collect();
+				let shown_below = compose_layers(below, region);
+				let mut blended = shown_below.clone();
+				CompositeLayer {
+					img: Cow::Borrowed(&process_img.0),
+					pos: origin,
+					z: z_layer.0,
+					opacity: opacity.0,
+					blend: blend.0,
+				}
+				.draw_on(&mut blended, region.min);
+
+				let offset = (region.min - origin).as_uvec2();
+				blended
+					.enumerate_pixels()
+					.zip(shown_below.pixels())
+					.for_each(|((x, y, result), below)| {
+						let at = ((offset.y + y) * size.x + offset.x + x) as usize * 4;
+						if let Some(pix) = detail_img.data.get_mut(at..at + 4) {
+							pix.copy_from_slice(&over_difference(*below, *result).0);
+						}
+					});
+			},
+		);
+}
+
+/// Pixel which drawn normally over `below` give `result`. Result darker than what
+/// show through a see through `below` can not be reached, so it is clamped
+fn over_difference(below: Rgba<u8>, result: Rgba<u8>) -> Rgba<u8> {
+	if below == result {
+		return Rgba([0; 4]);
+	}
+	let below_alpha = below.0[3] as f32 / 255.0;
+	if below_alpha >= 1.0 {
+		return result;
+	}
+	let result_alpha = result.0[3] as f32 / 255.0;
+	let alpha = ((result_alpha - below_alpha) / (1.0 - below_alpha)).clamp(0.0, 1.0);
+	if alpha <= 0.0 {
+		return Rgba([0; 4]);
+	}
+	let mut pix = [0, 0, 0, (alpha * 255.0).round() as u8];
+	pix.iter_mut().take(3).enumerate().for_each(|(n, channel)| {
+		let premultiplied =
+			result.0[n] as f32 * result_alpha - below.0[n] as f32 * below_alpha * (1.0 - alpha);
+		*channel = (premultiplied / alpha).clamp(0.0, 255.0).round() as u8;
+	});
+	Rgba(pix)
+}
+
+/// Glyphs as [Text2dBundle] draw them, logical pixel with top left at text position
+fn text_to_image(
+	text: &Text,
+	layout: &TextLayoutInfo,
+	image_assets: &Assets<Image>,
+	atlas_layouts: &Assets<TextureAtlasLayout>,
+	scale_factor: f32,
+) -> Option<RgbaImage> {
+	let physical = (layout.logical_size * scale_factor).ceil().as_uvec2();
+	if physical.cmpeq(UVec2::ZERO).any() {
+		return None;
+	}
+	let mut canvas = RgbaImage::new(physical.x, physical.y);
+	layout.glyphs.iter().for_each(|glyph| {
+		let (Some(atlas_img), Some(atlas_layout)) = (
+			image_assets.get(&glyph.atlas_info.texture),
+			atlas_layouts.get(&glyph.atlas_info.texture_atlas),
+		) else {
+			return;
+		};
+		let Some(glyph_rect) = atlas_layout.textures.get(glyph.atlas_info.glyph_index) else {
+			return;
+		};
+		// Opacity is applied by the layer, keep only the color
+		let [red, green, blue, _] = text
+			.sections
+			.get(glyph.section_index)
+			.map_or(Srgba::BLACK, |section| section.style.color.to_srgba())
+			.to_u8_array();
+		let glyph_size = glyph_rect.size();
+		// Position is glyph center with y going up from bottom
+		let top_left = Vec2::new(
+			glyph.position.x - glyph_size.x as f32 / 2.0,
+			physical.y as f32 - glyph.position.y - glyph_size.y as f32 / 2.0,
+		)
+		.round()
+		.as_ivec2();
+		let atlas_width = atlas_img.width();
+		(0..glyph_size.y).for_each(|y| {
+			(0..glyph_size.x).for_each(|x| {
+				let at = top_left + UVec2::new(x, y).as_ivec2();
+				if at.cmplt(IVec2::ZERO).any() || at.cmpge(physical.as_ivec2()).any() {
+					return;
+				}
+				let n = ((glyph_rect.min.y + y) * atlas_width + glyph_rect.min.x + x) as usize * 4;
+				let Some(alpha) = atlas_img.data.get(n + 3) else {
+					return;
+				};
+				canvas.put_pixel(at.x as u32, at.y as u32, Rgba([red, green, blue, *alpha]));
+			});
+		});
+	});
+	if scale_factor == 1.0 {
+		return Some(canvas);
+	}
+	let logical = layout.logical_size.ceil().as_uvec2().max(UVec2::ONE);
+	Some(imageops::resize(
+		&canvas,
+		logical.x,
+		logical.y,
+		imageops::FilterType::Triangle,
+	))
+}
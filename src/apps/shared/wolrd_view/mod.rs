@@ -1,8 +1,12 @@
+mod blend_view;
 mod view_render;
-use super::MarkerDisplay;
-use bevy::prelude::*;
+use super::{image_edition::process_image_changed, MarkerDisplay};
+use bevy::{prelude::*, text::update_text2d_layout};
+use blend_view::*;
 use view_render::*;
 
+pub use view_render::marching_ants;
+
 pub(super) struct LocalViewPlugin;
 impl Plugin for LocalViewPlugin {
 	fn build(&self, app: &mut App) {
@@ -16,6 +20,7 @@ impl Plugin for LocalViewPlugin {
 					draw_point_line,
 					draw_point,
 					draw_cropper,
+					draw_pixel_mask,
 					draw_selecting_box,
 					selected_obj_outline,
 					shape_drawer,
@@ -26,6 +31,12 @@ impl Plugin for LocalViewPlugin {
 					// draw_grid_box,
 				),
 			)
+			.add_systems(
+				PostUpdate,
+				display_blended
+					.after(process_image_changed)
+					.after(update_text2d_layout),
+			)
 			.add_systems(FixedUpdate, draw_xy_line);
 	}
 }
@@ -39,106 +39,73 @@ pub(super) fn draw_point(
 	});
 }
 
+/// Arrow for straight segment, curve show its handles, arc its radius and ellipse
 pub(super) fn draw_point_line(
 	mut gizmos: Gizmos,
-	query_point: Query<((&ObjectPosition, &PointType), Relations<PointToPoint>), With<ObjectPoint>>,
+	query_point: Query<
+		(
+			(Entity, &ObjectPosition, &PointType),
+			Relations<PointToPoint>,
+		),
+		With<ObjectPoint>,
+	>,
 	items: Query<Entity, Root<PointToPoint>>,
 ) {
+	// Segment begin where previous one end, arc end away from its center
+	let mut ends: HashMap<Entity, Vec2> = HashMap::new();
 	query_point
 		.traverse::<PointToPoint>(items.iter())
 		.track_self()
-		.for_each(|(parent, op_path_parent), _, (child, op_path_child), _| {
-			use PointType::*;
-			match (op_path_parent, op_path_child) {
-				(LineTo, LineTo) => {
-					gizmos
-						.arrow_2d(parent.xy(), child.xy(), Color::WHITE)
-						.with_tip_length(4.0);
-				},
-				_ => {
-					gizmos
-						.arrow_2d(parent.xy(), child.xy(), Color::WHITE)
-						.with_tip_length(4.0);
-				},
-				// (None, Some(path_child)) => match path_child {
-				// 	PathType::QuadraticBezier { to } => todo!(),
-				// 	PathType::CubricBezier { ctrl1, ctrl2, to } => todo!(),
-				// 	PathType::Arc {
-				// 		radii,
-				// 		sweep_angle,
-				// 		x_rotation,
-				// 	} => todo!(),
-				// },
-				// (Some(path_parent), None) => match path_child {
-				// 	PathType::QuadraticBezier { to } => todo!(),
-				// 	PathType::CubricBezier { ctrl1, ctrl2, to } => todo!(),
-				// 	PathType::Arc {
-				// 		radii,
-				// 		sweep_angle,
-				// 		x_rotation,
-				// 	} => todo!(),
-				// },
-				// (Some(path_parent), Some(path_child)) => match (path_parent, path_child) {
-				// 	(PathType::QuadraticBezier { to }, PathType::QuadraticBezier { to }) => todo!(),
-				// 	(
-				// 		PathType::QuadraticBezier { to },
-				// 		PathType::CubricBezier { ctrl1, ctrl2, to },
-				// 	) => todo!(),
-				// 	(
-				// 		PathType::QuadraticBezier { to },
-				// 		PathType::Arc {
-				// 			radii,
-				// 			sweep_angle,
-				// 			x_rotation,
-				// 		},
-				// 	) => todo!(),
-				// 	(
-				// 		PathType::CubricBezier { ctrl1, ctrl2, to },
-				// 		PathType::QuadraticBezier { to },
-				// 	) => todo!(),
-				// 	(
-				// 		PathType::CubricBezier { ctrl1, ctrl2, to },
-				// 		PathType::CubricBezier { ctrl1, ctrl2, to },
-				// 	) => todo!(),
-				// 	(
-				// 		PathType::CubricBezier { ctrl1, ctrl2, to },
-				// 		PathType::Arc {
-				// 			radii,
-				// 			sweep_angle,
-				// 			x_rotation,
-				// 		},
-				// 	) => gizmos.arc_2d(*center, *sweep_angle, *x_rotation, radii.to_angle(), fore),
-				// 	(
-				// 		PathType::Arc {
-				// 			radii,
-				// 			sweep_angle,
-				// 			x_rotation,
-				// 		},
-				// 		PathType::QuadraticBezier { to },
-				// 	) => gizmos.arc_2d(*center, *sweep_angle, *x_rotation, radii.to_angle(), fore),
-				// 	(
-				// 		PathType::Arc {
-				// 			radii,
-				// 			sweep_angle,
-				// 			x_rotation,
-				// 		},
-				// 		PathType::CubricBezier { ctrl1, ctrl2, to },
-				// 	) => todo!(),
-				// 	(
-				// 		PathType::Arc {
-				// 			radii,
-				// 			sweep_angle,
-				// 			x_rotation,
-				// 		},
-				// 		PathType::Arc {
-				// 			radii,
-				// 			sweep_angle,
-				// 			x_rotation,
-				// 		},
-				// 	) => todo!(),
-				// },
-			}
-		});
+		.for_each(
+			|(ent_parent, parent, _), _, (ent_child, child, point_type), _| {
+				let from = ends.get(&*ent_parent).copied().unwrap_or(parent.xy());
+				let to = point_type.end(from, child.xy());
+				ends.insert(*ent_child, to);
+				let handle_color = Color::AYU_LIME;
+				match point_type {
+					PointType::QuadraticBezier { ctrl } => {
+						gizmos.line_2d(from, *ctrl, handle_color);
+						gizmos.line_2d(*ctrl, to, handle_color);
+						gizmos.circle_2d(*ctrl, PICK_RANGE / 2.0, handle_color);
+					},
+					PointType::CubricBezier { ctrl1, ctrl2, .. } => {
+						gizmos.line_2d(from, *ctrl1, handle_color);
+						gizmos.line_2d(to, *ctrl2, handle_color);
+						gizmos.circle_2d(*ctrl1, PICK_RANGE / 2.0, handle_color);
+						gizmos.circle_2d(*ctrl2, PICK_RANGE / 2.0, handle_color);
+					},
+					PointType::Arc {
+						radii, x_rotation, ..
+					} => {
+						let center = child.xy();
+						if let Some((start, _)) = point_type.arc_start(from, center) {
+							gizmos.line_2d(center, start, handle_color);
+						}
+						gizmos.line_2d(center, to, handle_color);
+						gizmos.ellipse_2d(
+							center,
+							*x_rotation,
+							radii.abs(),
+							handle_color.with_alpha(0.3),
+						);
+						// Turning direction at the end
+						if let Some((_, ctrl2, end)) = point_type.arc_curves(from, center).last() {
+							let tail = *end - (*end - *ctrl2).normalize_or_zero() * 8.0;
+							gizmos
+								.arrow_2d(tail, *end, Color::WHITE)
+								.with_tip_length(4.0);
+						}
+					},
+					PointType::LineTo => {
+						gizmos.arrow_2d(from, to, Color::WHITE).with_tip_length(4.0);
+					},
+					// Jump to other outline
+					PointType::MoveTo => {
+						gizmos.line_2d(from, to, Color::WHITE.with_alpha(0.3));
+					},
+				}
+			},
+		);
 }
 
 pub(super) fn draw_user_cursor(
@@ -234,6 +201,58 @@ pub(super) fn draw_cropper(
 	});
 }
 
+const ANTS_DASH: f32 = 4.0;
+
+/// Black and white dashes walking along closed outline
+pub fn marching_ants(gizmos: &mut Gizmos, outline: &[Vec2], elapsed: f32) {
+	let mut walked = -(elapsed * 8.0).rem_euclid(ANTS_DASH * 2.0);
+	outline
+		.iter()
+		.zip(outline.iter().cycle().skip(1))
+		.for_each(|(from, to)| {
+			let length = from.distance(*to);
+			let dir = (*to - *from).normalize_or_zero();
+			let mut at = 0.0;
+			while at < length {
+				let cycle = (walked + at).rem_euclid(ANTS_DASH * 2.0);
+				let color = if cycle < ANTS_DASH {
+					Color::BLACK
+				} else {
+					Color::WHITE
+				};
+				let step = (ANTS_DASH - cycle.rem_euclid(ANTS_DASH))
+					.max(0.01)
+					.min(length - at);
+				gizmos.line_2d(*from + dir * at, *from + dir * (at + step), color);
+				at += step;
+			}
+			walked += length;
+		});
+}
+
+pub(super) fn draw_pixel_mask(
+	mut gizmos: Gizmos,
+	query_user: Query<&PixelMask, With<UserId>>,
+	query_object: Query<&ObjectPosition, With<ObjectImage>>,
+	time: Res<Time>,
+) {
+	query_user.iter().for_each(|pixel_mask| {
+		let Some(mask) = &pixel_mask.0 else {
+			return;
+		};
+		let Ok(obj_pos) = query_object.get(mask.ent) else {
+			return;
+		};
+		mask.outline.iter().for_each(|polygon| {
+			let world = polygon
+				.iter()
+				.map(|pixel| obj_pos.0 + Vec2::new(pixel.x, -pixel.y))
+				.collect::<Vec<_>>();
+			marching_ants(&mut gizmos, &world, time.elapsed_seconds());
+		});
+	});
+}
+
 pub(super) fn shape_drawer(
 	mut painter: ShapePainter,
 	mut mark_source: ResMut<MarkerDisplay>,
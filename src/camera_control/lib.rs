@@ -2,7 +2,7 @@ use bevy::{ecs::system::SystemParam, prelude::*, window::PrimaryWindow};
 use leafwing_input_manager::prelude::*;
 
 pub const CAMERA_VIEW_RANGE: f32 = 10000.0;
-pub(super) const TILE_DISTANCE: f32 = -CAMERA_VIEW_RANGE + 1.0;
+pub const TILE_DISTANCE: f32 = -CAMERA_VIEW_RANGE + 1.0;
 pub const MAX_VALID_RANGE: f32 = 1e+8;
 
 // #[derive(Component)]
@@ -12,7 +12,7 @@ pub const MAX_VALID_RANGE: f32 = 1e+8;
 pub struct MainCamera;
 
 #[derive(Resource, Default)]
-pub struct IsUnFocusOnUI(pub(super) bool);
+pub struct IsUnFocusOnUI(pub bool);
 
 impl IsUnFocusOnUI {
 	pub fn get(&self) -> bool {
@@ -21,7 +21,7 @@ impl IsUnFocusOnUI {
 }
 
 #[derive(Component)]
-pub(super) struct BackGroundTile;
+pub struct BackGroundTile;
 
 #[derive(SystemParam, Deref)]
 pub struct GlobalScreen2D<'w, 's>(
@@ -54,7 +54,7 @@ impl<'w, 's> GlobalScreen2D<'w, 's> {
 }
 
 #[derive(PartialEq, Eq, Clone, Copy, Debug, Reflect, Hash)]
-pub(super) enum GlobalCamAction {
+pub enum GlobalCamAction {
 	ZoomIn,
 	ZoomOut,
 	Scroll,
@@ -75,7 +75,7 @@ impl Actionlike for GlobalCamAction {
 }
 
 impl GlobalCamAction {
-	pub(super) fn bind_default() -> InputMap<Self> {
+	pub fn bind_default() -> InputMap<Self> {
 		InputMap::new([
 			(Self::ZoomIn, KeyCode::Equal),
 			(Self::ZoomOut, KeyCode::Minus),
@@ -89,7 +89,7 @@ impl GlobalCamAction {
 	}
 }
 
-pub(super) trait TiledBgColor {
+pub trait TiledBgColor {
 	fn bg_color(&self) -> Vec<u8>;
 }
 
@@ -2,10 +2,10 @@ use bevy::{color::palettes::tailwind::GRAY_700, prelude::*, window::WindowResize
 use bevy_cosmic_edit::FocusedWidget;
 use bevy_mod_picking::prelude::*;
 use leafwing_input_manager::prelude::*;
-pub mod lib;
 use lib::*;
+pub use rainful_diffolor::camera_control::lib;
 
-use crate::trait_bevy::BuildCommonImage;
+use rainful_diffolor::trait_bevy::BuildCommonImage;
 
 pub(super) struct CameraPlugin;
 impl Plugin for CameraPlugin {
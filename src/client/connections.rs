@@ -0,0 +1,261 @@
+use async_compat::Compat;
+use bevy::{
+	ecs::system::RunSystemOnce,
+	prelude::*,
+	tasks::{block_on, futures_lite::future, IoTaskPool},
+};
+use lightyear::prelude::*;
+use lightyear::{connection::netcode::CONNECT_TOKEN_BYTES, prelude::client::*};
+use std::net::SocketAddr;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use rainful_diffolor::apps::shared::{shared_config, PROTOCOL_ID};
+
+use super::{
+	AuthReply, ConnectTokenRequestTask, DisplayMsgEvent, ModerateNet, RdioClientState, CLIENT_ADDR,
+};
+
+pub(super) fn build_client_plugin() -> ClientPlugins {
+	let auth = Authentication::None;
+	let io = IoConfig {
+		transport: ClientTransport::UdpSocket(CLIENT_ADDR),
+		..default()
+	};
+	let net_config = NetConfig::Netcode {
+		auth,
+		config: NetcodeConfig::default(),
+		io,
+	};
+	let config = ClientConfig {
+		shared: shared_config(),
+		net: net_config,
+		replication: ReplicationConfig {
+			send_updates_mode: SendUpdatesMode::SinceLastSend,
+			..default()
+		},
+		..default()
+	};
+	ClientPlugins::new(config)
+}
+
+pub(super) fn fetch_connect_token(
+	mut connect_token_request: ResMut<ConnectTokenRequestTask>,
+	mut client_config: ResMut<ClientConfig>,
+	mut next_client_state: ResMut<NextState<RdioClientState>>,
+	mut cmd: Commands,
+	op_reconnecting: Option<Res<Reconnecting>>,
+) {
+	let Some(task) = &mut connect_token_request.task else {
+		return;
+	};
+	let Some(res_connect_token) = block_on(future::poll_once(task)) else {
+		return;
+	};
+	let connect_token = match res_connect_token {
+		Ok((connect_token, resume)) => {
+			connect_token_request.resume = resume;
+			connect_token
+		},
+		Err(msg) => {
+			cmd.trigger(DisplayMsgEvent(msg));
+			// Backoff will try again
+			if op_reconnecting.is_none() {
+				next_client_state.set(RdioClientState::Offline);
+			}
+			connect_token_request.task = None;
+			return;
+		},
+	};
+	if let NetConfig::Netcode { auth, .. } = &mut client_config.net {
+		*auth = Authentication::Token(connect_token);
+	}
+	cmd.connect_client();
+	cmd.trigger(DisplayMsgEvent("Connected to server.".to_owned()));
+	connect_token_request.task = None;
+}
+
+pub(super) fn activate_connect_token(
+	mut cmd: Commands,
+	mut connect_token_request: ResMut<ConnectTokenRequestTask>,
+	client_config: Res<ClientConfig>,
+) {
+	let NetConfig::Netcode { auth, .. } = &client_config.net else {
+		return;
+	};
+	if auth.has_token() {
+		cmd.connect_client();
+		return;
+	}
+	let auth_backend_addr = connect_token_request.auth_backend_addr;
+	let password = connect_token_request.password.clone();
+	let resume = connect_token_request.resume;
+	let task = IoTaskPool::get().spawn_local(Compat::new(async move {
+		get_connect_token_from_auth_backend(auth_backend_addr, password, resume).await
+	}));
+	connect_token_request.task = Some(task);
+	cmd.trigger(DisplayMsgEvent(
+		"Connecting server backend authorize.".to_owned(),
+	));
+}
+
+/// Retry with backoff after link lost, user and tools stay as they are
+#[derive(Resource)]
+pub(super) struct Reconnecting {
+	attempt: u32,
+	timer: Timer,
+}
+
+/// Server ended the session on purpose, don't come back
+#[derive(Resource)]
+pub(super) struct SessionEnded(String);
+
+const MAX_RECONNECT_ATTEMPT: u32 = 8;
+const MAX_RECONNECT_DELAY_SECS: f32 = 30.0;
+
+pub(super) fn handle_disconnection(
+	_trigger: Trigger<DisconnectEvent>,
+	mut cmd: Commands,
+	mut next_client_state: ResMut<NextState<RdioClientState>>,
+	op_session_ended: Option<Res<SessionEnded>>,
+	op_reconnecting: Option<Res<Reconnecting>>,
+) {
+	if let Some(session_ended) = op_session_ended {
+		cmd.trigger(DisplayMsgEvent(session_ended.0.clone()));
+		cmd.remove_resource::<SessionEnded>();
+		next_client_state.set(RdioClientState::Offline);
+		return;
+	}
+	if op_reconnecting.is_some() {
+		return;
+	}
+	cmd.trigger(DisplayMsgEvent("Connection lost, reconnecting.".to_owned()));
+	cmd.insert_resource(Reconnecting {
+		attempt: 0,
+		timer: Timer::from_seconds(1.0, TimerMode::Once),
+	});
+}
+
+pub(super) fn receive_moderate(
+	mut cmd: Commands,
+	mut events: EventReader<MessageEvent<ModerateNet>>,
+) {
+	events.read().for_each(|event| {
+		let msg = match event.message() {
+			ModerateNet::Kick(_) => "You were kicked from server.",
+			ModerateNet::Ban(_) => "You were banned from server.",
+		};
+		cmd.insert_resource(SessionEnded(msg.to_owned()));
+	});
+}
+
+pub(super) fn reconnect_with_backoff(
+	mut cmd: Commands,
+	mut reconnecting: ResMut<Reconnecting>,
+	mut client_config: ResMut<ClientConfig>,
+	mut next_client_state: ResMut<NextState<RdioClientState>>,
+	connect_token_request: Res<ConnectTokenRequestTask>,
+	networking_state: Res<State<NetworkingState>>,
+	time: Res<Time>,
+) {
+	if networking_state.get() == &NetworkingState::Connected {
+		cmd.remove_resource::<Reconnecting>();
+		cmd.trigger(DisplayMsgEvent("Reconnected.".to_owned()));
+		return;
+	}
+	if connect_token_request.task.is_some()
+		|| networking_state.get() == &NetworkingState::Connecting
+	{
+		return;
+	}
+	if !reconnecting.timer.tick(time.delta()).finished() {
+		return;
+	}
+	if reconnecting.attempt >= MAX_RECONNECT_ATTEMPT {
+		cmd.remove_resource::<Reconnecting>();
+		cmd.trigger(DisplayMsgEvent("Unable to reconnect.".to_owned()));
+		next_client_state.set(RdioClientState::Offline);
+		return;
+	}
+	reconnecting.attempt += 1;
+	let delay = 2f32
+		.powi(reconnecting.attempt as i32)
+		.min(MAX_RECONNECT_DELAY_SECS);
+	reconnecting.timer = Timer::from_seconds(delay, TimerMode::Once);
+	cmd.trigger(DisplayMsgEvent(format!(
+		"Reconnecting, attempt {}/{}.",
+		reconnecting.attempt, MAX_RECONNECT_ATTEMPT
+	)));
+
+	// Old token is used, ask backend again
+	if let NetConfig::Netcode { auth, .. } = &mut client_config.net {
+		*auth = Authentication::None;
+	}
+	cmd.add(|world: &mut World| {
+		world.run_system_once(activate_connect_token);
+	});
+}
+
+pub(super) fn disconnect_from_server(mut cmd: Commands, mut client_config: ResMut<ClientConfig>) {
+	cmd.disconnect_client();
+	let NetConfig::Netcode { auth, .. } = &mut client_config.net else {
+		return;
+	};
+	*auth = Authentication::None;
+}
+
+async fn get_connect_token_from_auth_backend(
+	auth_backend_addr: SocketAddr,
+	password: String,
+	resume: u128,
+) -> Result<(ConnectToken, u128), String> {
+	// Handshake hold its length in one byte, same limit as server command line
+	if password.len() > u8::MAX as usize {
+		return Err(format!("Password longer than {} bytes.", u8::MAX));
+	}
+	let mut stream = match tokio::net::TcpStream::connect(auth_backend_addr).await {
+		Ok(v) => v,
+		Err(e) => {
+			error!("Failed to connect to authentication server on: {:?}", e);
+			return Err("Unable to reach server.".to_owned());
+		},
+	};
+	let password = password.as_bytes();
+	let mut handshake = PROTOCOL_ID.to_le_bytes().to_vec();
+	handshake.push(password.len() as u8);
+	handshake.extend_from_slice(password);
+	handshake.extend(resume.to_le_bytes());
+	if let Err(e) = stream.write_all(&handshake).await {
+		error!("Failed to send password: {:?}", e);
+		return Err("Unable to reach server.".to_owned());
+	}
+	let reply = match stream.read_u8().await.map(AuthReply::from_byte) {
+		Ok(Some(reply)) => reply,
+		_ => {
+			error!("Failed to read reply from authentication server");
+			return Err("Invalid reply from server.".to_owned());
+		},
+	};
+	if reply != AuthReply::Accepted {
+		return Err(reply.describe().to_owned());
+	}
+	let mut buffer = [0u8; CONNECT_TOKEN_BYTES];
+	match stream.read_exact(&mut buffer).await {
+		Ok(n) if n == CONNECT_TOKEN_BYTES => {
+			trace!(
+				"Received token bytes: {:?}. Token len: {:?}",
+				buffer,
+				buffer.len()
+			);
+			let Ok(token) = ConnectToken::try_from_bytes(&buffer) else {
+				error!("Failed to parse token from authentication server");
+				return Err("Invalid reply from server.".to_owned());
+			};
+			let resume = stream.read_u128_le().await.unwrap_or_default();
+			Ok((token, resume))
+		},
+		_ => {
+			error!("Failed to read token from authentication server");
+			Err("Invalid reply from server.".to_owned())
+		},
+	}
+}
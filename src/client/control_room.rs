@@ -37,7 +37,9 @@ impl Plugin for MegaEditorPlugin {
 			.add_systems(
 				PreUpdate,
 				(
-					request_point.after(MainSet::Send),
+					request_point
+						.after(MainSet::Send)
+						.run_if(not(resource_exists::<join::JoinSync>)),
 					update_cursor_position.run_if(ui_unfocus),
 					hide_object.after(InputManagerSystem::ManualControl),
 					(connect_point, replicate_input_client).after(MainSet::Receive),
@@ -56,7 +58,16 @@ impl Plugin for MegaEditorPlugin {
 						pick_object.run_if(in_state(EditorTools::Pick)),
 						color_pick.run_if(in_state(EditorTools::ColorPick)),
 						edit_path.run_if(in_state(EditorTools::Path)),
+						crop_select.run_if(in_state(EditorTools::Crop)),
+						pixel_select.run_if(in_state(EditorTools::Select)),
 						pencel_line.run_if(in_state(EditorTools::Pencel)),
+						bucket_click.run_if(in_state(EditorTools::Fill)),
+						drag_shape.run_if(
+							in_state(EditorTools::Rectangle)
+								.or_else(in_state(EditorTools::Ellipse))
+								.or_else(in_state(EditorTools::Polygon))
+								.or_else(in_state(EditorTools::Star)),
+						),
 						pen_marker.run_if(in_state(EditorTools::Marker)),
 						resize_img.run_if(
 							in_state(EditorTools::Resize).or_else(in_state(EditorTools::Scale)),
@@ -69,21 +80,37 @@ impl Plugin for MegaEditorPlugin {
 						color_swap,
 						export_single_image_png,
 						copy_or_canvas,
+						cut_pixels,
 						export_svg,
 						paste_from_clip_board,
+						undo_redo,
+						image_transform,
+						new_group,
+						group_visibility,
 					),
 					// (
 					//     // line_path.run_if(in_state(EditorTools::Path)),
-					//     // crop_2d.run_if(in_state(EditorTools::Crop)),
 					//     // geometric_transform.run_if(in_state(EditorTools::Scale)),
 					// )
 					//     .run_if(cured_zone),
 					pending_image_object,
 					receive_image_data,
-					receive_draw_pen,
+					receive_image_patch,
+					receive_verified::<PenDraw>,
+					receive_verified::<BucketFill>,
 				)
 					.run_if(in_state(RdioClientState::Online)),
 			)
+			.add_systems(OnExit(EditorTools::Crop), clear_crop_select)
+			.add_systems(OnExit(EditorTools::Select), leave_pixel_select)
+			.add_systems(
+				OnEnter(EditorTools::Scale),
+				set_resize_kind(ResizeKind::Scale),
+			)
+			.add_systems(
+				OnEnter(EditorTools::Resize),
+				set_resize_kind(ResizeKind::Resize),
+			)
 			.add_systems(
 				PostUpdate,
 				(
@@ -130,11 +157,14 @@ fn edit_path(
 			&ActionState<SettingsAction>,
 			&mut ActionState<VerifyAction>,
 			&mut SelectedObject,
-			&CursorPos,
+			Ref<CursorPos>,
 		),
 		With<MainUser>,
 	>,
 	query_point: Query<(Entity, &ObjectPosition), (With<ObjectPoint>, With<ObjectWorld>)>,
+	query_handle: Query<(Entity, &PointType), (With<ObjectPoint>, With<ObjectWorld>)>,
+	mut dragged_handle: Local<Option<(Entity, u8)>>,
+	mut client: ResMut<ConnectionManager>,
 ) {
 	let Ok((mut pin_point, actions_key, mut action_client, mut selected_obj, cur_pos)) =
 		query_user.get_single_mut()
@@ -147,6 +177,45 @@ fn edit_path(
 	let mut sorted_depth = on_click.read().collect::<Vec<_>>();
 	sorted_depth.sort_by_key(|a| a.hit.depth as i64);
 
+	// Point under cursor win over handle
+	let is_on_point = sorted_depth
+		.iter()
+		.any(|pointer| query_point.contains(pointer.target()));
+	if actions_key.just_pressed(&SettingsAction::Primary) && !is_on_point {
+		*dragged_handle = query_handle
+			.iter()
+			.flat_map(|(ent_point, point_type)| {
+				point_type
+					.handles()
+					.into_iter()
+					.enumerate()
+					.map(move |(index, handle)| (ent_point, index as u8, handle))
+			})
+			.map(|(ent_point, index, handle)| (ent_point, index, handle.distance(cur_pos.xy())))
+			.filter(|(.., distance)| *distance < PICK_RANGE)
+			.min_by(|(.., a), (.., b)| a.total_cmp(b))
+			.map(|(ent_point, index, _)| (ent_point, index));
+	}
+	if let Some((ent, index)) = *dragged_handle {
+		if actions_key.just_released(&SettingsAction::Primary) {
+			*dragged_handle = None;
+		} else if cur_pos.is_changed() {
+			client
+				.send_message_to_target::<MainChannel, CurveEditNet>(
+					&mut CurveEditNet::Handle {
+						ent,
+						index,
+						to: cur_pos.xy(),
+					},
+					NetworkTarget::All,
+				)
+				.unwrap_or_else(|e| {
+					error!("Fail to send message: {:?}", e);
+				});
+		}
+		return;
+	}
+
 	for pointer in sorted_depth {
 		if pointer.button != PointerButton::Primary {
 			continue;
@@ -198,8 +267,16 @@ fn edit_path(
 	}
 }
 
+fn set_resize_kind(kind: ResizeKind) -> impl FnMut(Query<&mut ResizeKind, With<MainUser>>) {
+	move |mut query_user: Query<&mut ResizeKind, With<MainUser>>| {
+		if let Ok(mut resize_kind) = query_user.get_single_mut() {
+			resize_kind.set_if_neq(kind);
+		}
+	}
+}
+
 fn resize_img(
-	query_object: Query<&ObjectPosition, With<ObjectWorld>>,
+	query_object: Query<(&ObjectPosition, Option<&ProcessImage>), With<ObjectWorld>>,
 	mut query_user: Query<
 		(
 			Entity,
@@ -237,14 +314,21 @@ fn resize_img(
 		return;
 	};
 
-	let Ok(obj_pos) = query_object.get(*ent_main) else {
+	let Ok((obj_pos, op_process_img)) = query_object.get(*ent_main) else {
 		return;
 	};
 
 	if actions_key.just_pressed(&SettingsAction::Primary) {
 		resize_point.0 = Some(real_world_ray);
 		let pos_obj = obj_pos.0;
-		let little_box = Rect::from_center_half_size(pos_obj, Vec2::splat(20.0));
+		// Handle region follow image bound, other object only have a point
+		let little_box = match op_process_img {
+			Some(process_img) => {
+				let size = UVec2::from(process_img.dimensions()).as_vec2();
+				Rect::new(pos_obj.x, pos_obj.y - size.y, pos_obj.x + size.x, pos_obj.y)
+			},
+			None => Rect::from_center_half_size(pos_obj, Vec2::splat(20.0)),
+		};
 		let Rect { min, max } = little_box;
 		let recty = little_box.size() * 0.3;
 
@@ -0,0 +1,257 @@
+use std::{fs, path::PathBuf};
+
+use bevy::{
+	ecs::{system::SystemState, world::CommandQueue},
+	prelude::*,
+	tasks::{AsyncComputeTaskPool, IoTaskPool},
+};
+use client::ConnectionManager;
+use leafwing_input_manager::prelude::*;
+use rainful_diffolor::{source_to_docs, RDIO_IN_SAVE_DISK};
+
+use rainful_diffolor::apps::shared::prelude::*;
+
+use super::*;
+
+const RECENT_RDIO: &str = "Recent.txt";
+const MAX_RECENT_RDIO: usize = 10;
+
+pub(super) struct RdioDocumentPlugin;
+impl Plugin for RdioDocumentPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<RdioFile>()
+			.init_resource::<RecentRdio>()
+			.add_systems(Startup, load_recent_rdio)
+			.add_systems(
+				Update,
+				(save_rdio, open_rdio).run_if(in_state(RdioClientState::Online)),
+			);
+	}
+}
+
+/// Document path of last Save / Open
+#[derive(Resource, Default)]
+pub(super) struct RdioFile(pub Option<PathBuf>);
+
+/// Newest first
+#[derive(Resource, Default)]
+pub(super) struct RecentRdio(pub Vec<PathBuf>);
+
+fn rdio_dir() -> Option<PathBuf> {
+	source_to_docs().map(|docs_dir| docs_dir.join(RDIO_IN_SAVE_DISK))
+}
+
+fn load_recent_rdio(mut recent: ResMut<RecentRdio>) {
+	let Some(Ok(listed)) = rdio_dir().map(|dir| fs::read_to_string(dir.join(RECENT_RDIO))) else {
+		return;
+	};
+	recent.0 = listed
+		.lines()
+		.map(PathBuf::from)
+		.filter(|path| path.is_file())
+		.take(MAX_RECENT_RDIO)
+		.collect();
+}
+
+fn remember_rdio(world: &mut World, path: PathBuf) {
+	let mut recent = world.resource_mut::<RecentRdio>();
+	recent.0.retain(|recented| recented != &path);
+	recent.0.insert(0, path.clone());
+	recent.0.truncate(MAX_RECENT_RDIO);
+	let listed = recent
+		.0
+		.iter()
+		.filter_map(|recented| recented.to_str())
+		.collect::<Vec<_>>()
+		.join("\n");
+	world.resource_mut::<RdioFile>().0 = Some(path);
+
+	let Some(dir) = rdio_dir() else {
+		return;
+	};
+	IoTaskPool::get()
+		.spawn(async move {
+			fs::write(dir.join(RECENT_RDIO), listed)
+				.unwrap_or_else(|e| error!("Failed to save recent list: {:?}", e));
+		})
+		.detach();
+}
+
+/// Decode and send to server, admin only, document replace the whole board and its history
+pub(super) fn load_rdio_file(world: &mut World, path: PathBuf, bytes: &[u8]) {
+	let name = path
+		.file_name()
+		.and_then(|name| name.to_str())
+		.unwrap_or("Document")
+		.to_owned();
+	let mut saved_rdio = match SavedRdio::from_bytes(bytes) {
+		Ok(saved_rdio) => saved_rdio,
+		Err(e) => {
+			warn!("Invalid Rdio file {}: {:?}", name, e);
+			let msg = match e {
+				RdioFileError::NewerVersion(_) => format!("{name} is made by newer version"),
+				_ => format!("{name} is not valid Rdio file"),
+			};
+			world.trigger(DisplayMsgEvent(msg));
+			return;
+		},
+	};
+
+	let mut client = {
+		let mut system_state = SystemState::<ResMut<ConnectionManager>>::new(world);
+		system_state.get_mut(world)
+	};
+	client
+		.send_message::<MainChannel, SavedRdio>(&mut saved_rdio)
+		.unwrap_or_else(|e| error!("Fail to send message: {:?}", e));
+
+	remember_rdio(world, path);
+	world.trigger(DisplayMsgEvent(format!("Opened {name}")));
+}
+
+/// Parse shapes into path objects, server add them on top of the board for everyone
+pub(super) fn load_svg_file(world: &mut World, path: PathBuf, bytes: &[u8]) {
+	let name = path
+		.file_name()
+		.and_then(|name| name.to_str())
+		.unwrap_or("Svg")
+		.to_owned();
+	let parsed = String::from_utf8(bytes.to_vec())
+		.map_err(|e| SvgFileError::Read(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))
+		.and_then(|content| svg_to_rdio(&name, &content));
+	let saved_rdio = match parsed {
+		Ok(saved_rdio) => saved_rdio,
+		Err(e) => {
+			warn!("Invalid Svg file {}: {:?}", name, e);
+			let msg = match e {
+				SvgFileError::NoShape => format!("{name} has no shape to import"),
+				_ => format!("{name} is not valid Svg file"),
+			};
+			world.trigger(DisplayMsgEvent(msg));
+			return;
+		},
+	};
+
+	let mut client = {
+		let mut system_state = SystemState::<ResMut<ConnectionManager>>::new(world);
+		system_state.get_mut(world)
+	};
+	client
+		.send_message::<MainChannel, ImportRdio>(&mut ImportRdio(saved_rdio))
+		.unwrap_or_else(|e| error!("Fail to send message: {:?}", e));
+
+	world.trigger(DisplayMsgEvent(format!("Imported {name}")));
+}
+
+fn save_rdio(
+	mut cmd: Commands,
+	query_user: Query<&ActionState<ToolsStandAlone>, With<MainUser>>,
+	rdio_file: Res<RdioFile>,
+	rdio_param: RdioParam,
+) {
+	let Ok(action) = query_user.get_single() else {
+		return;
+	};
+	let save_as = action.just_pressed(&ToolsStandAlone::SaveAs);
+	if !save_as && !action.just_pressed(&ToolsStandAlone::Save) {
+		return;
+	}
+	let bytes = match rdio_param.collect().to_bytes() {
+		Ok(bytes) => bytes,
+		Err(e) => {
+			error!("Failed to encode Rdio: {:?}", e);
+			return;
+		},
+	};
+
+	let saved_path = rdio_file.0.clone().filter(|_| !save_as);
+	let ent_file = cmd.spawn_empty().id();
+	let task = AsyncComputeTaskPool::get().spawn(async move {
+		let mut cmd_queue = CommandQueue::default();
+		let path = match saved_path {
+			Some(path) => path,
+			None => {
+				let mut dialog = rfd::AsyncFileDialog::new()
+					.add_filter("Rdio", &[RDIO_EXTENSION])
+					.set_file_name(format!("Untitled.{RDIO_EXTENSION}"));
+				if let Some(dir) = rdio_dir() {
+					dialog = dialog.set_directory(dir);
+				}
+				let Some(handle) = dialog.save_file().await else {
+					return cmd_queue;
+				};
+				handle.path().with_extension(RDIO_EXTENSION)
+			},
+		};
+		if let Err(e) = fs::write(&path, bytes) {
+			error!("Failed to save Rdio: {:?}", e);
+			return cmd_queue;
+		}
+		cmd_queue.push(move |world: &mut World| {
+			let name = path
+				.file_name()
+				.and_then(|name| name.to_str())
+				.unwrap_or("Document")
+				.to_owned();
+			remember_rdio(world, path);
+			world.trigger(DisplayMsgEvent(format!("Saved {name}")));
+		});
+		cmd_queue
+	});
+	cmd.entity(ent_file).insert(FileReaded(task));
+}
+
+fn open_rdio(
+	mut cmd: Commands,
+	query_user: Query<&ActionState<ToolsStandAlone>, With<MainUser>>,
+	recent: Res<RecentRdio>,
+) {
+	let Ok(action) = query_user.get_single() else {
+		return;
+	};
+	let recented = if action.just_pressed(&ToolsStandAlone::OpenRecent) {
+		let Some(path) = recent.0.first().cloned() else {
+			cmd.trigger(DisplayMsgEvent("No recent document".to_owned()));
+			return;
+		};
+		Some(path)
+	} else if action.just_pressed(&ToolsStandAlone::Open) {
+		None
+	} else {
+		return;
+	};
+
+	let ent_file = cmd.spawn_empty().id();
+	let task = AsyncComputeTaskPool::get().spawn(async move {
+		let mut cmd_queue = CommandQueue::default();
+		let path = match recented {
+			Some(path) => path,
+			None => {
+				let mut dialog = rfd::AsyncFileDialog::new()
+					.add_filter("Rdio", &[RDIO_EXTENSION])
+					.add_filter("Svg", &[SVG_EXTENSION]);
+				if let Some(dir) = rdio_dir() {
+					dialog = dialog.set_directory(dir);
+				}
+				let Some(handle) = dialog.pick_file().await else {
+					return cmd_queue;
+				};
+				handle.path().to_path_buf()
+			},
+		};
+		let is_svg = path
+			.extension()
+			.is_some_and(|extension| extension.eq_ignore_ascii_case(SVG_EXTENSION));
+		match fs::read(&path) {
+			Ok(bytes) if is_svg => cmd_queue.push(move |world: &mut World| {
+				load_svg_file(world, path, &bytes);
+			}),
+			Ok(bytes) => cmd_queue.push(move |world: &mut World| {
+				load_rdio_file(world, path, &bytes);
+			}),
+			Err(e) => error!("Failed to read Rdio: {:?}", e),
+		}
+		cmd_queue
+	});
+	cmd.entity(ent_file).insert(FileReaded(task));
+}
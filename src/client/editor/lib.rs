@@ -68,6 +68,7 @@ pub(super) enum AttactObject {
 	Pick,
 	LockPick,
 	Visibility,
+	Fold,
 }
 
 impl AttactObject {
@@ -104,6 +105,19 @@ impl AttactObject {
 					}
 				})
 			},
+			AttactObject::Fold => On::<Pointer<Click>>::run(
+				move |mut cmd: Commands,
+				      query_group: Query<Has<GroupFolded>, With<ObjectGroup>>| {
+					let Ok(is_folded) = query_group.get(ent_obj) else {
+						return;
+					};
+					if is_folded {
+						cmd.entity(ent_obj).remove::<GroupFolded>();
+					} else {
+						cmd.entity(ent_obj).insert(GroupFolded);
+					}
+				},
+			),
 		}
 	}
 }
@@ -117,6 +131,9 @@ pub(super) struct ColorChoice;
 #[derive(Component)]
 pub(super) struct DisplayObjectDirectory;
 
+#[derive(Component)]
+pub(super) struct UserListHolder;
+
 #[derive(Component)]
 pub enum DisplayColor {
 	Foreground,
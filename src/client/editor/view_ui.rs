@@ -1,6 +1,6 @@
 use editor::DisplayColor;
 
-use crate::apps::client::*;
+use crate::client::*;
 
 pub(super) fn update_ui_content(
 	mut query_text: ParamSet<(
@@ -0,0 +1,170 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use client::ConnectionManager;
+
+use rainful_diffolor::{apps::shared::prelude::*, trait_bevy::*};
+
+use super::*;
+
+/// Give up waiting for objects, ask state anyway
+const JOIN_TIMEOUT: Duration = Duration::from_secs(15);
+
+pub(super) struct JoinSyncPlugin;
+impl Plugin for JoinSyncPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_systems(
+			Update,
+			(receive_join_manifest, track_join_sync, receive_join_state)
+				.chain()
+				.run_if(in_state(RdioClientState::Online)),
+		)
+		.add_systems(OnExit(RdioClientState::Online), |mut cmd: Commands| {
+			cmd.remove_resource::<JoinSync>();
+		});
+	}
+}
+
+/// Exist while late joiner catching up
+#[derive(Resource)]
+pub(super) struct JoinSync {
+	manifest: JoinManifest,
+	requested: bool,
+	applied: bool,
+	timer: Timer,
+}
+
+#[derive(Component)]
+struct JoinProgressText;
+
+fn receive_join_manifest(
+	mut cmd: Commands,
+	mut events: EventReader<MessageEvent<JoinManifest>>,
+	query_progress: Query<Entity, With<JoinProgressText>>,
+) {
+	let Some(manifest) = events.read().last().map(|event| *event.message()) else {
+		return;
+	};
+	query_progress
+		.iter()
+		.for_each(|ent| cmd.entity(ent).despawn_recursive());
+	cmd.spawn((
+		TextBundle::from_section(
+			"",
+			TextStyle {
+				font_size: FontTypeSize::NAME,
+				color: Srgba::BEVY_WHITE,
+				..default()
+			},
+		)
+		.with_style(Style {
+			position_type: PositionType::Absolute,
+			left: Val::Px(10.0),
+			bottom: Val::Px(10.0),
+			..default()
+		})
+		.with_background_color(Srgba::BEVY_BLACK.with_alpha(0.6)),
+		ZIndex::Global(i32::MAX),
+		JoinProgressText,
+		StateScoped(RdioClientState::Online),
+	));
+	cmd.insert_resource(JoinSync {
+		manifest,
+		requested: false,
+		applied: false,
+		timer: Timer::new(JOIN_TIMEOUT, TimerMode::Once),
+	});
+}
+
+fn track_join_sync(
+	mut cmd: Commands,
+	mut client: ResMut<ConnectionManager>,
+	op_join_sync: Option<ResMut<JoinSync>>,
+	mut query_progress: Query<(Entity, &mut Text), With<JoinProgressText>>,
+	query_object: Query<(), (With<ObjectWorld>, With<Confirmed>)>,
+	query_image: Query<(), (With<ObjectImage>, With<ProcessImage>, With<Confirmed>)>,
+	time: Res<Time>,
+) {
+	let Some(mut join_sync) = op_join_sync else {
+		return;
+	};
+	join_sync.timer.tick(time.delta());
+	let objects = query_object.iter().count() as u32;
+	let images = query_image.iter().count() as u32;
+	let manifest = join_sync.manifest;
+
+	if let Ok((_, mut text)) = query_progress.get_single_mut() {
+		text.sections[0].value = format!(
+			"Joining board: objects {}/{}, images {}/{}",
+			objects.min(manifest.objects),
+			manifest.objects,
+			images.min(manifest.images),
+			manifest.images
+		);
+	}
+
+	let timed_out = join_sync.timer.finished();
+	if !join_sync.requested && (objects >= manifest.objects || timed_out) {
+		client
+			.send_message::<MainChannel, RequestJoinState>(&mut RequestJoinState)
+			.unwrap_or_else(|e| {
+				error!("Fail to send message: {:?}", e);
+			});
+		join_sync.requested = true;
+	}
+	if join_sync.applied && (images >= manifest.images || timed_out) {
+		query_progress
+			.iter()
+			.for_each(|(ent, _)| cmd.entity(ent).despawn_recursive());
+		cmd.remove_resource::<JoinSync>();
+		let msg = if timed_out {
+			"Joined, some objects still loading."
+		} else {
+			"Joined, board is up to date."
+		};
+		cmd.trigger(DisplayMsgEvent(msg.to_owned()));
+	}
+}
+
+fn receive_join_state(
+	mut cmd: Commands,
+	mut events: EventReader<MessageEvent<JoinState>>,
+	op_join_sync: Option<ResMut<JoinSync>>,
+	query_point: Query<Entity, (With<ObjectPoint>, With<Confirmed>)>,
+	mut query_object: Query<&mut ObjectZLayer, (Without<ObjectPoint>, With<ObjectWorld>)>,
+	mut query_user: Query<&mut SelectedObject, (With<UserId>, Without<MainUser>)>,
+) {
+	let Some(mut join_sync) = op_join_sync else {
+		return;
+	};
+	events.read().for_each(|event| {
+		let JoinState {
+			relations,
+			z_order,
+			selections,
+		} = event.message();
+
+		relations.iter().for_each(|(ent1, ent2)| {
+			if !query_point.contains(*ent1) || !query_point.contains(*ent2) {
+				return;
+			}
+			cmd.entity(*ent1).insert(RequestedPoint);
+			cmd.entity(*ent2).insert(RequestedPoint);
+			cmd.trigger(ConnectRelations::<PointToPoint>::new(*ent1, *ent2));
+		});
+
+		let mut n = 0;
+		let mut many_obj = query_object.iter_many_mut(z_order.iter());
+		while let Some(mut obj_z) = many_obj.fetch_next() {
+			obj_z.set_if_neq(ObjectZLayer::at(n));
+			n += 1;
+		}
+
+		selections.iter().for_each(|(ent_user, selected)| {
+			if let Ok(mut selected_obj) = query_user.get_mut(*ent_user) {
+				*selected_obj = selected.clone();
+			}
+		});
+		join_sync.applied = true;
+	});
+}
@@ -3,13 +3,16 @@ use editor::ClientEditorPlugin;
 use lightyear::prelude::client::*;
 use lightyear::prelude::*;
 use platform_specific::PlatformSpecificPlugin;
+use rainful_diffolor::apps::{server::AppServerPlugin, shared::AppSharedPlugin};
 
-use super::shared::proto::MainChannel;
-use crate::apps::shared::prelude::*;
+use rainful_diffolor::apps::shared::prelude::*;
+use rainful_diffolor::apps::shared::proto::MainChannel;
 
 mod connections;
 mod control_room;
+mod document;
 mod editor;
+mod join;
 // mod experimental;
 mod performing;
 mod platform_specific;
@@ -18,9 +21,19 @@ mod world_view;
 
 use connections::*;
 use control_room::MegaEditorPlugin;
+use document::RdioDocumentPlugin;
+use join::JoinSyncPlugin;
 use performing::*;
 use sources::*;
 
+/// Client with its own host server
+pub struct ApplicationPlugin;
+impl Plugin for ApplicationPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_plugins((AppClientPlugin, AppServerPlugin::default(), AppSharedPlugin));
+	}
+}
+
 pub(super) struct AppClientPlugin;
 impl Plugin for AppClientPlugin {
 	fn build(&self, app: &mut App) {
@@ -31,13 +44,29 @@ impl Plugin for AppClientPlugin {
 				MegaEditorPlugin,
 				build_client_plugin(),
 				ClientEditorPlugin,
+				RdioDocumentPlugin,
+				JoinSyncPlugin,
 			))
 			.add_systems(
 				Update,
-				(receive_message, fetch_connect_token).run_if(in_state(RdioClientState::Online)),
+				(
+					receive_message,
+					receive_moderate,
+					fetch_connect_token,
+					reconnect_with_backoff.run_if(resource_exists::<Reconnecting>),
+				)
+					.run_if(in_state(RdioClientState::Online)),
 			)
 			.add_systems(OnEnter(RdioClientState::Online), activate_connect_token)
-			.add_systems(OnExit(RdioClientState::Offline), disconnect_from_server);
+			.add_systems(OnExit(RdioClientState::Offline), disconnect_from_server)
+			.add_systems(
+				OnExit(RdioClientState::Online),
+				|mut cmd: Commands, mut connect_token_request: ResMut<ConnectTokenRequestTask>| {
+					connect_token_request.resume = 0;
+					cmd.remove_resource::<Reconnecting>();
+					cmd.remove_resource::<SessionEnded>();
+				},
+			);
 	}
 }
 
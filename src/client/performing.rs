@@ -2,11 +2,11 @@ use arboard::{Clipboard, ImageData};
 use backend::PointerHits;
 use bevy::{
 	ecs::{system::SystemState, world::CommandQueue},
-	math::I64Vec2,
 	prelude::*,
 	render::primitives::Aabb,
 	sprite::Anchor,
 	tasks::{block_on, futures_lite::future, AsyncComputeTaskPool, IoTaskPool},
+	text::TextLayoutInfo,
 	window::PrimaryWindow,
 };
 use bevy_cosmic_edit::{BufferExtras, CosmicBuffer};
@@ -14,16 +14,19 @@ use bevy_mod_picking::prelude::*;
 use client::ConnectionManager;
 use editor::new_brush_collection;
 use image::*;
-use imageops::overlay;
 use leafwing_input_manager::{
 	action_diff::{ActionDiff, ActionDiffEvent, SummarizedActionState},
 	prelude::*,
 };
 use lightyear::prelude::*;
 use rainful_diffolor::{source_to_docs, RDIO_IN_SAVE_DISK};
-use std::{collections::HashSet, thread, time::Duration};
+use std::{borrow::Cow, collections::HashSet, f32::consts::TAU, thread, time::Duration};
 
-use crate::{apps::shared::prelude::*, camera_control::lib::*, trait_bevy::BuildCommonImage};
+use rainful_diffolor::{
+	apps::shared::{prelude::*, wolrd_view::marching_ants},
+	camera_control::lib::*,
+	trait_bevy::BuildCommonImage,
+};
 
 use super::*;
 
@@ -82,6 +85,7 @@ pub(super) fn setup_client_resources(world: &mut World) {
 
 	let all_client_observe = [
 		world.observe(handle_connection).id(),
+		world.observe(handle_disconnection).id(),
 		world.observe(unpickable_predicted).id(),
 		world.observe(new_brush_collection).id(),
 		world.observe(insert_as_objects).id(),
@@ -109,12 +113,24 @@ fn unpickable_predicted(
 
 fn handle_connection(
 	trigger: Trigger<ConnectEvent>,
-	mut query_user: Query<&mut UserId, With<MainUser>>,
+	mut cmd: Commands,
+	mut query_user: Query<(Entity, &mut UserId), With<MainUser>>,
+	op_reconnecting: Option<Res<Reconnecting>>,
 ) {
 	let event = trigger.event();
 	let client_id = event.client_id();
-	let mut user_id = query_user.single_mut();
+	let (ent_user, mut user_id) = query_user.single_mut();
 	user_id.0 = client_id;
+
+	// Server despawned old user, replicate again under new id
+	if op_reconnecting.is_some() {
+		cmd.entity(ent_user)
+			.remove::<client::Replicate>()
+			.insert(client::Replicate {
+				group: REPLICATION_GROUP,
+				..default()
+			});
+	}
 }
 
 pub(super) fn update_cursor_position(
@@ -147,25 +163,6 @@ pub(super) fn change_editor_tools(
 	}
 }
 
-// fn img_rotate(
-//     mut image_assets: ResMut<Assets<Image>>,
-//     query_object: Query<&Handle<Image>, With<ObjectWorld>>,
-// ) {
-//     let Ok((handle_img, _)) = query_object.get_single() else {
-//         return;
-//     };
-//     let detail_img = image_assets.get_mut(handle_img).unwrap();
-//     let size_img = detail_img.size();
-//     let rgba_img = RgbaImage::from_vec(size_img.x, size_img.y, detail_img.data.clone()).unwrap();
-//     let rotated_img = rotate90(&rgba_img);
-//     detail_img.resize(Extent3d {
-//         width: rotated_img.width(),
-//         height: rotated_img.height(),
-//         ..default()
-//     });
-//     detail_img.data = rotated_img.to_vec();
-// }
-
 /// From Leafwing Code (Without [dbg!])
 pub(super) fn generate_action_diffs<A: Actionlike>(
 	global_action_state: Option<Res<ActionState<A>>>,
@@ -186,6 +183,7 @@ pub fn export_single_image_png(
 		Has<ObjectPath>,
 		&SharingName,
 		Option<&SaveLocation>,
+		&ShownOpacity,
 	)>,
 	query_point: Query<&Parent, (With<ObjectPoint>, With<ObjectWorld>)>,
 	query_user: Query<(&ActionState<ToolsStandAlone>, &SelectedObject), With<MainUser>>,
@@ -207,7 +205,7 @@ pub fn export_single_image_png(
 
 	let rdio_img_dir = docs_dir.join(RDIO_IN_SAVE_DISK).join("Image");
 
-	for (ent_obj, op_process_img, is_path, name, op_location) in
+	for (ent_obj, op_process_img, is_path, name, op_location, opacity) in
 		query_object.iter_many(selected_obj.group.iter().chain(point_path.iter()))
 	{
 		let mut image_file = name.to_string();
@@ -219,7 +217,7 @@ pub fn export_single_image_png(
 			.unwrap_or(rdio_img_dir.clone())
 			.join(image_file);
 
-		let img_ready = if let Some(img) = op_process_img {
+		let mut img_ready = if let Some(img) = op_process_img {
 			img.0.clone()
 		} else if is_path {
 			let Some((data, placement)) = path_param.to_image(ent_obj) else {
@@ -230,6 +228,19 @@ pub fn export_single_image_png(
 		} else {
 			unreachable!();
 		};
+		// Nothing below to blend with, only opacity apply
+		if opacity.0 < 100 {
+			let mut faded = RgbaImage::new(img_ready.width(), img_ready.height());
+			CompositeLayer {
+				img: Cow::Borrowed(&img_ready),
+				pos: IVec2::ZERO,
+				z: 0,
+				opacity: opacity.0,
+				blend: DrawType::Normal,
+			}
+			.draw_on(&mut faded, IVec2::ZERO);
+			img_ready = faded;
+		}
 
 		IoTaskPool::get()
 			.spawn(async move {
@@ -248,14 +259,16 @@ pub fn copy_or_canvas(
 		Option<(&ProcessImage, &ObjectPosition)>,
 		Has<ObjectPath>,
 		&ObjectZLayer,
+		&ShownOpacity,
+		&ObjectBlend,
 	)>,
 	query_point: Query<&Parent, (With<ObjectPoint>, With<ObjectWorld>)>,
-	query_user: Query<(&ActionState<ToolsStandAlone>, &SelectedObject), With<MainUser>>,
+	query_user: Query<(&ActionState<ToolsStandAlone>, &SelectedObject, &PixelMask), With<MainUser>>,
 ) {
 	let Some(docs_dir) = source_to_docs() else {
 		return;
 	};
-	let Ok((action, selected_obj)) = query_user.get_single() else {
+	let Ok((action, selected_obj, pixel_mask)) = query_user.get_single() else {
 		return;
 	};
 
@@ -266,55 +279,44 @@ pub fn copy_or_canvas(
 			.collect::<HashSet<Entity>>();
 
 		let mut recty: Option<IRect> = None;
-		let mut q = query_object
+		let layers = query_object
 			.iter_many(selected_obj.group.iter().chain(point_path.iter()))
-			.filter_map(|(ent_obj, op_process_img, is_path, z_object)| {
-				if let Some((img, obj_pos)) = op_process_img {
-					let pos = obj_pos.as_ivec2() * IVec2::new(1, -1);
-					let size = UVec2::new(img.width(), img.height());
-					let new_rect = IRect::from_corners(pos, pos + size.as_ivec2());
-					if let Some(rected) = &mut recty {
-						*rected = rected.union(new_rect);
+			.filter_map(
+				|(ent_obj, op_process_img, is_path, z_object, opacity, blend)| {
+					let (img, pos) = if let Some((img, obj_pos)) = op_process_img {
+						let pos = obj_pos.as_ivec2() * IVec2::new(1, -1);
+						(Cow::Borrowed(&img.0), pos)
+					} else if is_path {
+						let Some((data, placement)) = path_param.to_image(ent_obj) else {
+							warn!("Invalid Path");
+							return None;
+						};
+						let pos = IVec2::new(placement.left, placement.top);
+						let img =
+							RgbaImage::from_vec(placement.width, placement.height, data).unwrap();
+						(Cow::Owned(img), pos)
 					} else {
-						recty = Some(new_rect);
-					}
-
-					Some((img.0.clone(), pos.as_i64vec2(), z_object.0))
-				} else if is_path {
-					let Some((data, placement)) = path_param.to_image(ent_obj) else {
-						warn!("Invalid Path");
 						return None;
 					};
-					let pos = IVec2::new(placement.left, placement.top);
-					let size = UVec2::new(placement.width, placement.height);
+					let size = UVec2::new(img.width(), img.height());
 					let new_rect = IRect::from_corners(pos, pos + size.as_ivec2());
 					if let Some(rected) = &mut recty {
 						*rected = rected.union(new_rect);
 					} else {
 						recty = Some(new_rect);
 					}
-					Some((
-						RgbaImage::from_vec(size.x, size.y, data).unwrap(),
-						pos.as_i64vec2(),
-						z_object.0,
-					))
-				} else {
-					None
-				}
-			})
+					Some(CompositeLayer {
+						img,
+						pos,
+						z: z_object.0,
+						opacity: opacity.0,
+						blend: blend.0,
+					})
+				},
+			)
 			.collect::<Vec<_>>();
-		q.sort_by_key(|a| a.2);
-
-		if let Some(rected) = recty {
-			let size = rected.size().as_uvec2();
-			let mut imged = RgbaImage::new(size.x, size.y);
-			q.into_iter().for_each(|(img, pos, _)| {
-				let I64Vec2 { x, y } = pos - rected.min.as_i64vec2();
-				overlay(&mut imged, &img, x, y);
-			});
-			return Some(imged);
-		}
-		None
+
+		recty.map(|rected| compose_layers(layers, rected))
 	};
 
 	if action.just_pressed(&ToolsStandAlone::ExportCanvas) {
@@ -334,29 +336,49 @@ pub fn copy_or_canvas(
 			.detach();
 	}
 	if action.just_pressed(&ToolsStandAlone::Copy) {
-		let Some(imged) = grouped_img() else {
+		// Masked pixels first, whole objects otherwise
+		let op_masked = pixel_mask.0.as_ref().and_then(|mask| {
+			let (_, Some((img, _)), ..) = query_object.get(mask.ent).ok()? else {
+				return None;
+			};
+			let bits = pixel_mask.bits_for(mask.ent, img.dimensions().into())?;
+			masked_pixels(img, &bits).map(|(_, picked)| picked)
+		});
+		let Some(imged) = op_masked.or_else(grouped_img) else {
 			return;
 		};
-		let mut clip_board = Clipboard::new().unwrap();
-		let to_clip_img = ImageData {
-			width: imged.width() as usize,
-			height: imged.height() as usize,
-			bytes: imged.to_vec().into(),
-		};
-		clip_board.set_image(to_clip_img).unwrap();
+		image_to_clipboard(&imged);
 	}
 }
 
+fn image_to_clipboard(imged: &RgbaImage) {
+	let mut clip_board = Clipboard::new().unwrap();
+	let to_clip_img = ImageData {
+		width: imged.width() as usize,
+		height: imged.height() as usize,
+		bytes: imged.to_vec().into(),
+	};
+	clip_board.set_image(to_clip_img).unwrap();
+}
+
 pub fn export_svg(
-	query_object: Query<(
-		Entity,
-		Option<(&ProcessImage, &ObjectPosition)>,
-		Has<ObjectPath>,
-		&ObjectZLayer,
-	)>,
+	query_object: Query<
+		(
+			Entity,
+			&SharingName,
+			&ObjectZLayer,
+			&ShownOpacity,
+			&ObjectBlend,
+			Option<(&ProcessImage, &ObjectPosition)>,
+			Option<(&TextValue, &ObjectPosition, &Text, &TextLayoutInfo)>,
+			Has<ObjectPath>,
+		),
+		With<ObjectWorld>,
+	>,
 	query_point: Query<&Parent, (With<ObjectPoint>, With<ObjectWorld>)>,
 	query_user: Query<(&ActionState<ToolsStandAlone>, &SelectedObject), With<MainUser>>,
 	path_param: PathParam,
+	group_param: GroupParam,
 ) {
 	let Some(docs_dir) = source_to_docs() else {
 		return;
@@ -423,33 +445,96 @@ pub fn export_svg(
 	// 	None
 	// };
 
-	if action.just_pressed(&ToolsStandAlone::ExportSvgRelative) {
-		let point_path = query_point
-			.iter_many(selected_obj.group.iter())
-			.map(|parent| parent.get())
-			.collect::<HashSet<Entity>>();
+	let is_relative = action.just_pressed(&ToolsStandAlone::ExportSvgRelative);
+	let is_absolute = action.just_pressed(&ToolsStandAlone::ExportSvgAbsolute);
+	if !is_relative && !is_absolute {
+		return;
+	}
+	// Selected group export everything inside
+	let expanded = group_param.expand(selected_obj.group.iter().copied());
+	let point_path = query_point
+		.iter_many(expanded.iter())
+		.map(|parent| parent.get())
+		.collect::<HashSet<Entity>>();
+	let mut selected = query_object
+		.iter_many(expanded.iter().chain(point_path.iter()))
+		.collect::<Vec<_>>();
+	selected.sort_by_key(|(_, _, z_object, ..)| z_object.0);
+	selected.dedup_by_key(|(ent_obj, ..)| *ent_obj);
+
+	let rdio_svg_dir = docs_dir.join(RDIO_IN_SAVE_DISK).join("SVG");
+	let save_svg = |file_name: String, document: svg::Document| {
+		let path = rdio_svg_dir.join(file_name);
+		IoTaskPool::get()
+			.spawn(async move {
+				svg::save(path, &document)
+					.unwrap_or_else(|e| error!("Failed to save SVG: {:?}", e));
+			})
+			.detach();
+	};
 
-		query_object
-			.iter_many(selected_obj.group.iter().chain(point_path.iter()))
-			.for_each(|(ent_obj, op_process_img, is_path, z_object)| {
-				if is_path {
-					let Some(document) = path_param.to_svg(ent_obj) else {
-						return;
-					};
-					let path = docs_dir
-						.join(RDIO_IN_SAVE_DISK)
-						.join("SVG")
-						.join("Canvas.svg");
-					IoTaskPool::get()
-						.spawn(async move {
-							svg::save(path, &document)
-								.unwrap_or_else(|e| error!("Failed to save SVG: {:?}", e));
-						})
-						.detach();
+	if is_relative {
+		selected.iter().filter(|(.., is_path)| *is_path).for_each(
+			|(ent_obj, name, _, opacity, ..)| {
+				let Some(document) = path_param.to_svg(*ent_obj, opacity.0) else {
+					warn!("Invalid Path");
+					return;
+				};
+				let mut svg_file = name.to_string();
+				if !svg_file.ends_with(".svg") {
+					svg_file.push_str(".svg");
 				}
-			});
+				save_svg(svg_file, document);
+			},
+		);
+	}
+	if is_absolute {
+		// Whole board in z order, images and texts along paths
+		let elements = selected
+			.iter()
+			.filter_map(
+				|(ent_obj, _, _, opacity, blend, op_img, op_text, is_path)| {
+					let mut opacity = opacity.0;
+					let (element, bound): (Box<dyn svg::Node>, IRect) =
+						if let Some((img, obj_pos)) = op_img {
+							let (mut image, bound) = img.to_svg(obj_pos.0)?;
+							match blend.to_css() {
+								// Opacity on image itself, group around it would blend only inside
+								Some(mode) => {
+									image = image
+										.set("opacity", opacity as f32 / 100.0)
+										.set("style", format!("mix-blend-mode:{}", mode));
+									opacity = 100;
+								},
+								None if blend.0 != DrawType::Normal => {
+									warn!("Blend of image has no SVG equal, exported as normal");
+								},
+								None => {},
+							}
+							(Box::new(image), bound)
+						} else if let Some((text, obj_pos, shown, layout)) = op_text {
+							let style = shown
+								.sections
+								.first()
+								.map(|section| section.style.clone())
+								.unwrap_or_default();
+							let (text, bound) = text.to_svg(obj_pos.0, &style, layout.logical_size);
+							(Box::new(text), bound)
+						} else if *is_path {
+							let (path, bound) = path_param.to_svg_path(*ent_obj)?;
+							(Box::new(path), bound)
+						} else {
+							return None;
+						};
+					Some((element, bound, opacity))
+				},
+			)
+			.collect::<Vec<_>>();
+		if elements.is_empty() {
+			return;
+		}
+		save_svg("Canvas.svg".to_owned(), svg_document(elements, IVec2::ZERO));
 	}
-	if action.just_pressed(&ToolsStandAlone::ExportSvgAbsolute) {}
 }
 
 pub(super) fn detect_file_drop(
@@ -477,6 +562,27 @@ pub(super) fn detect_file_drop(
 			let mut cmd_queue = CommandQueue::default();
 
 			cmd_queue.push(move |world: &mut World| {
+				if file_path
+					.extension()
+					.is_some_and(|extension| extension.eq_ignore_ascii_case(RDIO_EXTENSION))
+				{
+					match std::fs::read(&file_path) {
+						Ok(bytes) => document::load_rdio_file(world, file_path, &bytes),
+						Err(e) => error!("Failed to read Rdio: {:?}", e),
+					}
+					return;
+				}
+				if file_path
+					.extension()
+					.is_some_and(|extension| extension.eq_ignore_ascii_case(SVG_EXTENSION))
+				{
+					match std::fs::read(&file_path) {
+						Ok(bytes) => document::load_svg_file(world, file_path, &bytes),
+						Err(e) => error!("Failed to read Svg: {:?}", e),
+					}
+					world.entity_mut(ent_file).despawn_recursive();
+					return;
+				}
 				let name = file_path
 					.file_name()
 					.unwrap()
@@ -870,6 +976,70 @@ pub(super) fn pencel_line(
 	last_draw.set_if_neq(LastDrawPos(real_world_ray));
 }
 
+pub(super) fn bucket_click(
+	query_user: Query<(&CursorPos, &SelectedObject, &ActionState<SettingsAction>), With<MainUser>>,
+	mut client: ResMut<ConnectionManager>,
+) {
+	let Ok((cur_pos, selected_obj, action)) = query_user.get_single() else {
+		return;
+	};
+	if !action.just_pressed(&SettingsAction::Primary) || selected_obj.group.is_empty() {
+		return;
+	}
+	client
+		.send_message_to_target::<MainChannel, BucketFill>(
+			&mut BucketFill(*cur_pos),
+			NetworkTarget::All,
+		)
+		.unwrap_or_else(|e| {
+			error!("Fail to send message: {:?}", e);
+		});
+}
+
+/// Drag box of shape tool, shown to everyone as selecting box
+pub(super) fn drag_shape(
+	mut query_user: Query<
+		(
+			&mut BeginSelectPoint,
+			&CursorPos,
+			&ActionState<SettingsAction>,
+		),
+		With<MainUser>,
+	>,
+	editor_tools: Res<State<EditorTools>>,
+	corners: Res<ShapeCorners>,
+	mut client: ResMut<ConnectionManager>,
+) {
+	let Ok((mut pin_point, cur_pos, action)) = query_user.get_single_mut() else {
+		return;
+	};
+	let Some(kind) = editor_tools.shape(&corners) else {
+		return;
+	};
+	if action.just_pressed(&SettingsAction::Primary) {
+		pin_point.0 = Some(cur_pos.xy());
+	}
+	if !action.just_released(&SettingsAction::Primary) {
+		return;
+	}
+	let Some(from) = pin_point.0.take() else {
+		return;
+	};
+	let to = if action.pressed(&SettingsAction::Shift) {
+		ShapeKind::constrain(from, cur_pos.xy())
+	} else {
+		cur_pos.xy()
+	};
+	client
+		.send_message_to_target::<MainChannel, NewShapeNet>(
+			&mut NewShapeNet { kind, from, to },
+			NetworkTarget::All,
+		)
+		.unwrap_or_else(|e| {
+			error!("Fail to send message: {:?}", e);
+		});
+}
+
 pub(super) fn hide_object(
 	query_user: Query<(&SelectedObject, &ActionState<ToolsStandAlone>), With<MainUser>>,
 	mut query_object: Query<&mut Visibility, With<ObjectWorld>>,
@@ -893,6 +1063,402 @@ pub(super) fn hide_object(
 	});
 }
 
+/// Hiding group hide every object inside it
+pub(super) fn group_visibility(
+	query_group: Query<(Entity, Ref<Visibility>), With<ObjectGroup>>,
+	mut query_object: Query<&mut Visibility, (With<ObjectWorld>, Without<ObjectGroup>)>,
+	group_param: GroupParam,
+) {
+	query_group
+		.iter()
+		.filter(|(_, visibility)| visibility.is_changed() && !visibility.is_added())
+		.for_each(|(ent_group, group_visibility)| {
+			let mut many_obj = query_object.iter_many_mut(group_param.descendants(ent_group));
+			while let Some(mut visibility) = many_obj.fetch_next() {
+				*visibility = *group_visibility;
+			}
+		});
+}
+
+/// Shortcut of [VerifyAction::Group]
+pub(super) fn new_group(
+	mut query_user: Query<
+		(
+			&ActionState<ToolsStandAlone>,
+			&mut ActionState<VerifyAction>,
+		),
+		With<MainUser>,
+	>,
+) {
+	let Ok((action, mut action_verify)) = query_user.get_single_mut() else {
+		return;
+	};
+	if action.just_pressed(&ToolsStandAlone::NewGroup) {
+		action_verify.reset(&VerifyAction::Group);
+		action_verify.press(&VerifyAction::Group);
+	} else if action.just_released(&ToolsStandAlone::NewGroup) {
+		action_verify.release(&VerifyAction::Group);
+	}
+}
+
+pub(super) fn undo_redo(
+	query_user: Query<&ActionState<ToolsStandAlone>, With<MainUser>>,
+	mut client: ResMut<ConnectionManager>,
+) {
+	let Ok(action) = query_user.get_single() else {
+		return;
+	};
+	let mut bir = if action.just_pressed(&ToolsStandAlone::Undo) {
+		ObjectBirNet::Undo
+	} else if action.just_pressed(&ToolsStandAlone::Redo) {
+		ObjectBirNet::Redo
+	} else {
+		return;
+	};
+	client
+		.send_message::<MainChannel, ObjectBirNet>(&mut bir)
+		.unwrap_or_else(|e| {
+			error!("Fail to send message: {:?}", e);
+		});
+}
+
+/// Drag rectangle over images, Enter send it to server, Escape clear
+pub(super) fn crop_select(
+	mut query_user: Query<
+		(
+			&mut Selection,
+			&CursorPos,
+			&SelectedObject,
+			&ActionState<SettingsAction>,
+		),
+		With<MainUser>,
+	>,
+	mut client: ResMut<ConnectionManager>,
+) {
+	let Ok((mut selection, cur_pos, selected_obj, action)) = query_user.get_single_mut() else {
+		return;
+	};
+	let pixel_pos = cur_pos.xy().round();
+	if action.just_pressed(&SettingsAction::Primary) {
+		*selection = Selection(Some(pixel_pos), None);
+	} else if action.pressed(&SettingsAction::Primary) && selection.0.is_some() {
+		selection.1 = Some(pixel_pos);
+	}
+	if action.just_pressed(&SettingsAction::Escape) {
+		selection.set_if_neq(Selection::default());
+	}
+	if !action.just_pressed(&SettingsAction::Enter) {
+		return;
+	}
+	let (Some(begin), Some(end)) = (selection.0, selection.1) else {
+		return;
+	};
+	if selected_obj.group.is_empty() || Rect::from_corners(begin, end).is_empty() {
+		return;
+	}
+	client
+		.send_message::<MainChannel, CropImageNet>(&mut CropImageNet(begin, end))
+		.unwrap_or_else(|e| {
+			error!("Fail to send message: {:?}", e);
+		});
+	*selection = Selection::default();
+}
+
+pub(super) fn clear_crop_select(mut query_user: Query<&mut Selection, With<MainUser>>) {
+	if let Ok(mut selection) = query_user.get_single_mut() {
+		selection.set_if_neq(Selection::default());
+	}
+}
+
+const ELLIPSE_SEGMENT: usize = 64;
+
+/// World outline of finished drag
+fn select_outline(shape: SelectShape, begin: Vec2, end: Vec2, lasso: &[Vec2]) -> Vec<Vec2> {
+	let rect = Rect::from_corners(begin, end);
+	match shape {
+		SelectShape::Rectangle => vec![
+			rect.min,
+			Vec2::new(rect.max.x, rect.min.y),
+			rect.max,
+			Vec2::new(rect.min.x, rect.max.y),
+		],
+		SelectShape::Ellipse => (0..ELLIPSE_SEGMENT)
+			.map(|n| {
+				let angle = n as f32 / ELLIPSE_SEGMENT as f32 * TAU;
+				rect.center() + rect.half_size() * Vec2::from_angle(angle)
+			})
+			.collect(),
+		SelectShape::Lasso => lasso.to_vec(),
+	}
+}
+
+fn spawn_floating(
+	cmd: &mut Commands,
+	image_assets: &mut Assets<Image>,
+	ent_obj: Entity,
+	min: IVec2,
+	img: RgbaImage,
+	lifted_at: Option<IVec2>,
+) {
+	let texture = image_assets.rgba8_image(img.as_raw().clone(), img.dimensions().into());
+	cmd.spawn((
+		SpriteBundle {
+			transform: Transform::from_xyz(min.x as f32, -min.y as f32, 0.5),
+			sprite: Sprite {
+				anchor: Anchor::TopLeft,
+				..default()
+			},
+			texture,
+			..default()
+		},
+		FloatingPixels {
+			ent: ent_obj,
+			min,
+			img,
+			lifted_at,
+		},
+		Pickable::IGNORE,
+	))
+	.set_parent(ent_obj);
+}
+
+fn anchor_floating(
+	cmd: &mut Commands,
+	client: &mut ConnectionManager,
+	ent_floating: Entity,
+	floating: &FloatingPixels,
+) {
+	client
+		.send_message::<MainChannel, PixelSelectNet>(&mut PixelSelectNet::Anchor {
+			ent: floating.ent,
+			min: floating.min,
+			size: floating.img.dimensions().into(),
+			data: DataHold::to_compress(floating.img.as_raw()),
+		})
+		.unwrap_or_else(|e| {
+			error!("Fail to send message: {:?}", e);
+		});
+	cmd.entity(ent_floating).despawn_recursive();
+}
+
+/// Drag over main selected image to mask its pixels, Shift add to the mask.
+/// While floating, drag inside move it, click outside or Enter anchor it
+pub(super) fn pixel_select(
+	mut cmd: Commands,
+	mut gizmos: Gizmos,
+	mut lasso: Local<Vec<Vec2>>,
+	mut grab: Local<Option<IVec2>>,
+	mut query_user: Query<
+		(
+			&mut Selection,
+			&mut PixelMask,
+			&CursorPos,
+			&SelectedObject,
+			&SelectShape,
+			&PaintInk,
+			&ActionState<SettingsAction>,
+		),
+		With<MainUser>,
+	>,
+	query_object: Query<(&ObjectPosition, &ProcessImage), With<ObjectImage>>,
+	mut query_floating: Query<(Entity, &mut FloatingPixels, &mut Transform)>,
+	mut client: ResMut<ConnectionManager>,
+	time: Res<Time>,
+) {
+	let Ok((mut selection, mut pixel_mask, cur_pos, selected_obj, select_shape, paint, action)) =
+		query_user.get_single_mut()
+	else {
+		return;
+	};
+
+	if let Ok((ent_floating, mut floating, mut transform)) = query_floating.get_single_mut() {
+		let Ok((obj_pos, _)) = query_object.get(floating.ent) else {
+			return;
+		};
+		// Cut pixels go back where they were, pasted one is dropped
+		if action.just_pressed(&SettingsAction::Escape) {
+			match floating.lifted_at {
+				Some(lifted_at) => {
+					floating.min = lifted_at;
+					anchor_floating(&mut cmd, &mut client, ent_floating, &floating);
+				},
+				None => cmd.entity(ent_floating).despawn_recursive(),
+			}
+			return;
+		}
+		let pixel = cur_pos.pixel_to_img(obj_pos.0).as_ivec2();
+		let size = UVec2::from(floating.img.dimensions()).as_ivec2();
+		let is_inside = (pixel.cmpge(floating.min) & pixel.cmplt(floating.min + size)).all();
+		if action.just_pressed(&SettingsAction::Enter)
+			|| (action.just_pressed(&SettingsAction::Primary) && !is_inside)
+		{
+			anchor_floating(&mut cmd, &mut client, ent_floating, &floating);
+			return;
+		}
+		if action.just_pressed(&SettingsAction::Primary) {
+			*grab = Some(pixel - floating.min);
+		} else if !action.pressed(&SettingsAction::Primary) {
+			*grab = None;
+		}
+		if let Some(grabbed) = *grab {
+			floating.min = pixel - grabbed;
+			transform.translation.x = floating.min.x as f32;
+			transform.translation.y = -floating.min.y as f32;
+		}
+		let top_left = obj_pos.0 + (floating.min * IVec2::new(1, -1)).as_vec2();
+		let bottom_right = top_left + (size * IVec2::new(1, -1)).as_vec2();
+		marching_ants(
+			&mut gizmos,
+			&[
+				top_left,
+				Vec2::new(bottom_right.x, top_left.y),
+				bottom_right,
+				Vec2::new(top_left.x, bottom_right.y),
+			],
+			time.elapsed_seconds(),
+		);
+		return;
+	}
+
+	if action.just_pressed(&SettingsAction::Escape) {
+		pixel_mask.set_if_neq(PixelMask::default());
+		selection.set_if_neq(Selection::default());
+		lasso.clear();
+		return;
+	}
+	let world_pos = cur_pos.xy().round();
+	if action.just_pressed(&SettingsAction::Primary) {
+		*selection = Selection(Some(world_pos), None);
+		*lasso = vec![world_pos];
+	} else if action.pressed(&SettingsAction::Primary) && selection.0.is_some() {
+		selection.1 = Some(world_pos);
+		if lasso.last().is_some_and(|last| *last != world_pos) {
+			lasso.push(world_pos);
+		}
+	}
+	let color = paint.0.with_alpha(1.0);
+	match (select_shape, selection.0, selection.1) {
+		(SelectShape::Lasso, ..) => gizmos.linestrip_2d(lasso.iter().copied(), color),
+		(SelectShape::Ellipse, Some(begin), Some(end)) => {
+			let rect = Rect::from_corners(begin, end);
+			gizmos.ellipse_2d(rect.center(), 0.0, rect.half_size(), color);
+		},
+		_ => {},
+	}
+
+	if !action.just_released(&SettingsAction::Primary) {
+		return;
+	}
+	let Selection(Some(begin), op_end) = std::mem::take(&mut *selection) else {
+		return;
+	};
+	let outline = select_outline(*select_shape, begin, op_end.unwrap_or(begin), &lasso);
+	lasso.clear();
+	let op_mask = selected_obj.single.and_then(|ent_obj| {
+		let (obj_pos, process_img) = query_object.get(ent_obj).ok()?;
+		let pixel_outline = outline
+			.iter()
+			.map(|world| Vec2::new(world.x - obj_pos.0.x, obj_pos.0.y - world.y))
+			.collect();
+		MaskData::from_outline(ent_obj, process_img.dimensions().into(), pixel_outline)
+	});
+	let is_adding = action.pressed(&SettingsAction::Shift);
+	let Some(mask) = op_mask else {
+		// Click without area deselect
+		if !is_adding {
+			pixel_mask.set_if_neq(PixelMask::default());
+		}
+		return;
+	};
+	match &mut pixel_mask.0 {
+		Some(current) if is_adding => current.union(mask),
+		_ => pixel_mask.0 = Some(mask),
+	}
+}
+
+/// Floating selection anchor where it is
+pub(super) fn leave_pixel_select(
+	mut cmd: Commands,
+	mut query_user: Query<&mut Selection, With<MainUser>>,
+	query_floating: Query<(Entity, &FloatingPixels)>,
+	mut client: ResMut<ConnectionManager>,
+) {
+	if let Ok(mut selection) = query_user.get_single_mut() {
+		selection.set_if_neq(Selection::default());
+	}
+	query_floating.iter().for_each(|(ent_floating, floating)| {
+		anchor_floating(&mut cmd, &mut client, ent_floating, floating);
+	});
+}
+
+/// Masked pixels go to clipboard and float, server clear them from image
+pub(super) fn cut_pixels(
+	mut cmd: Commands,
+	mut image_assets: ResMut<Assets<Image>>,
+	mut query_user: Query<(&ActionState<ToolsStandAlone>, &mut PixelMask, &UserId), With<MainUser>>,
+	query_object: Query<(&ProcessImage, &PixelLock, &AlphaLock, &ObjectAccess), With<ObjectImage>>,
+	query_floating: Query<(), With<FloatingPixels>>,
+	mut client: ResMut<ConnectionManager>,
+) {
+	let Ok((action, mut pixel_mask, user_id)) = query_user.get_single_mut() else {
+		return;
+	};
+	if !action.just_pressed(&ToolsStandAlone::Cut) || !query_floating.is_empty() {
+		return;
+	}
+	let Some(mask) = pixel_mask.0.clone() else {
+		return;
+	};
+	let Ok((process_img, pixel_lock, alpha_lock, access)) = query_object.get(mask.ent) else {
+		return;
+	};
+	if !access.targets(&user_id.0)
+		|| pixel_lock.contains(&user_id.0)
+		|| alpha_lock.contains(&user_id.0)
+	{
+		return;
+	}
+	let Some((min, picked)) = pixel_mask
+		.bits_for(mask.ent, process_img.dimensions().into())
+		.and_then(|bits| masked_pixels(process_img, &bits))
+	else {
+		return;
+	};
+	image_to_clipboard(&picked);
+	let ent_obj = mask.ent;
+	client
+		.send_message::<MainChannel, PixelSelectNet>(&mut PixelSelectNet::Lift(mask))
+		.unwrap_or_else(|e| {
+			error!("Fail to send message: {:?}", e);
+		});
+	let min = min.as_ivec2();
+	spawn_floating(&mut cmd, &mut image_assets, ent_obj, min, picked, Some(min));
+	pixel_mask.0 = None;
+}
+
+pub(super) fn image_transform(
+	query_user: Query<(&ActionState<ToolsStandAlone>, &RotateSettings), With<MainUser>>,
+	mut client: ResMut<ConnectionManager>,
+) {
+	let Ok((action, rotate)) = query_user.get_single() else {
+		return;
+	};
+	let mut transform = if action.just_pressed(&ToolsStandAlone::FlipHorizontal) {
+		ImageTransformNet::FlipHorizontal
+	} else if action.just_pressed(&ToolsStandAlone::FlipVertical) {
+		ImageTransformNet::FlipVertical
+	} else if action.just_pressed(&ToolsStandAlone::Rotate) {
+		ImageTransformNet::Rotate(rotate.degree, rotate.filter)
+	} else {
+		return;
+	};
+	client
+		.send_message::<MainChannel, ImageTransformNet>(&mut transform)
+		.unwrap_or_else(|e| {
+			error!("Fail to send message: {:?}", e);
+		});
+}
+
 pub(super) fn color_swap(
 	mut query_user: Query<(&ActionState<ToolsStandAlone>, &mut PaintInk), With<MainUser>>,
 ) {
@@ -902,9 +1468,10 @@ pub(super) fn color_swap(
 	}
 }
 
-pub(super) fn receive_draw_pen(
+/// Server verified action, run on its user
+pub(super) fn receive_verified<T: Event + Copy>(
 	mut cmd: Commands,
-	mut events: EventReader<MessageEvent<ToClientEntDataEvent<PenDraw>>>,
+	mut events: EventReader<MessageEvent<ToClientEntDataEvent<T>>>,
 ) {
 	events.read().for_each(|event| {
 		let msg = event.message();
@@ -953,6 +1520,34 @@ pub(super) fn receive_image_data(
 	});
 }
 
+/// Image still waiting for whole data ignore the patch
+pub(super) fn receive_image_patch(
+	mut events: EventReader<MessageEvent<ReceiveImagePatch>>,
+	mut query_object: Query<&mut ProcessImage, (With<ObjectImage>, Without<PendingImage>)>,
+) {
+	events.read().for_each(|event| {
+		let rec_patch = event.message();
+		let Ok(mut process_img) = query_object.get_mut(rec_patch.ent) else {
+			return;
+		};
+		let Some(patch) = rec_patch
+			.data()
+			.uncompress()
+			.ok()
+			.and_then(|data| RgbaImage::from_vec(rec_patch.size.x, rec_patch.size.y, data))
+		else {
+			error!("Invalid image patch data");
+			return;
+		};
+		imageops::replace(
+			&mut process_img.0,
+			&patch,
+			rec_patch.min.x as i64,
+			rec_patch.min.y as i64,
+		);
+	});
+}
+
 pub(super) fn send_action_net(
 	mut client: ResMut<ConnectionManager>,
 	mut action_diff_events: EventReader<ActionDiffEvent<VerifyAction>>,
@@ -1115,12 +1710,17 @@ pub(super) fn pen_marker(
 		});
 }
 
+/// Float on main selected image while selecting pixels, new image otherwise
 pub(super) fn paste_from_clip_board(
-	query_user: Query<&ActionState<ToolsStandAlone>, With<MainUser>>,
+	query_user: Query<(&ActionState<ToolsStandAlone>, &SelectedObject, &PixelMask), With<MainUser>>,
+	query_object: Query<&ProcessImage, With<ObjectImage>>,
+	query_floating: Query<(), With<FloatingPixels>>,
+	editor_tools: Res<State<EditorTools>>,
+	mut image_assets: ResMut<Assets<Image>>,
 	mut client: ResMut<ConnectionManager>,
 	mut cmd: Commands,
 ) {
-	let Ok(action) = query_user.get_single() else {
+	let Ok((action, selected_obj, pixel_mask)) = query_user.get_single() else {
 		return;
 	};
 	if !action.just_pressed(&ToolsStandAlone::Paste) {
@@ -1137,7 +1737,7 @@ pub(super) fn paste_from_clip_board(
 		return;
 	};
 
-	let size = (width as u32, height as u32).into();
+	let size: UVec2 = (width as u32, height as u32).into();
 	let mut img_net = ImageNetwork::new("Pasted Image".to_owned(), &bytes, size);
 
 	let compress_len = img_net.data().len();
@@ -1147,6 +1747,23 @@ pub(super) fn paste_from_clip_board(
 		return;
 	}
 
+	let op_target = selected_obj
+		.single
+		.filter(|_| editor_tools.get() == &EditorTools::Select && query_floating.is_empty())
+		.and_then(|ent_obj| query_object.get(ent_obj).ok().map(|img| (ent_obj, img)));
+	if let Some((ent_obj, process_img)) = op_target {
+		let Some(pasted) = RgbaImage::from_vec(size.x, size.y, bytes.into_owned()) else {
+			return;
+		};
+		let img_size: UVec2 = process_img.dimensions().into();
+		let min = pixel_mask
+			.bits_for(ent_obj, img_size)
+			.and_then(|bits| mask_bound(&bits, img_size))
+			.map_or(IVec2::ZERO, |bound| bound.min.as_ivec2());
+		spawn_floating(&mut cmd, &mut image_assets, ent_obj, min, pasted, None);
+		return;
+	}
+
 	client
 		.send_message::<MainChannel, ImageNetwork>(&mut img_net)
 		.unwrap();
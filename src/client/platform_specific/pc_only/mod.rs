@@ -3,7 +3,7 @@ mod lib;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use self::lib::*;
-use crate::apps::client::*;
+use crate::client::*;
 use rainful_diffolor::{source_to_docs, APP_NAME};
 
 use bevy::{
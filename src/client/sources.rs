@@ -1,4 +1,4 @@
-use crate::{
+use rainful_diffolor::{
 	tool_tip::lib::{ToolName, ToolTip},
 	trait_bevy::ToolPath,
 };
@@ -37,6 +37,47 @@ pub struct EditorInfoComp;
 #[derive(Component)]
 pub struct ObjInfoController;
 
+/// Row of object in layers panel
+#[derive(Component)]
+pub struct LayerRow(pub Entity);
+
+/// Name shown on layer row, to find its text once object is renamed
+#[derive(Component)]
+pub struct LayerLabel(pub String);
+
+/// Layers tab text of main selected object mixing
+#[derive(Component, Clone, Copy)]
+pub enum LayerMixInfo {
+	Opacity,
+	Blend,
+}
+
+/// Stroke tab text of main selected path
+#[derive(Component, Clone, Copy)]
+pub enum StrokeInfo {
+	Width,
+	StartCap,
+	EndCap,
+	Dash,
+	DashOffset,
+}
+
+/// Corners of next polygon and star drawn
+#[derive(Resource, Clone, Copy)]
+pub struct ShapeCorners {
+	pub polygon: u8,
+	pub star: u8,
+}
+
+impl Default for ShapeCorners {
+	fn default() -> Self {
+		Self {
+			polygon: 6,
+			star: 5,
+		}
+	}
+}
+
 #[derive(Component, Reflect, Clone, Copy, EnumIter)]
 pub enum ColorPanelChanger {
 	Red,
@@ -64,6 +105,42 @@ pub struct RGBAhsl {
 #[derive(Component, Default)]
 pub struct MainUser;
 
+/// Used by [ToolsStandAlone::Rotate]
+#[derive(Component)]
+pub struct RotateSettings {
+	pub degree: f32,
+	pub filter: RotateFilter,
+}
+
+impl Default for RotateSettings {
+	fn default() -> Self {
+		Self {
+			degree: 90.0,
+			filter: RotateFilter::Nearest,
+		}
+	}
+}
+
+/// Used by [EditorTools::Select]
+#[derive(Component, Reflect, Default, Clone, Copy, PartialEq)]
+pub enum SelectShape {
+	#[default]
+	Rectangle,
+	Ellipse,
+	Lasso,
+}
+
+/// Pixels lifted by cut or paste, child of the image they float on
+#[derive(Component)]
+pub struct FloatingPixels {
+	pub ent: Entity,
+	/// Top left in image pixel
+	pub min: IVec2,
+	pub img: RgbaImage,
+	/// Where cut lifted it, server already cleared those pixels
+	pub lifted_at: Option<IVec2>,
+}
+
 pub const REPLICATION_GROUP: ReplicationGroup = ReplicationGroup::new_id(1);
 
 #[derive(Bundle, Default)]
@@ -77,6 +154,8 @@ pub struct MainUserBundle {
 	action_one: InputManagerBundle<ToolsStandAlone>,
 	action_normal: InputManagerBundle<SettingsAction>,
 	save: Save,
+	rotate: RotateSettings,
+	select_shape: SelectShape,
 }
 
 impl MainUserBundle {
@@ -117,17 +196,39 @@ pub enum EditorTools {
 	Text,
 	#[reflect(@ToolTip("Create and edit Path"), @ToolPath("gimp-tool-path.png"))]
 	Path,
-	// Rectangle,
-	// Ellipse,
-	// Fill,
-
-	// Crop,
+	#[reflect(@ToolTip("Crop selected images, Enter to apply"), @ToolPath("gimp-selection.png"))]
+	Crop,
+	#[reflect(@ToolTip("Select pixels of image, drag floating selection\nEnter to anchor, Escape to put back"), @ToolPath("gimp-selection.png"))]
+	Select,
+	#[reflect(@ToolTip("Fill similar color with main color"), @ToolPath("gimp-tool-paintbrush.png"))]
+	Fill,
+	#[reflect(@ToolTip("Drag to create rectangle path\nShift for square"), @ToolPath("gimp-tool-path.png"))]
+	Rectangle,
+	#[reflect(@ToolTip("Drag to create ellipse path\nShift for circle"), @ToolPath("gimp-tool-path.png"))]
+	Ellipse,
+	#[reflect(@ToolTip("Drag to create polygon path\nShift for regular"), @ToolPath("gimp-tool-path.png"))]
+	Polygon,
+	#[reflect(@ToolTip("Drag to create star path\nShift for regular"), @ToolPath("gimp-tool-path.png"))]
+	Star,
 }
 
 impl EditorTools {
 	pub(super) fn bind_default() -> InputMap<Self> {
 		InputMap::new([(Self::Pencel, KeyCode::KeyP)])
 	}
+	pub(super) fn shape(&self, corners: &ShapeCorners) -> Option<ShapeKind> {
+		match self {
+			Self::Rectangle => Some(ShapeKind::Rectangle),
+			Self::Ellipse => Some(ShapeKind::Ellipse),
+			Self::Polygon => Some(ShapeKind::Polygon {
+				sides: corners.polygon,
+			}),
+			Self::Star => Some(ShapeKind::Star {
+				points: corners.star,
+			}),
+			_ => None,
+		}
+	}
 }
 
 #[derive(Actionlike, Component, Reflect, Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -166,6 +267,26 @@ impl ToolsStandAlone {
 				ToolsStandAlone::Save,
 				ButtonlikeChord::new([KeyCode::ControlLeft, KeyCode::KeyS]),
 			)
+			.with(
+				Self::SaveAs,
+				ButtonlikeChord::new([KeyCode::ControlLeft, KeyCode::ShiftLeft, KeyCode::KeyS]),
+			)
+			.with(
+				Self::Open,
+				ButtonlikeChord::new([KeyCode::ControlLeft, KeyCode::KeyO]),
+			)
+			.with(
+				Self::Undo,
+				ButtonlikeChord::new([KeyCode::ControlLeft, KeyCode::KeyZ]),
+			)
+			.with(
+				Self::Redo,
+				ButtonlikeChord::new([KeyCode::ControlLeft, KeyCode::KeyY]),
+			)
+			.with(
+				Self::Cut,
+				ButtonlikeChord::new([KeyCode::ControlLeft, KeyCode::KeyX]),
+			)
 			.with(
 				Self::Copy,
 				ButtonlikeChord::new([KeyCode::ControlLeft, KeyCode::KeyC]),
@@ -174,6 +295,10 @@ impl ToolsStandAlone {
 				Self::Paste,
 				ButtonlikeChord::new([KeyCode::ControlLeft, KeyCode::KeyV]),
 			)
+			.with(
+				Self::NewGroup,
+				ButtonlikeChord::new([KeyCode::ControlLeft, KeyCode::KeyG]),
+			)
 	}
 }
 
@@ -254,32 +379,44 @@ fn white_black(v: &mut [[u8; 4]], value: impl Into<i16>) {
 	}
 }
 
-impl PaintInk {
-	pub(super) fn red(&self) -> Vec<u8> {
+/// Color slider ramps of [PaintInk] stroke color
+pub trait PaintRamp {
+	fn red(&self) -> Vec<u8>;
+	fn green(&self) -> Vec<u8>;
+	fn blue(&self) -> Vec<u8>;
+	fn alpha(&self) -> Vec<u8>;
+	fn hue(&self) -> Vec<u8>;
+	fn saturation(&self) -> Vec<u8>;
+	fn lightness(&self) -> Vec<u8>;
+	fn sat_light(&self) -> Vec<u8>;
+}
+
+impl PaintRamp for PaintInk {
+	fn red(&self) -> Vec<u8> {
 		let [red, green, blue, _] = self.0.to_u8_array();
 		let mut v: Vec<[u8; 4]> = (0..=255).map(|r| [r, green, blue, 255]).collect();
 		white_black(&mut v, red);
 		v.concat()
 	}
-	pub(super) fn green(&self) -> Vec<u8> {
+	fn green(&self) -> Vec<u8> {
 		let [red, green, blue, _] = self.0.to_u8_array();
 		let mut v: Vec<[u8; 4]> = (0..=255).map(|g| [red, g, blue, 255]).collect();
 		white_black(&mut v, green);
 		v.concat()
 	}
-	pub(super) fn blue(&self) -> Vec<u8> {
+	fn blue(&self) -> Vec<u8> {
 		let [red, green, blue, _] = self.0.to_u8_array();
 		let mut v: Vec<[u8; 4]> = (0..=255).map(|b| [red, green, b, 255]).collect();
 		white_black(&mut v, blue);
 		v.concat()
 	}
-	pub(super) fn alpha(&self) -> Vec<u8> {
+	fn alpha(&self) -> Vec<u8> {
 		let [red, green, blue, alpha] = self.0.to_u8_array();
 		let mut v: Vec<[u8; 4]> = (0..=255).map(|a| [red, green, blue, a]).collect();
 		white_black(&mut v, alpha);
 		v.concat()
 	}
-	pub(super) fn hue(&self) -> Vec<u8> {
+	fn hue(&self) -> Vec<u8> {
 		let Hsla {
 			hue,
 			saturation,
@@ -296,7 +433,7 @@ impl PaintInk {
 		white_black(&mut v, hue as i16);
 		v.concat()
 	}
-	pub(super) fn saturation(&self) -> Vec<u8> {
+	fn saturation(&self) -> Vec<u8> {
 		let Hsla {
 			hue,
 			saturation,
@@ -313,7 +450,7 @@ impl PaintInk {
 		white_black(&mut v, (saturation * 100.0) as i16);
 		v.concat()
 	}
-	pub(super) fn lightness(&self) -> Vec<u8> {
+	fn lightness(&self) -> Vec<u8> {
 		let Hsla {
 			hue,
 			saturation,
@@ -330,7 +467,7 @@ impl PaintInk {
 		white_black(&mut v, (lightness * 100.0) as i16);
 		v.concat()
 	}
-	pub(super) fn sat_light(&self) -> Vec<u8> {
+	fn sat_light(&self) -> Vec<u8> {
 		let Hsla {
 			hue,
 			mut saturation,
@@ -1,9 +1,9 @@
 use bevy::prelude::*;
 
-use crate::apps::shared::helperful_tool::{hide_ent, unhide_ent};
+use rainful_diffolor::apps::shared::helperful_tool::{hide_ent, unhide_ent};
 
 use super::{DisplayBrush, EditorTools, RdioClientState};
-use crate::apps::client::*;
+use crate::client::*;
 use bevy::render::{primitives::Aabb, render_resource::Extent3d};
 
 pub(super) struct LocalViewPlugin;
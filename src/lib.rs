@@ -1,3 +1,4 @@
+#![allow(clippy::type_complexity)]
 use bevy::{
 	asset::{io::AssetSourceId, AssetPath},
 	prelude::*,
@@ -14,8 +15,18 @@ use std::{
 };
 use winit::window::Icon;
 
+pub mod apps;
+pub mod tool_tip;
+pub mod trait_bevy;
+/// Camera values shared with server, camera plugin stays in app binary
+pub mod camera_control {
+	pub mod lib;
+}
+
 pub const APP_NAME: &str = "Rainful Diffolor";
 pub const RDIO_IN_SAVE_DISK: &str = "Rdio";
+/// Scratch files and server autosave
+pub const TEMPORARY_DIR: &str = "Temporary";
 
 pub struct StartupAppPlugin;
 impl Plugin for StartupAppPlugin {
@@ -115,12 +126,14 @@ fn create_file_holder() {
 		return;
 	};
 	let screen_file = docs_dir.join("ScreenShot");
-	let temp = docs_dir.join("Temporary");
+	let temp = docs_dir.join(TEMPORARY_DIR);
 	let rdio_dir = docs_dir.join(RDIO_IN_SAVE_DISK);
 	let rdio_img_dir = rdio_dir.join("Image");
+	let rdio_svg_dir = rdio_dir.join("SVG");
 	let _ = fs::create_dir_all(screen_file);
 	let _ = fs::create_dir_all(rdio_dir);
 	let _ = fs::create_dir_all(rdio_img_dir);
+	let _ = fs::create_dir_all(rdio_svg_dir);
 	let _ = fs::create_dir_all(temp);
 }
 
@@ -4,10 +4,8 @@
 use bevy::prelude::*;
 use moonshine_save::{load::LoadPlugin, save::SavePlugin};
 use rainful_diffolor::*;
-mod apps;
 mod camera_control;
-mod tool_tip;
-mod trait_bevy;
+mod client;
 use bevy_cosmic_edit::*;
 use bevy_mod_picking::prelude::*;
 
@@ -24,7 +22,7 @@ fn main() -> AppExit {
 			bevy_embedded_assets::EmbeddedAssetPlugin::default(),
 			StartupAppPlugin,
 			DefaultPickingPlugins,
-			apps::ApplicationPlugin,
+			client::ApplicationPlugin,
 			camera_control::CameraPlugin,
 			tool_tip::ToolInfoPlugin,
 			vleue_kinetoscope::AnimatedImagePlugin,
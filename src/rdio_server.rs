@@ -0,0 +1,175 @@
+use bevy::{
+	app::ScheduleRunnerPlugin, hierarchy::HierarchyPlugin, input::InputPlugin, log::LogPlugin,
+	prelude::*, state::app::StatesPlugin,
+};
+use lightyear::{
+	connection::netcode::PRIVATE_KEY_BYTES,
+	prelude::{generate_key, Key},
+};
+use rainful_diffolor::apps::{
+	server::{OwnerPolicy, ServerSettings},
+	shared::{prelude::*, FIXED_TIMESTEP_HZ},
+	DedicatedServerPlugin,
+};
+use std::{
+	fs,
+	io::Write,
+	net::{IpAddr, Ipv4Addr, SocketAddr},
+	path::Path,
+	time::Duration,
+};
+
+const USAGE: &str = "Usage: rdio-server [OPTIONS]
+
+Options:
+    --bind <IP>             Address to listen on [default: 0.0.0.0]
+    --port <PORT>           Game port [default: SERVER_ADDR port]
+    --backend-port <PORT>   Authentication port [default: SERVER_ADDR_BACKEND port]
+    --password <PASSWORD>   Password required to join
+    --admin-password <PASSWORD>
+                            Password granting admin [default: random, printed on start]
+    --owner-policy <keep|host>
+                            Objects of user who left stay orphaned or go to an admin [default: keep]
+    --key-file <PATH>       Hex netcode private key, created when missing [default: random each start]
+    -h, --help              Print help";
+
+struct ServerArgs {
+	addr: SocketAddr,
+	settings: ServerSettings,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<ServerArgs>, String> {
+	let mut addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), SERVER_ADDR.port());
+	let mut settings = ServerSettings::default();
+	while let Some(flag) = args.next() {
+		if flag == "-h" || flag == "--help" {
+			return Ok(None);
+		}
+		let Some(value) = args.next() else {
+			return Err(format!("Missing value for {flag}"));
+		};
+		match flag.as_str() {
+			"--bind" => {
+				let ip = value
+					.parse()
+					.map_err(|_| format!("Invalid address: {value}"))?;
+				addr.set_ip(ip);
+			},
+			"--port" => {
+				let port = value
+					.parse()
+					.map_err(|_| format!("Invalid port: {value}"))?;
+				addr.set_port(port);
+			},
+			"--backend-port" => {
+				settings.backend_port = value
+					.parse()
+					.map_err(|_| format!("Invalid port: {value}"))?;
+			},
+			"--password" => settings.password = Some(check_password(value)?),
+			"--admin-password" => {
+				if value.is_empty() {
+					return Err("Admin password must not be empty".to_owned());
+				}
+				settings.password_admin = Some(check_password(value)?);
+			},
+			"--owner-policy" => {
+				settings.owner_policy = match value.as_str() {
+					"keep" => OwnerPolicy::KeepOrphaned,
+					"host" => OwnerPolicy::TransferToHost,
+					_ => return Err(format!("Invalid owner policy: {value}")),
+				};
+			},
+			"--key-file" => settings.private_key = Some(load_or_create_key(Path::new(&value))?),
+			_ => return Err(format!("Unknown option: {flag}")),
+		}
+	}
+	if addr.port() == settings.backend_port {
+		return Err("Game port and backend port must differ".to_owned());
+	}
+	Ok(Some(ServerArgs { addr, settings }))
+}
+
+/// Client send password length in one byte
+fn check_password(value: String) -> Result<String, String> {
+	if value.len() > u8::MAX as usize {
+		return Err(format!("Password longer than {} bytes", u8::MAX));
+	}
+	Ok(value)
+}
+
+fn load_or_create_key(path: &Path) -> Result<Key, String> {
+	if !path.exists() {
+		let key = generate_key();
+		let hex = key
+			.iter()
+			.map(|byte| format!("{byte:02x}"))
+			.collect::<String>();
+		let mut options = fs::OpenOptions::new();
+		options.write(true).create_new(true);
+		#[cfg(unix)]
+		std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+		options
+			.open(path)
+			.and_then(|mut file| file.write_all(hex.as_bytes()))
+			.map_err(|e| format!("Unable to write key file: {e}"))?;
+		return Ok(key);
+	}
+	#[cfg(unix)]
+	{
+		use std::os::unix::fs::PermissionsExt;
+		let mode = fs::metadata(path)
+			.map_err(|e| format!("Unable to read key file: {e}"))?
+			.permissions()
+			.mode();
+		if mode & 0o077 != 0 {
+			return Err(format!(
+				"Key file readable by others (mode {:o}), run: chmod 600 {}",
+				mode & 0o777,
+				path.display()
+			));
+		}
+	}
+	let hex = fs::read_to_string(path).map_err(|e| format!("Unable to read key file: {e}"))?;
+	let hex = hex.trim();
+	let mut key: Key = [0; PRIVATE_KEY_BYTES];
+	if hex.len() != PRIVATE_KEY_BYTES * 2 {
+		return Err(format!(
+			"Key file must hold {} hex digits",
+			PRIVATE_KEY_BYTES * 2
+		));
+	}
+	for (byte, pair) in key.iter_mut().zip(hex.as_bytes().chunks(2)) {
+		let pair = std::str::from_utf8(pair).map_err(|_| "Key file is not hex".to_owned())?;
+		*byte = u8::from_str_radix(pair, 16).map_err(|_| "Key file is not hex".to_owned())?;
+	}
+	Ok(key)
+}
+
+fn main() -> AppExit {
+	let ServerArgs { addr, settings } = match parse_args(std::env::args().skip(1)) {
+		Ok(Some(server_args)) => server_args,
+		Ok(None) => {
+			println!("{USAGE}");
+			return AppExit::Success;
+		},
+		Err(e) => {
+			eprintln!("{e}\n\n{USAGE}");
+			return AppExit::error();
+		},
+	};
+	App::new()
+		.add_plugins((
+			MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(
+				1.0 / FIXED_TIMESTEP_HZ,
+			))),
+			LogPlugin::default(),
+			AssetPlugin::default(),
+			StatesPlugin,
+			HierarchyPlugin,
+			TransformPlugin,
+			InputPlugin,
+			DedicatedServerPlugin { addr, settings },
+		))
+		.run()
+}
@@ -10,7 +10,7 @@ use crate::{
 	trait_bevy::{BevyColorTheme, FontTypeSize},
 };
 
-pub(super) struct ToolInfoPlugin;
+pub struct ToolInfoPlugin;
 impl Plugin for ToolInfoPlugin {
 	fn build(&self, app: &mut App) {
 		app.init_resource::<ActiveContent>()